@@ -0,0 +1,294 @@
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use godcoin::prelude::*;
+use std::{
+    fs,
+    io::{self, Read},
+    process,
+};
+
+mod tx_builder;
+
+fn main() {
+    godcoin::init().unwrap();
+
+    let in_file = Arg::with_name("in_file")
+        .long("in")
+        .takes_value(true)
+        .help("Reads the hex encoded transaction from the file instead of stdin");
+    let out_file = Arg::with_name("out_file")
+        .long("out")
+        .takes_value(true)
+        .help("Writes the output to the file instead of stdout");
+    let expiry = Arg::with_name("expiry")
+        .long("expiry")
+        .takes_value(true)
+        .default_value("60")
+        .help("The time in seconds when the transaction expires from now");
+
+    let app = App::new("godcoin-tx")
+        .about("Builds and signs GODcoin transactions offline")
+        .version(env!("CARGO_PKG_VERSION"))
+        .setting(AppSettings::VersionlessSubcommands)
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("build-transfer")
+                .about("Builds an unsigned transfer transaction")
+                .arg(expiry.clone())
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The address of the account to transfer from"),
+                )
+                .arg(
+                    Arg::with_name("call_fn")
+                        .long("call-fn")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The function to call in the script"),
+                )
+                .arg(
+                    Arg::with_name("args")
+                        .long("args")
+                        .takes_value(true)
+                        .help("The hex value of the arguments that the script requires"),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The amount of tokens allowed to be transferred"),
+                )
+                .arg(
+                    Arg::with_name("fee")
+                        .long("fee")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The fee to pay for the transaction"),
+                )
+                .arg(
+                    Arg::with_name("memo")
+                        .long("memo")
+                        .takes_value(true)
+                        .help("The memo to attach to the transaction"),
+                )
+                .arg(out_file.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("build-mint")
+                .about("Builds an unsigned mint transaction")
+                .arg(expiry)
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The address of the account receiving the tokens"),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The amount of tokens to be minted"),
+                )
+                .arg(
+                    Arg::with_name("attachment_path")
+                        .long("attachment-path")
+                        .takes_value(true)
+                        .requires("attachment_name")
+                        .help("The path to the attachment for the transaction"),
+                )
+                .arg(
+                    Arg::with_name("attachment_name")
+                        .long("attachment-name")
+                        .takes_value(true)
+                        .help("The name of the attachment"),
+                )
+                .arg(out_file.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("sign")
+                .about("Appends signatures to a transaction in the order the keys are provided")
+                .arg(
+                    Arg::with_name("key")
+                        .long("key")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("The private key WIF to sign with"),
+                )
+                .arg(
+                    Arg::with_name("key_file")
+                        .long("key-file")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("A file containing the private key WIF to sign with"),
+                )
+                .arg(in_file.clone())
+                .arg(out_file.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("decode")
+                .about("Decodes a transaction")
+                .arg(in_file.clone())
+                .arg(out_file.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("txid")
+                .about("Calculates the ID of a transaction")
+                .arg(in_file)
+                .arg(out_file),
+        );
+    let matches = app.get_matches();
+
+    let res = match matches.subcommand() {
+        ("build-transfer", Some(args)) => build_transfer(args),
+        ("build-mint", Some(args)) => build_mint(args),
+        ("sign", Some(args)) => sign(args),
+        ("decode", Some(args)) => decode(args),
+        ("txid", Some(args)) => txid(args),
+        _ => Err("Failed to match subcommand".to_string()),
+    };
+
+    if let Err(e) = res {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn build_transfer(args: &ArgMatches) -> Result<(), String> {
+    let from = AccountId::from_wif(args.value_of("from").unwrap())
+        .map_err(|e| format!("Failed to parse account address: {:?}", e))?;
+    let call_fn = args
+        .value_of("call_fn")
+        .unwrap()
+        .parse()
+        .map_err(|e| format!("Failed to parse call_fn id: {}", e))?;
+    let call_args = match args.value_of("args") {
+        Some(call_args) => tx_builder::hex_to_bytes(call_args)?,
+        None => vec![],
+    };
+    let amount = args
+        .value_of("amount")
+        .unwrap()
+        .parse()
+        .map_err(|_| "Failed to parse asset amount")?;
+    let fee = args
+        .value_of("fee")
+        .unwrap()
+        .parse()
+        .map_err(|_| "Failed to parse asset fee")?;
+    let memo = args.value_of("memo").unwrap_or("").as_bytes();
+
+    let tx = tx_builder::build_transfer_tx(
+        from,
+        call_fn,
+        call_args,
+        amount,
+        fee,
+        memo.into(),
+        parse_expiry(args)?,
+    )?;
+    write_output(args, &tx_builder::encode_tx(&tx))
+}
+
+fn build_mint(args: &ArgMatches) -> Result<(), String> {
+    let to = AccountId::from_wif(args.value_of("to").unwrap())
+        .map_err(|e| format!("Failed to parse account address: {:?}", e))?;
+    let amount = args
+        .value_of("amount")
+        .unwrap()
+        .parse()
+        .map_err(|_| "Failed to parse asset")?;
+    let (attachment, attachment_name) = match args.value_of("attachment_path") {
+        Some(path) => (
+            tx_builder::read_attachment(path)?,
+            args.value_of("attachment_name").unwrap(),
+        ),
+        None => (vec![], ""),
+    };
+
+    let tx = tx_builder::build_mint_tx(
+        to,
+        amount,
+        parse_expiry(args)?,
+        attachment,
+        attachment_name.to_string(),
+    );
+    write_output(args, &tx_builder::encode_tx(&tx))
+}
+
+fn sign(args: &ArgMatches) -> Result<(), String> {
+    let mut wifs: Vec<String> = args
+        .values_of("key")
+        .map(|keys| keys.map(str::to_owned).collect())
+        .unwrap_or_default();
+    if let Some(paths) = args.values_of("key_file") {
+        for path in paths {
+            let wif = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read key file {}: {}", path, e))?;
+            wifs.push(wif.trim().to_owned());
+        }
+    }
+    if wifs.is_empty() {
+        return Err("At least one --key or --key-file must be provided".to_string());
+    }
+
+    let mut tx = tx_builder::decode_tx(&read_input(args)?)?;
+    for wif in wifs {
+        let key = PrivateKey::from_wif(&wif)
+            .map_err(|e| format!("Failed to parse private key: {:?}", e))?;
+        tx.append_sign(&key);
+    }
+    write_output(args, &tx_builder::encode_tx(&tx))
+}
+
+fn decode(args: &ArgMatches) -> Result<(), String> {
+    let tx = tx_builder::decode_tx(&read_input(args)?)?;
+    write_output(args, &format!("{:#?}", tx))
+}
+
+fn txid(args: &ArgMatches) -> Result<(), String> {
+    let tx = tx_builder::decode_tx(&read_input(args)?)?;
+    let txid = tx.calc_txid();
+    write_output(args, &faster_hex::hex_string(txid.as_ref()).unwrap())
+}
+
+fn parse_expiry(args: &ArgMatches) -> Result<u64, String> {
+    let expiry: u64 = args
+        .value_of("expiry")
+        .unwrap()
+        .parse()
+        .map_err(|_| "Failed to parse expiry")?;
+    Ok(godcoin::get_epoch_time() + expiry)
+}
+
+fn read_input(args: &ArgMatches) -> Result<String, String> {
+    let input = match args.value_of("in_file") {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read input file {}: {}", path, e))?,
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| format!("Failed to read stdin: {}", e))?;
+            input
+        }
+    };
+    Ok(input.trim().to_owned())
+}
+
+fn write_output(args: &ArgMatches, output: &str) -> Result<(), String> {
+    match args.value_of("out_file") {
+        Some(path) => fs::write(path, format!("{}\n", output))
+            .map_err(|e| format!("Failed to write output file {}: {}", path, e)),
+        None => {
+            println!("{}", output);
+            Ok(())
+        }
+    }
+}
@@ -5,6 +5,7 @@ use std::{
 };
 
 mod keypair;
+mod tx_builder;
 mod wallet;
 
 use self::keypair::*;
@@ -0,0 +1,98 @@
+use godcoin::{constants::*, prelude::*};
+use std::{
+    fs::File,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+pub fn build_mint_tx(
+    to: AccountId,
+    amount: Asset,
+    expiry: u64,
+    attachment: Vec<u8>,
+    attachment_name: String,
+) -> TxVariant {
+    TxVariant::V0(TxVariantV0::MintTx(MintTx {
+        base: Tx {
+            nonce: gen_nonce(),
+            expiry,
+            fee: Asset::new(0),
+            signature_pairs: vec![],
+        },
+        to,
+        amount,
+        attachment,
+        attachment_name,
+    }))
+}
+
+pub fn build_transfer_tx(
+    from: AccountId,
+    call_fn: u8,
+    args: Vec<u8>,
+    amount: Asset,
+    fee: Asset,
+    memo: Vec<u8>,
+    expiry: u64,
+) -> Result<TxVariant, String> {
+    if memo.len() > MAX_MEMO_BYTE_SIZE {
+        return Err(format!(
+            "Memo exceeds the max byte size {}",
+            MAX_MEMO_BYTE_SIZE
+        ));
+    }
+
+    Ok(TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+        base: Tx {
+            nonce: gen_nonce(),
+            expiry,
+            fee,
+            signature_pairs: vec![],
+        },
+        from,
+        call_fn,
+        args,
+        amount,
+        memo,
+    })))
+}
+
+pub fn read_attachment(path: &str) -> Result<Vec<u8>, String> {
+    let path = Path::new(path);
+    let mut file = File::open(path).map_err(|e| {
+        let cur_dir = std::env::current_dir().unwrap();
+        format!("Failed to open file: {:?} (cwd: {:?})", e, cur_dir)
+    })?;
+    let meta = file
+        .metadata()
+        .map_err(|e| format!("Failed to query file metadata: {:?}", e))?;
+    let mut buf = Vec::with_capacity(meta.len() as usize);
+    file.read_to_end(&mut buf)
+        .map_err(|e| format!("Failed to read file entirely: {:?}", e))?;
+    Ok(buf)
+}
+
+pub fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0; hex.len() / 2];
+    faster_hex::hex_decode(hex.as_bytes(), &mut bytes).map_err(|_| "invalid hex string")?;
+    Ok(bytes)
+}
+
+pub fn decode_tx(hex: &str) -> Result<TxVariant, String> {
+    let tx_bytes = hex_to_bytes(hex)?;
+    let cursor = &mut Cursor::<&[u8]>::new(&tx_bytes);
+    let tx = TxVariant::deserialize(cursor).ok_or("Failed to decode tx")?;
+    Ok(tx)
+}
+
+pub fn encode_tx(tx: &TxVariant) -> String {
+    let mut buf = Vec::with_capacity(4096);
+    tx.serialize(&mut buf);
+    faster_hex::hex_string(&buf).unwrap()
+}
+
+fn gen_nonce() -> u32 {
+    let mut nonce = [0; 4];
+    sodiumoxide::randombytes::randombytes_into(&mut nonce);
+    u32::from_ne_bytes(nonce)
+}
@@ -39,24 +39,49 @@ pub fn build_create_tx(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), Str
         godcoin::get_epoch_time() + expiry
     };
 
-    let fee = args
-        .value_of("fee")
-        .unwrap()
-        .parse()
-        .map_err(|_| "Failed to parse asset for the fee")?;
-
-    let account = {
-        let id = {
-            let mut bytes = [0; 8];
-            sodiumoxide::randombytes::randombytes_into(&mut bytes);
-            AccountId::from_ne_bytes(bytes)
+    let (fee, balance) = {
+        let fee: Option<Asset> = match args.value_of("fee") {
+            Some(fee) => Some(
+                fee.parse()
+                    .map_err(|_| "Failed to parse asset for the fee")?,
+            ),
+            None => None,
+        };
+        let balance: Option<Asset> = match args.value_of("balance") {
+            Some(balance) => Some(
+                balance
+                    .parse()
+                    .map_err(|_| "Failed to parse asset for the balance")?,
+            ),
+            None => None,
         };
 
-        let balance = args
-            .value_of("balance")
-            .unwrap()
-            .parse()
-            .map_err(|_| "Failed to parse asset for the balance")?;
+        match (fee, balance) {
+            (Some(fee), Some(balance)) => (fee, balance),
+            (fee, balance) => {
+                let res = send_rpc_req(wallet, rpc::Request::GetAccountInfo(creator)).map_err(
+                    |e| {
+                        format!(
+                            "Failed to query the creator's fees, provide --fee and --balance explicitly: {}",
+                            e
+                        )
+                    },
+                )?;
+                let (min_fee, min_bal) = min_create_account_fees(res)?;
+                let fee = fee.unwrap_or(min_fee);
+                let balance = balance.unwrap_or(min_bal);
+                println!(
+                    "Using fee {} and balance {}",
+                    fee.to_string(),
+                    balance.to_string()
+                );
+                (fee, balance)
+            }
+        }
+    };
+
+    let account = {
+        let id = AccountId::derive(creator, nonce);
 
         let permissions = {
             let threshold = args
@@ -104,6 +129,17 @@ pub fn build_create_tx(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), Str
     Ok(())
 }
 
+/// Extracts the minimum fee and starting balance required by the creator to create an account
+/// from a `GetAccountInfo` response.
+fn min_create_account_fees(res: Msg) -> Result<(Asset, Asset), String> {
+    match res.body {
+        Body::Response(rpc::Response::GetAccountInfo(info)) => info
+            .create_account_fees()
+            .ok_or_else(|| "Arithmetic overflow computing the account creation fees".to_string()),
+        _ => Err("Failed to query the creator's fees: unexpected response".to_string()),
+    }
+}
+
 pub fn build_update_tx(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
     let nonce = {
         let mut bytes = [0; 4];
@@ -273,3 +309,40 @@ pub fn list(wallet: &mut Wallet, _args: &ArgMatches) -> Result<(), String> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_fees_from_account_info() {
+        let info = AccountInfo {
+            account: Account::create_default(
+                0,
+                Permissions {
+                    threshold: 1,
+                    keys: vec![],
+                },
+            ),
+            net_fee: "0.00100 TEST".parse().unwrap(),
+            account_fee: "0.00005 TEST".parse().unwrap(),
+        };
+        let res = Msg {
+            id: 0,
+            body: Body::Response(rpc::Response::GetAccountInfo(info)),
+        };
+
+        let (fee, balance) = min_create_account_fees(res).unwrap();
+        assert_eq!(fee.to_string(), "0.00210 TEST");
+        assert_eq!(balance.to_string(), "0.00420 TEST");
+    }
+
+    #[test]
+    fn min_fees_from_unexpected_response() {
+        let res = Msg {
+            id: 0,
+            body: Body::Response(rpc::Response::Broadcast),
+        };
+        assert!(min_create_account_fees(res).is_err());
+    }
+}
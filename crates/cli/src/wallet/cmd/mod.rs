@@ -1,17 +1,17 @@
 use super::*;
+use crate::tx_builder;
 use clap::ArgMatches;
 use godcoin::{constants::*, prelude::*};
 use std::{
-    fs::File,
-    io::{Cursor, Read},
-    path::Path,
+    fs,
+    io::{self, Cursor, Write},
 };
 
 #[macro_use]
 pub mod util;
 pub mod account;
 
-use util::{send_print_rpc_req, send_rpc_req};
+use util::{send_print_rpc_req, send_rpc_req, send_rpc_stream_req, RangeProgress};
 
 pub fn create_wallet(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
     let state = wallet.db.state();
@@ -112,42 +112,151 @@ pub fn check_script_size(_wallet: &mut Wallet, args: &ArgMatches) -> Result<(),
 }
 
 pub fn decode_tx(_wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
-    let hex = args.value_of("hex").unwrap();
-    let tx_bytes = hex_to_bytes!(hex)?;
-    let cursor = &mut Cursor::<&[u8]>::new(&tx_bytes);
-    let tx = TxVariant::deserialize(cursor).ok_or("Failed to decode tx")?;
+    let tx = tx_builder::decode_tx(args.value_of("hex").unwrap())?;
     println!("{:#?}", tx);
 
     Ok(())
 }
 
+pub fn decode_block(_wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
+    let block = decode_hex_block(args.value_of("hex").unwrap())?;
+    print!("{}", format_block(&block));
+
+    Ok(())
+}
+
+fn decode_hex_block(hex: &str) -> Result<Block, String> {
+    let bytes = tx_builder::hex_to_bytes(hex)?;
+    let cur = &mut Cursor::<&[u8]>::new(&bytes);
+    Block::deserialize(cur).ok_or_else(|| "Failed to decode block, input may be truncated".into())
+}
+
+fn format_block(block: &Block) -> String {
+    let mut out = String::new();
+    match block.header() {
+        BlockHeader::V0(header) => {
+            out.push_str(&format!("Height: {}\n", header.height));
+            out.push_str(&format!("Timestamp: {}\n", header.timestamp));
+            out.push_str(&format!("Previous hash: {}\n", header.previous_hash));
+            out.push_str(&format!("Receipt root: {}\n", header.receipt_root));
+        }
+    }
+    out.push_str(&format!("Header hash: {}\n", block.calc_header_hash()));
+    match block.signer() {
+        Some(signer) => out.push_str(&format!("Signer: {}\n", signer.pub_key.to_wif())),
+        None => out.push_str("Signer: none\n"),
+    }
+    out.push_str(&format!("Rewards: {}\n", block.rewards().to_string()));
+    out.push_str(&format!("Receipts: {}\n", block.receipts().len()));
+
+    for (index, receipt) in block.receipts().iter().enumerate() {
+        out.push_str(&format!("\nReceipt {}:\n{:#?}\n", index, receipt.tx));
+        out.push_str(&format!("Log: {:#?}\n", receipt.log));
+        let script = match &receipt.tx {
+            TxVariant::V0(tx) => match tx {
+                TxVariantV0::CreateAccountTx(tx) => Some(&tx.account.script),
+                TxVariantV0::UpdateAccountTx(tx) => tx.new_script.as_ref(),
+                _ => None,
+            },
+        };
+        if let Some(script) = script {
+            out.push_str("Script:\n");
+            match script.disassemble() {
+                Ok(ops) => {
+                    for (pos, op) in ops {
+                        out.push_str(&format!(
+                            "  {:04}: {}\n",
+                            pos,
+                            script_builder::op_to_string(&op)
+                        ));
+                    }
+                }
+                Err(e) => out.push_str(&format!("  Failed to disassemble script: {:?}\n", e)),
+            }
+        }
+    }
+    out
+}
+
 pub fn sign_tx(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
     check_unlocked!(wallet);
     let hex = args.value_of("hex").unwrap();
-    let accounts: Vec<&str> = args.values_of("account").unwrap().collect();
+    let keys = account_keys(wallet, args)?;
 
-    let mut tx_bytes = hex_to_bytes!(hex)?;
-    let mut tx = {
-        let cursor = &mut Cursor::<&[u8]>::new(&tx_bytes);
-        TxVariant::deserialize(cursor).ok_or("Failed to decode tx")?
-    };
+    println!("{}", sign_hex_tx(hex, &keys)?);
+    Ok(())
+}
 
-    for account in accounts {
+pub fn sign_batch(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
+    check_unlocked!(wallet);
+    let keys = account_keys(wallet, args)?;
+
+    let input = args.value_of("file").unwrap();
+    let input =
+        fs::read_to_string(input).map_err(|e| format!("Failed to read {}: {}", input, e))?;
+    let batch = sign_hex_batch(&input, &keys);
+    for (line, e) in &batch.failures {
+        println!("Line {}: {}", line, e);
+    }
+
+    let output = args.value_of("out").unwrap();
+    let mut signed = batch.signed.join("\n");
+    if !signed.is_empty() {
+        signed.push('\n');
+    }
+    fs::write(output, signed).map_err(|e| format!("Failed to write {}: {}", output, e))?;
+    println!(
+        "Signed {} of {} transactions",
+        batch.signed.len(),
+        batch.signed.len() + batch.failures.len()
+    );
+    Ok(())
+}
+
+/// Collects the keys of every wallet account provided by the `account` argument.
+fn account_keys(wallet: &Wallet, args: &ArgMatches) -> Result<Vec<KeyPair>, String> {
+    let mut keys = vec![];
+    for account in args.values_of("account").unwrap() {
         let account = wallet
             .db
             .get_account(account)
             .ok_or("Account does not exist")?;
-        for key in account.keys {
-            tx.append_sign(&key);
-        }
+        keys.extend(account.keys);
     }
+    Ok(keys)
+}
 
-    tx_bytes.clear();
-    tx_bytes.reserve(128);
-    tx.serialize(&mut tx_bytes);
-    println!("{}", faster_hex::hex_string(&tx_bytes).unwrap());
+/// Appends a signature from each key to the hex encoded transaction.
+fn sign_hex_tx(hex: &str, keys: &[KeyPair]) -> Result<String, String> {
+    let mut tx = tx_builder::decode_tx(hex)?;
+    for key in keys {
+        tx.append_sign(key);
+    }
+    Ok(tx_builder::encode_tx(&tx))
+}
 
-    Ok(())
+#[derive(Debug, Default)]
+struct SignedBatch {
+    /// Signed transactions in hex format in the order they were read.
+    signed: Vec<String>,
+    /// Line numbers, starting at 1, of the transactions that failed to be signed with the reason.
+    failures: Vec<(usize, String)>,
+}
+
+/// Signs each newline-separated hex transaction in the input. Blank lines are skipped.
+fn sign_hex_batch(input: &str, keys: &[KeyPair]) -> SignedBatch {
+    let mut batch = SignedBatch::default();
+    for (index, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match sign_hex_tx(line, keys) {
+            Ok(tx) => batch.signed.push(tx),
+            Err(e) => batch.failures.push((index + 1, e)),
+        }
+    }
+    batch
 }
 
 pub fn unsign_tx(_wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
@@ -157,42 +266,23 @@ pub fn unsign_tx(_wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String>
         .parse()
         .map_err(|_| "Failed to parse signature position".to_string())?;
 
-    let mut tx_bytes = hex_to_bytes!(args.value_of("hex").unwrap())?;
-    let mut tx = {
-        let cursor = &mut Cursor::<&[u8]>::new(&tx_bytes);
-        TxVariant::deserialize(cursor).ok_or("Failed to decode tx")?
-    };
-
+    let mut tx = tx_builder::decode_tx(args.value_of("hex").unwrap())?;
     if sig_pos < tx.sigs().len() {
         tx.sigs_mut().remove(sig_pos);
     }
 
-    tx_bytes.clear();
-    tx.serialize(&mut tx_bytes);
-    println!("{}", faster_hex::hex_string(&tx_bytes).unwrap());
+    println!("{}", tx_builder::encode_tx(&tx));
 
     Ok(())
 }
 
 pub fn broadcast(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
-    let hex = args.value_of("hex").unwrap();
-    let tx_bytes = hex_to_bytes!(hex)?;
-    let tx = {
-        let cursor = &mut Cursor::<&[u8]>::new(&tx_bytes);
-        TxVariant::deserialize(cursor).ok_or("Failed to decode tx")?
-    };
-
+    let tx = tx_builder::decode_tx(args.value_of("hex").unwrap())?;
     send_print_rpc_req(wallet, rpc::Request::Broadcast(tx));
     Ok(())
 }
 
 pub fn build_mint_tx(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
-    let nonce: u32 = {
-        let mut nonce = [0; 4];
-        sodiumoxide::randombytes::randombytes_into(&mut nonce);
-        u32::from_ne_bytes(nonce)
-    };
-
     let expiry: u64 = {
         let expiry: u64 = args
             .value_of("expiry")
@@ -208,51 +298,38 @@ pub fn build_mint_tx(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), Strin
         .parse()
         .map_err(|_| "Failed to parse asset")?;
 
-    let res = send_rpc_req(wallet, rpc::Request::GetProperties)?;
-    let owner = match res.body {
-        Body::Response(rpc::Response::GetProperties(props)) => props.owner,
-        _ => return Err("Failed to get blockchain properties".to_string()),
-    };
-    let owner_wallet = match owner.as_ref() {
-        TxVariant::V0(owner) => match owner {
-            TxVariantV0::OwnerTx(owner) => &owner.wallet,
-            _ => unreachable!("blockchain properties must be an owner tx"),
+    let to = match args.value_of("to") {
+        Some(to) => match wallet.db.get_account(to) {
+            Some(acc) => acc.id,
+            None => AccountId::from_wif(to)
+                .map_err(|e| format!("Failed to parse account address: {:?}", e))?,
         },
+        None => {
+            let res = send_rpc_req(wallet, rpc::Request::GetProperties)?;
+            let owner = match res.body {
+                Body::Response(rpc::Response::GetProperties(props)) => props.owner,
+                _ => return Err("Failed to get blockchain properties".to_string()),
+            };
+            match owner.as_ref() {
+                TxVariant::V0(owner) => match owner {
+                    TxVariantV0::OwnerTx(owner) => owner.wallet,
+                    _ => unreachable!("blockchain properties must be an owner tx"),
+                },
+            }
+        }
     };
 
     let (attachment, attachment_name) =
         if let Some(attachment_path) = args.value_of("attachment_path") {
-            let path = Path::new(attachment_path);
-            let mut file = File::open(path).map_err(|e| {
-                let cur_dir = std::env::current_dir().unwrap();
-                format!("Failed to open file: {:?} (cwd: {:?})", e, cur_dir)
-            })?;
-            let meta = file
-                .metadata()
-                .map_err(|e| format!("Failed to query file metadata: {:?}", e))?;
-            let mut buf = Vec::with_capacity(meta.len() as usize);
-            file.read_to_end(&mut buf)
-                .map_err(|e| format!("Failed to read file entirely: {:?}", e))?;
-            (buf, args.value_of("attachment_name").unwrap())
+            let attachment = tx_builder::read_attachment(attachment_path)?;
+            (attachment, args.value_of("attachment_name").unwrap())
         } else {
             (vec![], "")
         };
 
-    let mint_tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
-        base: Tx {
-            nonce,
-            expiry,
-            fee: Asset::new(0),
-            signature_pairs: vec![],
-        },
-        to: owner_wallet.clone(),
-        amount,
-        attachment,
-        attachment_name: attachment_name.to_string(),
-    }));
-    let mut buf = Vec::with_capacity(4096);
-    mint_tx.serialize(&mut buf);
-    println!("{}", faster_hex::hex_string(&buf).unwrap());
+    let mint_tx =
+        tx_builder::build_mint_tx(to, amount, expiry, attachment, attachment_name.to_string());
+    println!("{}", tx_builder::encode_tx(&mint_tx));
 
     Ok(())
 }
@@ -260,12 +337,6 @@ pub fn build_mint_tx(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), Strin
 pub fn build_transfer_tx(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
     check_unlocked!(wallet);
 
-    let nonce: u32 = {
-        let mut nonce = [0; 4];
-        sodiumoxide::randombytes::randombytes_into(&mut nonce);
-        u32::from_ne_bytes(nonce)
-    };
-
     let expiry: u64 = {
         let expiry: u64 = args
             .value_of("expiry")
@@ -305,23 +376,16 @@ pub fn build_transfer_tx(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), S
         .map_err(|_| "Failed to parse asset fee")?;
     let memo = args.value_of("memo").unwrap_or("").as_bytes();
 
-    let transfer_tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
-        base: Tx {
-            nonce,
-            expiry,
-            fee,
-            signature_pairs: vec![],
-        },
-        from: from_acc,
+    let transfer_tx = tx_builder::build_transfer_tx(
+        from_acc,
         call_fn,
-        args: call_args,
+        call_args,
         amount,
-        memo: memo.into(),
-    }));
-
-    let mut buf = Vec::with_capacity(4096);
-    transfer_tx.serialize(&mut buf);
-    println!("{}", faster_hex::hex_string(&buf).unwrap());
+        fee,
+        memo.into(),
+        expiry,
+    )?;
+    println!("{}", tx_builder::encode_tx(&transfer_tx));
 
     Ok(())
 }
@@ -341,3 +405,101 @@ pub fn get_block(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
     send_print_rpc_req(wallet, rpc::Request::GetBlock(height));
     Ok(())
 }
+
+pub fn get_block_range(wallet: &mut Wallet, args: &ArgMatches) -> Result<(), String> {
+    let min_height: u64 = args
+        .value_of("min_height")
+        .unwrap()
+        .parse()
+        .map_err(|_| "Failed to parse min height argument".to_string())?;
+    let max_height: u64 = args
+        .value_of("max_height")
+        .unwrap()
+        .parse()
+        .map_err(|_| "Failed to parse max height argument".to_string())?;
+
+    // Progress is written to stderr to keep the blocks on stdout
+    let mut progress = RangeProgress::default();
+    let req = rpc::Request::GetBlockRange(min_height, max_height);
+    let res = send_rpc_stream_req(wallet, req, |res| {
+        if let rpc::Response::GetBlock(block) = res {
+            println!("{:#?}", block);
+        }
+        let done = progress.update(res);
+        eprint!("\r{}", progress);
+        let _ = io::stderr().flush();
+        done
+    });
+    eprintln!();
+    res?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_genesis_block() {
+        let tmp_dir = {
+            let mut num = [0; 8];
+            sodiumoxide::randombytes::randombytes_into(&mut num);
+            std::env::temp_dir().join(format!("godcoin_cli_test_{}", u64::from_be_bytes(num)))
+        };
+        fs::create_dir(&tmp_dir).unwrap();
+        let chain = Blockchain::new(&tmp_dir.join("blklog"), &tmp_dir.join("index"));
+        let info = chain.create_genesis_block(KeyPair::gen());
+        let genesis = chain.get_block(0).unwrap();
+        drop(chain);
+        fs::remove_dir_all(&tmp_dir).unwrap();
+
+        let mut buf = Vec::with_capacity(4096);
+        genesis.serialize(&mut buf);
+        let hex = faster_hex::hex_string(&buf).unwrap();
+
+        let block = decode_hex_block(&hex).unwrap();
+        assert_eq!(&block, genesis.as_ref());
+
+        let out = format_block(&block);
+        assert!(out.starts_with("Height: 0\n"));
+        assert!(out.contains(&format!("Header hash: {}\n", genesis.calc_header_hash())));
+        assert!(out.contains(&format!("Signer: {}\n", info.minter_key.0.to_wif())));
+        assert!(out.contains(&format!("Receipts: {}\n", genesis.receipts().len())));
+        assert!(out.contains(&format!(
+            "  0011: OP_DEFINE\n  0013: OP_ACCOUNTID {}\n  0022: OP_CHECKPERMS\n",
+            info.owner_id.to_wif()
+        )));
+        assert!(out.contains("  0023: OP_DEFINE AccountId Asset\n"));
+
+        // Every truncation of the block fails to decode without panicking
+        for len in (0..hex.len()).step_by(2) {
+            assert!(decode_hex_block(&hex[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn sign_batch_reports_malformed_lines() {
+        let key = KeyPair::gen();
+        let txs: Vec<String> = (0..2)
+            .map(|_| {
+                let tx = tx_builder::build_mint_tx(0, Asset::new(100), 1, vec![], String::new());
+                tx_builder::encode_tx(&tx)
+            })
+            .collect();
+        let input = format!("{}\nnot hex\n\n{}\n", txs[0], txs[1]);
+
+        let batch = sign_hex_batch(&input, std::slice::from_ref(&key));
+        assert_eq!(batch.signed.len(), 2);
+        assert_eq!(batch.failures.len(), 1);
+        assert_eq!(batch.failures[0].0, 2);
+
+        for (signed, unsigned) in batch.signed.iter().zip(&txs) {
+            let mut signed = tx_builder::decode_tx(signed).unwrap();
+            assert_eq!(signed.sigs().len(), 1);
+            assert_eq!(signed.sigs()[0].pub_key, key.0);
+
+            signed.sigs_mut().clear();
+            assert_eq!(&tx_builder::encode_tx(&signed), unsigned);
+        }
+    }
+}
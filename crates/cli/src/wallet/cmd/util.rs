@@ -2,12 +2,52 @@ use crate::Wallet;
 use godcoin::net::*;
 use native_tls::TlsConnector;
 use std::{
-    io::Cursor,
+    error, fmt,
+    io::{self, Cursor},
     net::{SocketAddr, TcpStream, ToSocketAddrs},
     time::Duration,
 };
 use tungstenite::{client, protocol::Message, stream::Stream};
 
+#[derive(Debug)]
+pub enum WalletError {
+    /// The node URL did not resolve to any IPv4 address.
+    NoAddress,
+    /// The TCP connection to the node could not be established.
+    Connect(io::Error),
+    /// The TCP connection to the node was not established in time.
+    Timeout(io::Error),
+    /// The WebSocket handshake with the node failed.
+    Handshake(tungstenite::Error),
+    /// Sending the request or receiving the response over the WebSocket failed.
+    Transport(tungstenite::Error),
+    /// The response received from the node is malformed.
+    Deserialize(io::Error),
+    /// The node processed the request and responded with an error.
+    Server(ErrorKind),
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoAddress => write!(f, "No resolved IPv4 addresses found from host"),
+            Self::Connect(e) | Self::Timeout(e) => write!(f, "Failed to connect to host: {:?}", e),
+            Self::Handshake(e) => write!(f, "Failed to init ws socket: {:?}", e),
+            Self::Transport(e) => write!(f, "Failed to communicate with host: {:?}", e),
+            Self::Deserialize(e) => write!(f, "Failed to deserialize response: {}", e),
+            Self::Server(kind) => write!(f, "Server responded with an error: {:?}", kind),
+        }
+    }
+}
+
+impl error::Error for WalletError {}
+
+impl From<WalletError> for String {
+    fn from(e: WalletError) -> String {
+        e.to_string()
+    }
+}
+
 macro_rules! check_unlocked {
     ($self:expr) => {
         if $self.db.state() != DbState::Unlocked {
@@ -36,7 +76,21 @@ pub fn send_print_rpc_req(wallet: &mut Wallet, body: rpc::Request) {
     }
 }
 
-pub fn send_rpc_req(wallet: &mut Wallet, body: rpc::Request) -> Result<Msg, String> {
+pub fn send_rpc_req(wallet: &mut Wallet, body: rpc::Request) -> Result<Msg, WalletError> {
+    send_rpc_stream_req(wallet, body, |_| true)
+}
+
+/// Sends a request that may be answered with multiple responses, such as `GetBlockRange`. The
+/// `on_response` callback is invoked for every response received and returns whether it is the
+/// final response of the request.
+pub fn send_rpc_stream_req<F>(
+    wallet: &mut Wallet,
+    body: rpc::Request,
+    mut on_response: F,
+) -> Result<Msg, WalletError>
+where
+    F: FnMut(&rpc::Response) -> bool,
+{
     let buf = {
         let req_id = {
             let id = wallet.req_id;
@@ -67,12 +121,17 @@ pub fn send_rpc_req(wallet: &mut Wallet, body: rpc::Request) -> Result<Msg, Stri
                     SocketAddr::V4(_) => break addr,
                     _ => continue,
                 },
-                None => return Err("No resolved IPv4 addresses found from host".to_string()),
+                None => return Err(WalletError::NoAddress),
             }
         };
 
-        let stream = TcpStream::connect_timeout(&addr, Duration::from_secs(3))
-            .map_err(|e| format!("Failed to connect to host: {:?}", e))?;
+        let stream =
+            TcpStream::connect_timeout(&addr, Duration::from_secs(3)).map_err(|e| {
+                match e.kind() {
+                    io::ErrorKind::TimedOut => WalletError::Timeout(e),
+                    _ => WalletError::Connect(e),
+                }
+            })?;
         let stream = match wallet.url.scheme() {
             "ws" => Stream::Plain(stream),
             "wss" => {
@@ -86,33 +145,43 @@ pub fn send_rpc_req(wallet: &mut Wallet, body: rpc::Request) -> Result<Msg, Stri
             _ => panic!("Expected node url scheme to be ws or wss"),
         };
 
-        let (ws, _) = client(wallet.url.clone(), stream)
-            .map_err(|e| format!("Failed to init ws socket: {:?}", e))?;
+        let (ws, _) = client(wallet.url.clone(), stream).map_err(|e| match e {
+            tungstenite::HandshakeError::Failure(e) => WalletError::Handshake(e),
+            tungstenite::HandshakeError::Interrupted(_) => {
+                unreachable!("handshake on a blocking stream cannot be interrupted")
+            }
+        })?;
         ws
     };
-    ws.write_message(Message::Binary(buf)).unwrap();
-    ws.write_pending().unwrap();
+    ws.write_message(Message::Binary(buf))
+        .map_err(WalletError::Transport)?;
+    ws.write_pending().map_err(WalletError::Transport)?;
 
     let msg = loop {
-        let msg = ws.read_message().unwrap();
+        let msg = ws.read_message().map_err(WalletError::Transport)?;
         match msg {
             Message::Binary(res) => {
                 let mut cursor = Cursor::<&[u8]>::new(&res);
-                let msg = Msg::deserialize(&mut cursor)
-                    .map_err(|e| format!("Failed to deserialize response: {}", e))?;
-                match msg.body {
-                    Body::Error(_) | Body::Response(_) => break msg,
+                let msg = Msg::deserialize(&mut cursor).map_err(WalletError::Deserialize)?;
+                let done = match &msg.body {
+                    Body::Error(_) => true,
+                    Body::Response(res) => on_response(res),
                     Body::Ping(nonce) => {
                         let msg = Msg {
                             id: msg.id,
-                            body: Body::Pong(nonce),
+                            body: Body::Pong(*nonce),
                         };
                         let mut buf = Vec::with_capacity(16);
                         msg.serialize(&mut buf);
-                        ws.write_message(Message::Binary(buf)).unwrap();
-                        ws.write_pending().unwrap();
+                        ws.write_message(Message::Binary(buf))
+                            .map_err(WalletError::Transport)?;
+                        ws.write_pending().map_err(WalletError::Transport)?;
+                        false
                     }
-                    _ => continue,
+                    _ => false,
+                };
+                if done {
+                    break msg;
                 }
             }
             _ => continue,
@@ -120,5 +189,92 @@ pub fn send_rpc_req(wallet: &mut Wallet, body: rpc::Request) -> Result<Msg, Stri
     };
     let _ = ws.close(None);
 
-    Ok(msg)
+    check_response(msg)
+}
+
+/// Tracks the number of blocks received while streaming a `GetBlockRange` request.
+#[derive(Debug, Default)]
+pub struct RangeProgress {
+    total: Option<u64>,
+    received: u64,
+}
+
+impl RangeProgress {
+    /// Updates the progress from a block range response, returning whether the range is complete.
+    pub fn update(&mut self, res: &rpc::Response) -> bool {
+        match res {
+            rpc::Response::GetBlockRangeStart { count } => {
+                self.total = Some(*count);
+                false
+            }
+            rpc::Response::GetBlock(_) => {
+                self.received += 1;
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for RangeProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.total {
+            Some(total) => write!(f, "Received {}/{} blocks", self.received, total),
+            None => write!(f, "Received {} blocks", self.received),
+        }
+    }
+}
+
+fn check_response(msg: Msg) -> Result<Msg, WalletError> {
+    match msg.body {
+        Body::Error(kind) => Err(WalletError::Server(kind)),
+        _ => Ok(msg),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_error_maps_to_variant() {
+        let msg = Msg {
+            id: 0,
+            body: Body::Error(ErrorKind::InvalidHeight),
+        };
+        match check_response(msg) {
+            Err(WalletError::Server(ErrorKind::InvalidHeight)) => {}
+            res => panic!("Expected an invalid height server error: {:?}", res),
+        }
+
+        let msg = Msg {
+            id: 0,
+            body: Body::Response(rpc::Response::Broadcast),
+        };
+        assert!(check_response(msg).is_ok());
+    }
+
+    #[test]
+    fn range_progress_total_from_start() {
+        let mut progress = RangeProgress::default();
+        assert_eq!(progress.to_string(), "Received 0 blocks");
+
+        let done = progress.update(&rpc::Response::GetBlockRangeStart { count: 25 });
+        assert!(!done);
+        assert_eq!(progress.to_string(), "Received 0/25 blocks");
+
+        assert!(progress.update(&rpc::Response::GetBlockRange));
+        assert_eq!(progress.to_string(), "Received 0/25 blocks");
+    }
+
+    #[test]
+    fn display_matches_messages() {
+        assert_eq!(
+            WalletError::NoAddress.to_string(),
+            "No resolved IPv4 addresses found from host"
+        );
+        let e = io::Error::from(io::ErrorKind::ConnectionRefused);
+        let expected = format!("Failed to connect to host: {:?}", e);
+        assert_eq!(WalletError::Connect(e).to_string(), expected);
+    }
 }
@@ -234,6 +234,16 @@ impl Wallet {
                             .help("Binary transaction in hex format"),
                     ),
             )
+            .subcommand(
+                SubCommand::with_name("decode_block")
+                    .about("Decodes a block and prints it to console")
+                    .arg(
+                        Arg::with_name("hex")
+                            .required(true)
+                            .takes_value(true)
+                            .help("Binary block in hex format"),
+                    ),
+            )
             .subcommand(
                 SubCommand::with_name("sign_tx")
                     .about("Signs a raw transaction")
@@ -253,6 +263,32 @@ impl Wallet {
                             .help("Account to sign the transaction, accepts multiple"),
                     ),
             )
+            .subcommand(
+                SubCommand::with_name("sign_batch")
+                    .about("Signs a file of newline-separated raw transactions")
+                    .arg(
+                        Arg::with_name("file")
+                            .long("file")
+                            .required(true)
+                            .takes_value(true)
+                            .help("File containing binary transactions in hex format"),
+                    )
+                    .arg(
+                        Arg::with_name("out")
+                            .long("out")
+                            .required(true)
+                            .takes_value(true)
+                            .help("File to write the signed transactions to"),
+                    )
+                    .arg(
+                        Arg::with_name("account")
+                            .long("account")
+                            .required(true)
+                            .takes_value(true)
+                            .multiple(true)
+                            .help("Account to sign the transactions, accepts multiple"),
+                    ),
+            )
             .subcommand(
                 SubCommand::with_name("unsign_tx")
                     .about("Removes a signature from a raw transaction")
@@ -296,8 +332,8 @@ impl Wallet {
                         Arg::with_name("fee")
                             .long("fee")
                             .takes_value(true)
-                            .required(true)
-                            .help("The fee to pay for the transaction"),
+                            .required(false)
+                            .help("The fee to pay for the transaction, queried from the node when omitted"),
                     )
                     .arg(
                         Arg::with_name("creator")
@@ -310,8 +346,8 @@ impl Wallet {
                         Arg::with_name("balance")
                             .long("balance")
                             .takes_value(true)
-                            .required(true)
-                            .help("The balance to be deposited in the new account"),
+                            .required(false)
+                            .help("The balance to be deposited in the new account, queried from the node when omitted"),
                     )
                     .arg(
                         Arg::with_name("script")
@@ -413,6 +449,12 @@ impl Wallet {
                             .required(true)
                             .help("The amount of tokens to be minted"),
                     )
+                    .arg(
+                        Arg::with_name("to")
+                            .long("to")
+                            .takes_value(true)
+                            .help("Wallet account name or address to mint to [default: owner wallet]"),
+                    )
                     .arg(
                         Arg::with_name("attachment_path")
                             .long("attachment-path")
@@ -492,6 +534,22 @@ impl Wallet {
                             .help("The height of the block to retrieve"),
                     ),
             )
+            .subcommand(
+                SubCommand::with_name("get_block_range")
+                    .about("Retrieve a range of blocks from the network")
+                    .arg(
+                        Arg::with_name("min_height")
+                            .required(true)
+                            .takes_value(true)
+                            .help("The height of the first block to retrieve"),
+                    )
+                    .arg(
+                        Arg::with_name("max_height")
+                            .required(true)
+                            .takes_value(true)
+                            .help("The height of the last block to retrieve"),
+                    ),
+            )
             .get_matches_from_safe(args);
 
         match cli {
@@ -511,7 +569,9 @@ impl Wallet {
                 ("args_to_bin", Some(args)) => (true, cmd::args_to_bin(self, args)),
                 ("check_script_size", Some(args)) => (true, cmd::check_script_size(self, args)),
                 ("decode_tx", Some(args)) => (true, cmd::decode_tx(self, args)),
+                ("decode_block", Some(args)) => (true, cmd::decode_block(self, args)),
                 ("sign_tx", Some(args)) => (true, cmd::sign_tx(self, args)),
+                ("sign_batch", Some(args)) => (true, cmd::sign_batch(self, args)),
                 ("unsign_tx", Some(args)) => (true, cmd::unsign_tx(self, args)),
                 ("broadcast", Some(args)) => (true, cmd::broadcast(self, args)),
                 ("build_create_account_tx", Some(args)) => {
@@ -524,6 +584,7 @@ impl Wallet {
                 ("build_transfer_tx", Some(args)) => (true, cmd::build_transfer_tx(self, args)),
                 ("get_properties", Some(args)) => (true, cmd::get_properties(self, args)),
                 ("get_block", Some(args)) => (true, cmd::get_block(self, args)),
+                ("get_block_range", Some(args)) => (true, cmd::get_block_range(self, args)),
                 _ => panic!("No subcommands matched: {:#?}", args),
             },
             Err(e) => (true, Err(format!("{}", e.message))),
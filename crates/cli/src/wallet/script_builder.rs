@@ -126,3 +126,44 @@ pub fn build(ops: &[&str]) -> Result<Script, BuildError> {
         .build()
         .map_err(|total_bytes| BuildError::ScriptSizeOverflow(total_bytes))
 }
+
+/// Formats the operation using the same textual representation accepted by `build`.
+pub fn op_to_string(op: &OpFrame) -> String {
+    match op {
+        OpFrame::OpDefine(args) => {
+            let mut s = "OP_DEFINE".to_string();
+            for arg in args {
+                s.push_str(&format!(" {:?}", arg));
+            }
+            s
+        }
+        OpFrame::OpTransfer => "OP_TRANSFER".to_string(),
+        OpFrame::OpDestroy => "OP_DESTROY".to_string(),
+        OpFrame::False => "OP_FALSE".to_string(),
+        OpFrame::True => "OP_TRUE".to_string(),
+        OpFrame::AccountId(id) => format!("OP_ACCOUNTID {}", id.to_wif()),
+        OpFrame::Asset(asset) => format!("OP_ASSET {}", asset.to_string()),
+        OpFrame::OpLoadAmt => "OP_LOADAMT".to_string(),
+        OpFrame::OpLoadRemAmt => "OP_LOADREMAMT".to_string(),
+        OpFrame::OpAdd => "OP_ADD".to_string(),
+        OpFrame::OpSub => "OP_SUB".to_string(),
+        OpFrame::OpMul => "OP_MUL".to_string(),
+        OpFrame::OpDiv => "OP_DIV".to_string(),
+        OpFrame::OpNot => "OP_NOT".to_string(),
+        OpFrame::OpIf => "OP_IF".to_string(),
+        OpFrame::OpElse => "OP_ELSE".to_string(),
+        OpFrame::OpEndIf => "OP_ENDIF".to_string(),
+        OpFrame::OpReturn => "OP_RETURN".to_string(),
+        OpFrame::OpAbort => "OP_ABORT".to_string(),
+        OpFrame::OpCheckPerms => "OP_CHECKPERMS".to_string(),
+        OpFrame::OpCheckPermsFastFail => "OP_CHECKPERMSFASTFAIL".to_string(),
+        OpFrame::OpCheckMultiPerms(threshold, acc_count) => {
+            format!("OP_CHECKMULTIPERMS {} {}", threshold, acc_count)
+        }
+        OpFrame::OpCheckMultiPermsFastFail(threshold, acc_count) => {
+            format!("OP_CHECKMULTIPERMSFASTFAIL {} {}", threshold, acc_count)
+        }
+        OpFrame::OpCheckTime(time) => format!("OP_CHECKTIME {}", time),
+        OpFrame::OpCheckTimeFastFail(time) => format!("OP_CHECKTIMEFASTFAIL {}", time),
+    }
+}
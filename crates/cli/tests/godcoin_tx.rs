@@ -0,0 +1,124 @@
+use godcoin::prelude::*;
+use std::{
+    io::{Cursor, Write},
+    process::{Command, Stdio},
+};
+
+fn run(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_godcoin-tx"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "godcoin-tx {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
+
+fn decode_hex(hex: &str) -> TxVariant {
+    let mut bytes = vec![0; hex.len() / 2];
+    faster_hex::hex_decode(hex.as_bytes(), &mut bytes).unwrap();
+    TxVariant::deserialize(&mut Cursor::<&[u8]>::new(&bytes)).unwrap()
+}
+
+#[test]
+fn build_sign_decode_transfer() {
+    godcoin::init().unwrap();
+    let keys = [KeyPair::gen(), KeyPair::gen()];
+    let from = 1234u64.to_wif();
+    let amount = Asset::new(100_000).to_string();
+    let fee = Asset::new(25).to_string();
+
+    let unsigned = run(
+        &[
+            "build-transfer",
+            "--from",
+            &*from,
+            "--call-fn",
+            "1",
+            "--args",
+            "0102",
+            "--amount",
+            &amount,
+            "--fee",
+            &fee,
+            "--memo",
+            "hello",
+        ],
+        "",
+    );
+    let tx = decode_hex(&unsigned);
+    assert!(tx.sigs().is_empty());
+    match &tx {
+        TxVariant::V0(TxVariantV0::TransferTx(transfer)) => {
+            assert_eq!(transfer.from, 1234);
+            assert_eq!(transfer.call_fn, 1);
+            assert_eq!(transfer.args, vec![0x01, 0x02]);
+            assert_eq!(transfer.amount, Asset::new(100_000));
+            assert_eq!(transfer.fee, Asset::new(25));
+            assert_eq!(transfer.memo, b"hello".to_vec());
+        }
+        _ => panic!("Expected transfer tx: {:?}", tx),
+    }
+
+    let key_file = std::env::temp_dir().join(format!("godcoin_tx_key_{}", std::process::id()));
+    std::fs::write(&key_file, format!("{}\n", keys[1].1.to_wif())).unwrap();
+    let signed = run(
+        &[
+            "sign",
+            "--key",
+            &*keys[0].1.to_wif(),
+            "--key-file",
+            key_file.to_str().unwrap(),
+        ],
+        &unsigned,
+    );
+    std::fs::remove_file(&key_file).unwrap();
+
+    let signed_tx = decode_hex(&signed);
+    assert!(signed_tx.verify_sigs(&[keys[0].0.clone(), keys[1].0.clone()]));
+    assert_eq!(signed_tx.sigs()[0].pub_key, keys[0].0);
+    assert_eq!(signed_tx.sigs()[1].pub_key, keys[1].0);
+
+    let decoded = run(&["decode"], &signed);
+    assert_eq!(decoded, format!("{:#?}", signed_tx));
+
+    let txid = run(&["txid"], &signed);
+    assert_eq!(
+        txid,
+        faster_hex::hex_string(signed_tx.calc_txid().as_ref()).unwrap()
+    );
+    // Signatures are not part of the txid
+    assert_eq!(run(&["txid"], &unsigned), txid);
+}
+
+#[test]
+fn build_mint() {
+    godcoin::init().unwrap();
+    let to = 42u64.to_wif();
+    let amount = Asset::new(1_000_000).to_string();
+
+    let hex = run(&["build-mint", "--to", &*to, "--amount", &amount], "");
+    match decode_hex(&hex) {
+        TxVariant::V0(TxVariantV0::MintTx(mint)) => {
+            assert_eq!(mint.to, 42);
+            assert_eq!(mint.amount, Asset::new(1_000_000));
+            assert!(mint.attachment.is_empty());
+        }
+        tx => panic!("Expected mint tx: {:?}", tx),
+    }
+}
@@ -1,13 +1,57 @@
 use crate::{
     asset::Asset,
-    crypto::{PublicKey, SigPair},
+    crypto::{DoubleSha256, PublicKey, SigPair},
     script::{Arg, Builder, FnBuilder, OpFrame, Script},
     serializer::*,
 };
-use std::io::{self, Cursor};
+use sodiumoxide::crypto::sign::PUBLICKEYBYTES;
+use std::{
+    convert::TryInto,
+    io::{self, Cursor},
+    mem,
+};
 
 pub type AccountId = u64;
 
+pub trait DeriveAccountId {
+    /// Derives an account ID by hashing the creator's ID and a nonce. The same inputs always
+    /// produce the same ID and distinct pairs are unlikely to collide.
+    fn derive(creator: AccountId, nonce: u32) -> AccountId;
+}
+
+impl DeriveAccountId for AccountId {
+    fn derive(creator: AccountId, nonce: u32) -> AccountId {
+        let mut hasher = DoubleSha256::new();
+        hasher.update(&creator.to_be_bytes());
+        hasher.update(&nonce.to_be_bytes());
+        let digest = hasher.finalize();
+
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&digest.as_ref()[..8]);
+        AccountId::from_be_bytes(bytes)
+    }
+}
+
+pub trait AccountIdBytes: Sized {
+    /// Decodes an account ID from its big-endian address bytes. Returns `None` if the slice is not
+    /// exactly 8 bytes long.
+    fn from_slice(bytes: &[u8]) -> Option<Self>;
+
+    /// Encodes the account ID as its big-endian address bytes.
+    fn to_bytes(&self) -> [u8; mem::size_of::<AccountId>()];
+}
+
+impl AccountIdBytes for AccountId {
+    fn from_slice(bytes: &[u8]) -> Option<AccountId> {
+        let bytes = bytes.try_into().ok()?;
+        Some(AccountId::from_be_bytes(bytes))
+    }
+
+    fn to_bytes(&self) -> [u8; mem::size_of::<AccountId>()] {
+        self.to_be_bytes()
+    }
+}
+
 pub const MAX_PERM_KEYS: u8 = 8;
 pub const IMMUTABLE_ACCOUNT_THRESHOLD: u8 = 0xFF;
 
@@ -40,6 +84,16 @@ impl Account {
         }
     }
 
+    /// Returns the number of bytes written when the account is serialized.
+    pub fn serialized_size(&self) -> usize {
+        mem::size_of::<AccountId>()
+            + var_i64_size(self.balance.amount)
+            + mem::size_of::<u32>()
+            + self.script.len()
+            + self.permissions.serialized_size()
+            + 1
+    }
+
     pub fn serialize(&self, buf: &mut Vec<u8>) {
         buf.push_u64(self.id);
         buf.push_asset(self.balance);
@@ -79,15 +133,14 @@ impl Permissions {
         }
 
         let mut valid_threshold = 0;
-        'sig_loop: for pair in sigs {
-            for key in &self.keys {
-                if key == &pair.pub_key {
-                    if key.verify(data, &pair.signature) {
-                        valid_threshold += 1;
-                        continue 'sig_loop;
-                    } else {
-                        return Err(PermsSigVerifyErr::InvalidSig);
-                    }
+        for (i, pair) in sigs.iter().enumerate() {
+            if let Some(key) = self.keys.iter().find(|key| *key == &pair.pub_key) {
+                if !key.verify(data, &pair.signature) {
+                    return Err(PermsSigVerifyErr::InvalidSig);
+                }
+                // Each key only counts once towards the threshold
+                if !sigs[..i].iter().any(|prev| prev.pub_key == pair.pub_key) {
+                    valid_threshold += 1;
                 }
             }
         }
@@ -124,6 +177,11 @@ impl Permissions {
         true
     }
 
+    /// Returns the number of bytes written when the permissions are serialized.
+    pub fn serialized_size(&self) -> usize {
+        2 + self.keys.len() * PUBLICKEYBYTES
+    }
+
     pub fn serialize(&self, buf: &mut Vec<u8>) {
         buf.push(self.threshold);
         buf.push(self.keys.len() as u8);
@@ -161,6 +219,37 @@ mod tests {
         script::Script,
     };
     use sodiumoxide::crypto::sign;
+    use std::collections::HashSet;
+
+    #[test]
+    fn account_id_bytes_roundtrip() {
+        for &id in &[0, 1, 0x0102_0304_0506_0708, AccountId::max_value()] {
+            let bytes = id.to_bytes();
+            assert_eq!(bytes, id.to_be_bytes());
+            assert_eq!(AccountId::from_slice(&bytes), Some(id));
+        }
+    }
+
+    #[test]
+    fn account_id_from_invalid_length() {
+        assert_eq!(AccountId::from_slice(&[]), None);
+        assert_eq!(AccountId::from_slice(&[0; 7]), None);
+        assert_eq!(AccountId::from_slice(&[0; 9]), None);
+    }
+
+    #[test]
+    fn derive_account_id() {
+        assert_eq!(AccountId::derive(1, 2), AccountId::derive(1, 2));
+        assert_ne!(AccountId::derive(1, 2), AccountId::derive(2, 1));
+        assert_ne!(AccountId::derive(0, 0), 0);
+
+        let mut ids = HashSet::new();
+        for creator in 0..100 {
+            for nonce in 0..100 {
+                assert!(ids.insert(AccountId::derive(creator, nonce)));
+            }
+        }
+    }
 
     #[test]
     fn verify_equal_threshold() {
@@ -311,6 +400,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn verify_sigs_counts_duplicate_key_once() {
+        let (account, keys) = create_dummy_account(2, 4);
+        let mut sigs = Vec::new();
+        let data = "Hello world".as_bytes();
+        sigs.push(keys[0].sign(data));
+        sigs.push(keys[0].sign(data));
+
+        assert_eq!(
+            account.permissions.verify(data, &sigs),
+            Err(PermsSigVerifyErr::InsufficientThreshold)
+        );
+
+        sigs.push(keys[1].sign(data));
+        assert_eq!(account.permissions.verify(data, &sigs), Ok(()));
+    }
+
+    #[test]
+    fn serialized_size_matches_serialization() {
+        let (mut account, _) = create_dummy_account(0, 0);
+        let accounts = {
+            let mut accounts = vec![account.clone()];
+
+            account.balance = "1234567.89012 TEST".parse().unwrap();
+            account.script = Script::new(vec![0xFF; 2048]);
+            accounts.push(account.clone());
+
+            let (mut account, _) = create_dummy_account(MAX_PERM_KEYS, MAX_PERM_KEYS);
+            account.balance = "0.00001 TEST".parse().unwrap();
+            account.destroyed = true;
+            accounts.push(account);
+
+            accounts.push(Account::create_default(
+                u64::max_value(),
+                Permissions {
+                    threshold: IMMUTABLE_ACCOUNT_THRESHOLD,
+                    keys: vec![],
+                },
+            ));
+            accounts
+        };
+
+        for account in accounts {
+            let mut buf = vec![];
+            account.serialize(&mut buf);
+            assert_eq!(account.serialized_size(), buf.len());
+        }
+    }
+
     fn create_dummy_account(threshold: u8, key_count: u8) -> (Account, Vec<KeyPair>) {
         let keys: Vec<KeyPair> = (0..key_count).map(|_| KeyPair::gen()).collect();
         let account = Account {
@@ -1,6 +1,6 @@
 use num_bigint::BigInt;
 use num_traits::ToPrimitive;
-use std::{fmt, str::FromStr};
+use std::{borrow::Cow, fmt, str::FromStr};
 
 mod precision;
 use self::precision::*;
@@ -28,6 +28,28 @@ impl Asset {
         Asset { amount }
     }
 
+    /// Creates an asset from a number of whole units (e.g. `5` is `5.00000`), returning `None` if
+    /// the amount overflows.
+    #[inline]
+    pub fn from_whole(units: i64) -> Option<Asset> {
+        Some(Asset {
+            amount: set_decimals_i64(units, 0, MAX_PRECISION)?,
+        })
+    }
+
+    /// Returns the whole units of the amount truncated towards zero (e.g. `-1.50000` is `-1`).
+    #[inline]
+    pub fn whole_part(&self) -> i64 {
+        self.amount / DECIMAL_MULT_I64[MAX_PRECISION as usize]
+    }
+
+    /// Returns the fractional units of the amount in the smallest unit, with the same sign as the
+    /// amount (e.g. `-1.50000` is `-50000`).
+    #[inline]
+    pub fn fractional_part(&self) -> i64 {
+        self.amount % DECIMAL_MULT_I64[MAX_PRECISION as usize]
+    }
+
     #[inline]
     pub fn checked_add(self, other: Self) -> Option<Self> {
         Some(Asset {
@@ -35,6 +57,12 @@ impl Asset {
         })
     }
 
+    /// Sums all the assets, returning `None` if an overflow occurs. An empty iterator sums to zero.
+    pub fn checked_sum<I: IntoIterator<Item = Asset>>(iter: I) -> Option<Self> {
+        iter.into_iter()
+            .try_fold(Asset::default(), |acc, asset| acc.checked_add(asset))
+    }
+
     #[inline]
     pub fn checked_sub(self, other: Self) -> Option<Self> {
         Some(Asset {
@@ -42,6 +70,24 @@ impl Asset {
         })
     }
 
+    /// Flips the sign of the amount, returning `None` for the minimum amount which has no positive
+    /// counterpart.
+    #[inline]
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Asset {
+            amount: self.amount.checked_neg()?,
+        })
+    }
+
+    /// Returns the absolute amount, returning `None` for the minimum amount which has no positive
+    /// counterpart.
+    #[inline]
+    pub fn checked_abs(self) -> Option<Self> {
+        Some(Asset {
+            amount: self.amount.checked_abs()?,
+        })
+    }
+
     pub fn checked_mul(self, other: Self) -> Option<Self> {
         const MUL_PRECISION: u8 = MAX_PRECISION * 2;
         let mul = i128::from(self.amount).checked_mul(i128::from(other.amount))?;
@@ -54,6 +100,39 @@ impl Asset {
         })
     }
 
+    /// Adds the assets, clamping the amount to the `i64` bounds instead of overflowing.
+    #[inline]
+    pub fn saturating_add(self, other: Self) -> Self {
+        Asset {
+            amount: self.amount.saturating_add(other.amount),
+        }
+    }
+
+    /// Subtracts the assets, clamping the amount to the `i64` bounds instead of overflowing.
+    #[inline]
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Asset {
+            amount: self.amount.saturating_sub(other.amount),
+        }
+    }
+
+    /// Multiplies the assets, clamping the amount to the `i64` bounds instead of overflowing. The
+    /// clamp is applied after the fixed-point rescale.
+    pub fn saturating_mul(self, other: Self) -> Self {
+        const MUL_PRECISION: u8 = MAX_PRECISION * 2;
+        // The product of two i64 values always fits in an i128 and rescaling can only shrink it
+        let mul = i128::from(self.amount) * i128::from(other.amount);
+        let final_mul = set_decimals_i128(mul, MUL_PRECISION, MAX_PRECISION).unwrap();
+        let amount = if final_mul > i128::from(::std::i64::MAX) {
+            ::std::i64::MAX
+        } else if final_mul < i128::from(::std::i64::MIN) {
+            ::std::i64::MIN
+        } else {
+            final_mul as i64
+        };
+        Asset { amount }
+    }
+
     pub fn checked_div(self, other: Self) -> Option<Self> {
         if other.amount == 0 {
             return None;
@@ -65,6 +144,20 @@ impl Asset {
         })
     }
 
+    /// Returns the remainder of `checked_div` using the same fixed-point scaling, such that
+    /// `a == (a / b) * b + (a % b)` holds within precision. Returns `None` when the divisor is zero.
+    pub fn checked_rem(self, other: Self) -> Option<Self> {
+        if other.amount == 0 {
+            return None;
+        }
+        const DIV_PRECISION: u8 = MAX_PRECISION * 2;
+        let a = set_decimals_i64(self.amount, MAX_PRECISION, DIV_PRECISION)?;
+        let rem = a.checked_rem(other.amount)?;
+        Some(Asset {
+            amount: set_decimals_i64(rem, DIV_PRECISION, MAX_PRECISION)?,
+        })
+    }
+
     pub fn checked_pow(self, num: u16) -> Option<Self> {
         if num == 0 {
             return Some(Asset {
@@ -94,6 +187,20 @@ impl Asset {
             amount: res.to_i64()?,
         })
     }
+
+    /// Formats the amount with all decimal places but without the asset symbol (e.g. `1.00000`),
+    /// for compact displays such as tables.
+    pub fn to_string_no_symbol(&self) -> String {
+        let precision = MAX_PRECISION as usize;
+        let digits = format!(
+            "{:0>width$}",
+            i128::from(self.amount).abs(),
+            width = precision + 1
+        );
+        let (int, dec) = digits.split_at(digits.len() - precision);
+        let sign = if self.amount < 0 { "-" } else { "" };
+        format!("{}{}.{}", sign, int, dec)
+    }
 }
 
 impl fmt::Debug for Asset {
@@ -103,23 +210,8 @@ impl fmt::Debug for Asset {
 }
 
 impl ToString for Asset {
-    #[allow(clippy::comparison_chain)]
     fn to_string(&self) -> String {
-        let mut s = self.amount.to_string();
-        {
-            let len = s.len();
-            // See std::core::cmp for the most optimal comparisons
-            if len < MAX_PRECISION as usize {
-                let start = if self.amount < 0 { 1 } else { 0 };
-                let diff = MAX_PRECISION as usize - len + start;
-                s.insert_str(start, "0.");
-                s.insert_str(start + 2, &"0".repeat(diff));
-            } else if len == MAX_PRECISION as usize {
-                s.insert_str(0, "0.");
-            } else {
-                s.insert(len - (MAX_PRECISION as usize), '.');
-            }
-        }
+        let mut s = self.to_string_no_symbol();
         s.push(' ');
         s.push_str(ASSET_SYMBOL);
         s
@@ -130,12 +222,13 @@ impl FromStr for Asset {
     type Err = AssetError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = strip_thousands_separators(s.trim())?;
         if s.len() > MAX_STR_LEN {
             return Err(AssetError {
                 kind: AssetErrorKind::StrTooLarge,
             });
         }
-        let mut split = s.trim().splitn(2, ' ');
+        let mut split = s.splitn(2, ' ');
 
         let amount: i64;
         match split.next() {
@@ -203,6 +296,40 @@ impl FromStr for Asset {
     }
 }
 
+/// Removes the commas separating thousands in the integer portion of the amount (e.g.
+/// `1,234.50000 TEST`). Commas must separate groups of three digits, a comma anywhere else in the
+/// amount is an invalid amount.
+fn strip_thousands_separators(s: &str) -> Result<Cow<'_, str>, AssetError> {
+    let amount_len = s.find(' ').unwrap_or(s.len());
+    let (amount, rest) = s.split_at(amount_len);
+    if !amount.contains(',') {
+        return Ok(Cow::Borrowed(s));
+    }
+
+    let err = AssetError {
+        kind: AssetErrorKind::InvalidAmount,
+    };
+    let (int, dec) = amount.split_at(amount.find('.').unwrap_or(amount.len()));
+    if dec.contains(',') {
+        return Err(err);
+    }
+    let int = int.trim_start_matches('-');
+    for (i, group) in int.split(',').enumerate() {
+        let valid_len = if i == 0 {
+            (1..=3).contains(&group.len())
+        } else {
+            group.len() == 3
+        };
+        if !valid_len {
+            return Err(err);
+        }
+    }
+
+    let mut stripped = amount.replace(',', "");
+    stripped.push_str(rest);
+    Ok(Cow::Owned(stripped))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,6 +349,34 @@ mod tests {
         c(get_asset("-0.00000 TEST"), "0");
     }
 
+    #[test]
+    fn parse_thousands_separators() {
+        let c = |asset: &str, expected: &str| {
+            assert_eq!(get_asset(asset), get_asset(expected), "Asset: {}", asset);
+        };
+
+        c("1,234.50000 TEST", "1234.50000 TEST");
+        c("-1,234.50000 TEST", "-1234.50000 TEST");
+        c("12,345,678.00001 TEST", "12345678.00001 TEST");
+        c("999.00000 TEST", "999.00000 TEST");
+        // The maximum string length applies after the separators are removed
+        c("92,233,720,368,547.75807 TEST", "92233720368547.75807 TEST");
+
+        let e = |asset: &str| {
+            let e = Asset::from_str(asset).err().unwrap();
+            assert_eq!(e.kind, AssetErrorKind::InvalidAmount, "Asset: {}", asset);
+        };
+        e("1,234.500,00 TEST");
+        e("1234.50,000 TEST");
+        e(",123.00000 TEST");
+        e("-,123.00000 TEST");
+        e("123,.00000 TEST");
+        e("1,,234.00000 TEST");
+        e("1,2.00000 TEST");
+        e("1,2345.00000 TEST");
+        e("1234,567.00000 TEST");
+    }
+
     #[test]
     fn asset_to_str() {
         let c = |asset: Asset, s: &str| {
@@ -236,6 +391,67 @@ mod tests {
         c(get_asset("1.00000 TEST"), "1.00000 TEST");
     }
 
+    #[test]
+    fn asset_to_str_no_symbol() {
+        let c = |asset: Asset, s: &str| {
+            assert_eq!(asset.to_string_no_symbol(), s);
+            assert_eq!(asset.to_string(), format!("{} {}", s, ASSET_SYMBOL));
+        };
+        c(get_asset("1.00000 TEST"), "1.00000");
+        c(get_asset("123.45600 TEST"), "123.45600");
+        c(get_asset("-1.00000 TEST"), "-1.00000");
+        c(get_asset("-123.45600 TEST"), "-123.45600");
+        c(get_asset("0.00000 TEST"), "0.00000");
+        c(get_asset("0.00001 TEST"), "0.00001");
+        c(get_asset("0.12345 TEST"), "0.12345");
+        c(get_asset("-0.00001 TEST"), "-0.00001");
+        c(get_asset("-0.01234 TEST"), "-0.01234");
+        c(get_asset("-0.12345 TEST"), "-0.12345");
+        c(Asset::new(i64::max_value()), "92233720368547.75807");
+        c(Asset::new(i64::min_value()), "-92233720368547.75808");
+    }
+
+    #[test]
+    fn whole_units() {
+        let a = Asset::from_whole(5).unwrap();
+        assert_eq!(a.to_string(), "5.00000 TEST");
+        assert_eq!(a, get_asset("5.00000 TEST"));
+        assert_eq!((a.whole_part(), a.fractional_part()), (5, 0));
+
+        let a = Asset::from_whole(-3).unwrap();
+        assert_eq!(a.to_string(), "-3.00000 TEST");
+        assert_eq!((a.whole_part(), a.fractional_part()), (-3, 0));
+
+        let c = |s: &str, whole: i64, frac: i64| {
+            let a = get_asset(s);
+            assert_eq!((a.whole_part(), a.fractional_part()), (whole, frac));
+            assert_eq!(
+                Asset::from_whole(whole)
+                    .unwrap()
+                    .checked_add(Asset::new(frac))
+                    .unwrap(),
+                a
+            );
+        };
+        c("123.45600 TEST", 123, 45600);
+        c("-1.50000 TEST", -1, -50000);
+        c("-0.00001 TEST", 0, -1);
+        c("0.00000 TEST", 0, 0);
+        c("92233720368547.75807 TEST", 92_233_720_368_547, 75807);
+        c("-92233720368547.75808 TEST", -92_233_720_368_547, -75808);
+
+        assert_eq!(
+            Asset::from_whole(92_233_720_368_547),
+            Some(get_asset("92233720368547.00000 TEST"))
+        );
+        assert_eq!(Asset::from_whole(92_233_720_368_548), None);
+        assert_eq!(
+            Asset::from_whole(-92_233_720_368_547),
+            Some(get_asset("-92233720368547.00000 TEST"))
+        );
+        assert_eq!(Asset::from_whole(-92_233_720_368_548), None);
+    }
+
     #[test]
     fn fail_parsing_invalid_input() {
         let c = |asset: &str, err: AssetErrorKind| {
@@ -332,6 +548,114 @@ mod tests {
         assert!(a.checked_div(get_asset("0.00000 TEST")).is_none());
     }
 
+    #[test]
+    fn negate_and_abs() {
+        let c = |asset: &str, neg: &str, abs: &str| {
+            let asset = get_asset(asset);
+            assert_eq!(asset.checked_neg().unwrap().to_string(), neg);
+            assert_eq!(asset.checked_abs().unwrap().to_string(), abs);
+        };
+        c("1.50000 TEST", "-1.50000 TEST", "1.50000 TEST");
+        c("-1.50000 TEST", "1.50000 TEST", "1.50000 TEST");
+        c("0.00001 TEST", "-0.00001 TEST", "0.00001 TEST");
+        c("-0.50000 TEST", "0.50000 TEST", "0.50000 TEST");
+        c("0.00000 TEST", "0.00000 TEST", "0.00000 TEST");
+        c(
+            "92233720368547.75807 TEST",
+            "-92233720368547.75807 TEST",
+            "92233720368547.75807 TEST",
+        );
+
+        let min = Asset::new(::std::i64::MIN);
+        assert_eq!(min.checked_neg(), None);
+        assert_eq!(min.checked_abs(), None);
+        let min_plus_one = Asset::new(::std::i64::MIN + 1);
+        assert_eq!(
+            min_plus_one.checked_neg(),
+            Some(Asset::new(::std::i64::MAX))
+        );
+        assert_eq!(
+            min_plus_one.checked_abs(),
+            Some(Asset::new(::std::i64::MAX))
+        );
+    }
+
+    #[test]
+    fn perform_remainder() {
+        let c = |asset: Asset, amount: &str| {
+            assert_eq!(asset.to_string(), amount);
+        };
+        let check_identity = |a: Asset, b: Asset| {
+            let div = a.checked_div(b).unwrap();
+            let rem = a.checked_rem(b).unwrap();
+            let res = div.checked_mul(b).unwrap().checked_add(rem).unwrap();
+            assert!((res.amount - a.amount).abs() <= 1, "{:?} != {:?}", res, a);
+        };
+
+        let a = get_asset("123.45600 TEST");
+        c(
+            a.checked_rem(get_asset("23.00000 TEST")).unwrap(),
+            "0.00005 TEST",
+        );
+        c(
+            a.checked_rem(get_asset("-23.00000 TEST")).unwrap(),
+            "0.00005 TEST",
+        );
+        c(
+            get_asset("-123.45600 TEST")
+                .checked_rem(get_asset("23.00000 TEST"))
+                .unwrap(),
+            "-0.00005 TEST",
+        );
+        c(
+            get_asset("10.00000 TEST")
+                .checked_rem(get_asset("2.00000 TEST"))
+                .unwrap(),
+            "0.00000 TEST",
+        );
+        c(a, "123.45600 TEST");
+
+        let values = [
+            "123.45600 TEST",
+            "-123.45600 TEST",
+            "23.00000 TEST",
+            "-23.00000 TEST",
+            "0.00001 TEST",
+            "-0.00003 TEST",
+            "1.00020 TEST",
+            "3.00000 TEST",
+            "-7.12345 TEST",
+        ];
+        for a in &values {
+            for b in &values {
+                check_identity(get_asset(a), get_asset(b));
+            }
+        }
+
+        assert!(a.checked_rem(get_asset("0.00000 TEST")).is_none());
+        assert!(get_asset("-1.00000 TEST")
+            .checked_rem(Asset::new(0))
+            .is_none());
+    }
+
+    #[test]
+    fn checked_sum() {
+        assert_eq!(Asset::checked_sum(vec![]), Some(Asset::default()));
+
+        let assets = vec![
+            get_asset("1.00000 TEST"),
+            get_asset("2.50000 TEST"),
+            get_asset("-0.50000 TEST"),
+        ];
+        assert_eq!(Asset::checked_sum(assets), Some(get_asset("3.00000 TEST")));
+
+        let assets = vec![
+            get_asset("1.00000 TEST"),
+            get_asset("92233720368547.75807 TEST"),
+        ];
+        assert_eq!(Asset::checked_sum(assets), None);
+    }
+
     #[test]
     fn invalid_arithmetic() {
         let a = get_asset("10.00000 TEST");
@@ -343,6 +667,64 @@ mod tests {
         assert_eq!(a.checked_mul(b), None);
     }
 
+    #[test]
+    fn saturating_arithmetic() {
+        let max = Asset::new(::std::i64::MAX);
+        let min = Asset::new(::std::i64::MIN);
+        let two = get_asset("2.00000 TEST");
+
+        assert_eq!(max.saturating_add(two), max);
+        assert_eq!(
+            min.saturating_add(two.saturating_mul(Asset::new(-100_000))),
+            min
+        );
+        assert_eq!(
+            max.saturating_sub(two.saturating_mul(Asset::new(-100_000))),
+            max
+        );
+        assert_eq!(min.saturating_sub(two), min);
+        assert_eq!(max.saturating_mul(two), max);
+        assert_eq!(min.saturating_mul(two), min);
+        assert_eq!(max.saturating_mul(get_asset("-2.00000 TEST")), min);
+        assert_eq!(min.saturating_mul(get_asset("-2.00000 TEST")), max);
+        assert_eq!(max.saturating_mul(min), min);
+
+        let values = [
+            "123.45600 TEST",
+            "-123.45600 TEST",
+            "0.00001 TEST",
+            "0.00000 TEST",
+            "100000.11111 TEST",
+            "-7.12345 TEST",
+        ];
+        for a in &values {
+            for b in &values {
+                let (a, b) = (get_asset(a), get_asset(b));
+                assert_eq!(Some(a.saturating_add(b)), a.checked_add(b));
+                assert_eq!(Some(a.saturating_sub(b)), a.checked_sub(b));
+                assert_eq!(Some(a.saturating_mul(b)), a.checked_mul(b));
+            }
+        }
+    }
+
+    #[test]
+    fn sort_assets() {
+        let mut assets = vec![
+            get_asset("10.00000 TEST"),
+            get_asset("-1.00000 TEST"),
+            get_asset("0.00001 TEST"),
+            get_asset("0.00000 TEST"),
+            get_asset("-0.00001 TEST"),
+            get_asset("1.00000 TEST"),
+        ];
+        let mut amounts: Vec<i64> = assets.iter().map(|a| a.amount).collect();
+        assets.sort();
+        amounts.sort();
+        assert_eq!(assets.iter().map(|a| a.amount).collect::<Vec<_>>(), amounts);
+        assert_eq!(assets.iter().max(), Some(&get_asset("10.00000 TEST")));
+        assert_eq!(assets.iter().min(), Some(&get_asset("-1.00000 TEST")));
+    }
+
     fn get_asset(s: &str) -> Asset {
         Asset::from_str(s).unwrap()
     }
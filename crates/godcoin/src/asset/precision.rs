@@ -2,7 +2,7 @@
 
 use num_bigint::BigInt;
 
-const DECIMAL_MULT_I64: [i64; 19] = [
+pub const DECIMAL_MULT_I64: [i64; 19] = [
     1,
     10,
     100,
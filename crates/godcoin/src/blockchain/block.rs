@@ -1,21 +1,104 @@
 use crate::{
     account::AccountId,
     asset::Asset,
-    blockchain::Receipt,
-    crypto::{double_sha256, Digest, DoubleSha256, KeyPair, SigPair},
+    blockchain::{BloomFilter, Receipt},
+    crypto::{double_sha256, Digest, DoubleSha256, SigPair, Signer},
     serializer::*,
     tx::TxVariant,
 };
 use std::{collections::BTreeSet, io::Cursor, ops::Deref, sync::Arc};
 
-pub type BlockFilter = BTreeSet<AccountId>;
+/// Accounts a client is interested in when retrieving blocks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockFilter {
+    /// Matches exactly the specified accounts. An empty set matches no accounts.
+    Accounts(BTreeSet<AccountId>),
+    /// Matches accounts probabilistically to support large sets of accounts. Matches may be false
+    /// positives and must be verified by the client.
+    Bloom(BloomFilter),
+}
+
+impl BlockFilter {
+    /// Creates an empty filter that matches accounts exactly.
+    #[inline]
+    pub fn new() -> Self {
+        BlockFilter::Accounts(BTreeSet::new())
+    }
+
+    #[inline]
+    pub fn insert(&mut self, id: AccountId) {
+        match self {
+            BlockFilter::Accounts(accounts) => {
+                accounts.insert(id);
+            }
+            BlockFilter::Bloom(bloom) => bloom.insert(id),
+        }
+    }
 
+    #[inline]
+    pub fn contains(&self, id: AccountId) -> bool {
+        match self {
+            BlockFilter::Accounts(accounts) => accounts.contains(&id),
+            BlockFilter::Bloom(bloom) => bloom.contains(id),
+        }
+    }
+
+    /// Returns whether the filter is guaranteed to match no accounts.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        match self {
+            BlockFilter::Accounts(accounts) => accounts.is_empty(),
+            BlockFilter::Bloom(bloom) => bloom.bits().iter().all(|b| *b == 0),
+        }
+    }
+}
+
+impl Default for BlockFilter {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A block retrieved with a block filter applied. When the block contains no receipts matching the
+/// filter, only the header and its signature are provided to save bandwidth. Otherwise, the full
+/// block is provided.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum FilteredBlock {
     Header((BlockHeader, SigPair)),
     Block(Arc<Block>),
 }
 
+impl FilteredBlock {
+    /// Returns whether the full block is available. A filtered block without the full block means
+    /// there were no matches for the filter.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        match self {
+            FilteredBlock::Header(_) => false,
+            FilteredBlock::Block(_) => true,
+        }
+    }
+
+    #[inline]
+    pub fn header(&self) -> BlockHeader {
+        match self {
+            FilteredBlock::Header((header, _)) => header.clone(),
+            FilteredBlock::Block(block) => block.header(),
+        }
+    }
+
+    /// Returns the full block if available, otherwise `None` when the block did not match the
+    /// filter.
+    #[inline]
+    pub fn into_block(self) -> Option<Arc<Block>> {
+        match self {
+            FilteredBlock::Header(_) => None,
+            FilteredBlock::Block(block) => Some(block),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Block {
     V0(BlockV0),
@@ -64,11 +147,13 @@ impl Block {
         }
     }
 
-    pub fn sign(&mut self, key_pair: &KeyPair) {
+    /// Signs the header hash using the provided signer, which may be a `KeyPair` or an external
+    /// signer.
+    pub fn sign(&mut self, signer: &dyn Signer) {
         let buf = self.calc_header_hash();
         match self {
             Block::V0(block) => {
-                block.signer = Some(key_pair.sign(buf.as_ref()));
+                block.signer = Some(signer.sign(buf.as_ref()));
             }
         }
     }
@@ -157,6 +242,21 @@ impl BlockHeader {
             _ => None,
         }
     }
+
+    pub fn calc_hash(&self) -> Digest {
+        let mut buf = Vec::with_capacity(1024);
+        self.serialize(&mut buf);
+        double_sha256(&buf)
+    }
+
+    /// Returns whether this header links to the previous header by hash. This allows verifying a
+    /// chain of headers without the full blocks.
+    pub fn verify_link(&self, prev: &Self) -> bool {
+        let cur_prev_hash = match self {
+            BlockHeader::V0(header) => &header.previous_hash,
+        };
+        cur_prev_hash == &prev.calc_hash()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -204,15 +304,19 @@ pub struct BlockV0 {
 
 impl BlockV0 {
     pub fn new_child(&self, receipts: Vec<Receipt>) -> Block {
+        self.new_child_with_reward(receipts, Asset::default())
+    }
+
+    /// Creates the next block with rewards of the transaction fees plus the provided block reward,
+    /// see `RewardSchedule`.
+    pub fn new_child_with_reward(&self, receipts: Vec<Receipt>, reward: Asset) -> Block {
         let previous_hash = self.calc_header_hash();
         let height = self.header.height + 1;
         let receipt_root = calc_receipt_root(&receipts);
         let timestamp = crate::get_epoch_time();
-        let rewards = receipts
-            .iter()
-            .fold(Asset::default(), |acc, receipt| match &receipt.tx {
-                TxVariant::V0(tx) => acc.checked_add(tx.fee).unwrap(),
-            });
+        let rewards = calc_fees(&receipts)
+            .and_then(|fees| fees.checked_add(reward))
+            .unwrap();
         Block::V0(BlockV0 {
             header: BlockHeaderV0 {
                 previous_hash,
@@ -241,6 +345,8 @@ impl Deref for BlockV0 {
     }
 }
 
+/// Calculates the double SHA-256 hash of the serialized receipts in order. A block without receipts
+/// has the root `double_sha256(&[])`, allowing empty blocks to be produced and verified.
 pub fn calc_receipt_root(receipts: &[Receipt]) -> Digest {
     let mut hasher = DoubleSha256::new();
     let mut buf = Vec::with_capacity(4096);
@@ -252,6 +358,13 @@ pub fn calc_receipt_root(receipts: &[Receipt]) -> Digest {
     hasher.finalize()
 }
 
+/// Sums the fees of the receipts, returning `None` if an overflow occurs.
+pub fn calc_fees(receipts: &[Receipt]) -> Option<Asset> {
+    Asset::checked_sum(receipts.iter().map(|receipt| match &receipt.tx {
+        TxVariant::V0(tx) => tx.fee,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -299,6 +412,35 @@ mod tests {
         assert_eq!(block, dec);
     }
 
+    #[test]
+    fn filtered_block_helpers() {
+        let keys = KeyPair::gen();
+        let mut block = Block::V0(BlockV0 {
+            header: BlockHeaderV0 {
+                previous_hash: Digest::from_slice(&[0; 32]).unwrap(),
+                height: 1,
+                timestamp: 0,
+                receipt_root: double_sha256(&[0; 0]),
+            },
+            signer: None,
+            rewards: Asset::default(),
+            receipts: vec![],
+        });
+        block.sign(&keys);
+        let block = Arc::new(block);
+
+        let full = FilteredBlock::Block(Arc::clone(&block));
+        assert!(full.is_full());
+        assert_eq!(full.header(), block.header());
+        assert_eq!(full.into_block(), Some(Arc::clone(&block)));
+
+        let signer = block.signer().unwrap().clone();
+        let header = FilteredBlock::Header((block.header(), signer));
+        assert!(!header.is_full());
+        assert_eq!(header.header(), block.header());
+        assert_eq!(header.into_block(), None);
+    }
+
     #[test]
     fn receipt_root() {
         let mut block = Block::V0(BlockV0 {
@@ -323,6 +465,59 @@ mod tests {
         assert!(!block.verify_receipt_root());
     }
 
+    #[test]
+    fn receipt_root_for_receipt_counts() {
+        let create_receipt = |nonce: u32| Receipt {
+            tx: TxVariant::V0(TxVariantV0::MintTx(MintTx {
+                base: Tx {
+                    nonce,
+                    expiry: 1234567890,
+                    fee: Asset::default(),
+                    signature_pairs: Vec::new(),
+                },
+                to: 0,
+                amount: Asset::new(100),
+                attachment: vec![],
+                attachment_name: "".to_owned(),
+            })),
+            log: vec![],
+        };
+        let root = |block: &Block| match block.header() {
+            BlockHeader::V0(header) => header.receipt_root,
+        };
+        let genesis = BlockV0 {
+            header: BlockHeaderV0 {
+                previous_hash: Digest::from_slice(&[0; 32]).unwrap(),
+                height: 0,
+                timestamp: 0,
+                receipt_root: double_sha256(&[0; 0]),
+            },
+            signer: None,
+            rewards: Asset::default(),
+            receipts: vec![],
+        };
+
+        let empty = genesis.new_child(vec![]);
+        assert_eq!(calc_receipt_root(&[]), double_sha256(&[0; 0]));
+        assert_eq!(root(&empty), calc_receipt_root(&[]));
+        assert!(empty.verify_receipt_root());
+
+        let single = genesis.new_child(vec![create_receipt(0)]);
+        assert!(single.verify_receipt_root());
+        assert_ne!(root(&single), root(&empty));
+
+        let receipts: Vec<Receipt> = (0..100).map(create_receipt).collect();
+        let many = genesis.new_child(receipts.clone());
+        assert!(many.verify_receipt_root());
+        assert_eq!(root(&many), calc_receipt_root(&receipts));
+        assert_ne!(root(&many), root(&single));
+
+        // The order of the receipts is committed to
+        let mut reversed = receipts;
+        reversed.reverse();
+        assert_ne!(calc_receipt_root(&reversed), root(&many));
+    }
+
     #[test]
     fn previous_hash() {
         let block_0 = Block::V0(BlockV0 {
@@ -364,4 +559,42 @@ mod tests {
         assert!(block_1.verify_previous_hash(&block_0));
         assert!(!block_1_invalid.verify_previous_hash(&block_0));
     }
+
+    #[test]
+    fn header_verify_link() {
+        let header_0 = BlockHeader::V0(BlockHeaderV0 {
+            previous_hash: Digest::from_slice(&[0; 32]).unwrap(),
+            height: 0,
+            timestamp: 0,
+            receipt_root: double_sha256(&[0; 0]),
+        });
+
+        let block_0 = match &header_0 {
+            BlockHeader::V0(header) => Block::V0(BlockV0 {
+                header: header.clone(),
+                signer: None,
+                rewards: Asset::default(),
+                receipts: vec![],
+            }),
+        };
+        assert_eq!(header_0.calc_hash(), block_0.calc_header_hash());
+
+        let header_1 = BlockHeader::V0(BlockHeaderV0 {
+            previous_hash: header_0.calc_hash(),
+            height: 1,
+            timestamp: 0,
+            receipt_root: double_sha256(&[0; 0]),
+        });
+
+        let header_1_invalid = BlockHeader::V0(BlockHeaderV0 {
+            previous_hash: Digest::from_slice(&[0; 32]).unwrap(),
+            height: 1,
+            timestamp: 0,
+            receipt_root: double_sha256(&[0; 0]),
+        });
+
+        assert!(header_1.verify_link(&header_0));
+        assert!(!header_1_invalid.verify_link(&header_0));
+        assert!(!header_0.verify_link(&header_1));
+    }
 }
@@ -0,0 +1,148 @@
+use crate::account::AccountId;
+
+/// Maximum size of the bit array in bytes, allowing roughly 28,000 accounts at a 1% false positive
+/// rate.
+pub const MAX_BLOOM_FILTER_BYTES: usize = 32_768;
+
+/// Maximum number of hash functions that may be applied per account.
+pub const MAX_BLOOM_FILTER_HASHES: u8 = 32;
+
+/// A probabilistic set of accounts. Membership checks never produce false negatives but may produce
+/// false positives at a rate determined by the size of the filter and the number of accounts
+/// inserted. Clients must re-check any matches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BloomFilter {
+    bits: Vec<u8>,
+    hash_count: u8,
+}
+
+impl BloomFilter {
+    /// Creates an empty filter sized to hold `expected_items` with the desired false positive rate.
+    /// The size is capped by `MAX_BLOOM_FILTER_BYTES`, which increases the false positive rate for
+    /// very large sets.
+    pub fn new(expected_items: usize, fp_rate: f64) -> Self {
+        assert!(
+            fp_rate > 0.0 && fp_rate < 1.0,
+            "false positive rate must be between 0 and 1"
+        );
+        let ln2 = std::f64::consts::LN_2;
+        let items = expected_items.max(1) as f64;
+        let bit_count = (-items * fp_rate.ln() / (ln2 * ln2)).ceil();
+        let byte_count = ((bit_count / 8.0).ceil() as usize)
+            .max(1)
+            .min(MAX_BLOOM_FILTER_BYTES);
+        let hash_count = ((byte_count * 8) as f64 / items * ln2).round() as u8;
+        Self {
+            bits: vec![0; byte_count],
+            hash_count: hash_count.max(1).min(MAX_BLOOM_FILTER_HASHES),
+        }
+    }
+
+    /// Reconstructs a filter from its raw parts, returning `None` if the parts exceed the filter
+    /// limits.
+    pub fn from_parts(bits: Vec<u8>, hash_count: u8) -> Option<Self> {
+        if bits.is_empty()
+            || bits.len() > MAX_BLOOM_FILTER_BYTES
+            || hash_count == 0
+            || hash_count > MAX_BLOOM_FILTER_HASHES
+        {
+            return None;
+        }
+        Some(Self { bits, hash_count })
+    }
+
+    #[inline]
+    pub fn bits(&self) -> &[u8] {
+        &self.bits
+    }
+
+    #[inline]
+    pub fn hash_count(&self) -> u8 {
+        self.hash_count
+    }
+
+    pub fn insert(&mut self, id: AccountId) {
+        for index in self.bit_indices(id) {
+            self.bits[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    pub fn contains(&self, id: AccountId) -> bool {
+        self.bit_indices(id)
+            .all(|index| self.bits[index / 8] & (1 << (index % 8)) != 0)
+    }
+
+    /// Derives the bit positions of an account using double hashing.
+    fn bit_indices(&self, id: AccountId) -> impl Iterator<Item = usize> {
+        let bit_count = (self.bits.len() * 8) as u64;
+        let h1 = mix(id);
+        let h2 = mix(id ^ 0x9E37_79B9_7F4A_7C15) | 1;
+        (0..u64::from(self.hash_count))
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bit_count) as usize)
+    }
+}
+
+/// The SplitMix64 finalizer, used to spread account IDs across the filter.
+#[inline]
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserted_accounts_are_members() {
+        let mut filter = BloomFilter::new(1000, 0.01);
+        for id in 0..1000 {
+            filter.insert(id * 7);
+        }
+        for id in 0..1000 {
+            assert!(filter.contains(id * 7));
+        }
+    }
+
+    #[test]
+    fn empty_filter_has_no_members() {
+        let filter = BloomFilter::new(100, 0.01);
+        for id in 0..1000 {
+            assert!(!filter.contains(id));
+        }
+    }
+
+    #[test]
+    fn false_positive_rate_within_bounds() {
+        let fp_rate = 0.01;
+        let mut filter = BloomFilter::new(1000, fp_rate);
+        for id in 0..1000 {
+            filter.insert(id);
+        }
+
+        let trials = 100_000;
+        let false_positives = (1000..1000 + trials)
+            .filter(|&id| filter.contains(id))
+            .count();
+        let observed = false_positives as f64 / trials as f64;
+        // Allow some leeway over the theoretical rate
+        assert!(observed < fp_rate * 2.0, "observed rate: {}", observed);
+    }
+
+    #[test]
+    fn size_is_capped() {
+        let filter = BloomFilter::new(10_000_000, 0.0001);
+        assert_eq!(filter.bits().len(), MAX_BLOOM_FILTER_BYTES);
+        assert!(filter.hash_count() >= 1);
+    }
+
+    #[test]
+    fn from_parts_validates_limits() {
+        assert!(BloomFilter::from_parts(vec![0; 8], 3).is_some());
+        assert!(BloomFilter::from_parts(vec![], 3).is_none());
+        assert!(BloomFilter::from_parts(vec![0; 8], 0).is_none());
+        assert!(BloomFilter::from_parts(vec![0; 8], MAX_BLOOM_FILTER_HASHES + 1).is_none());
+        assert!(BloomFilter::from_parts(vec![0; MAX_BLOOM_FILTER_BYTES + 1], 3).is_none());
+    }
+}
@@ -13,6 +13,8 @@ pub enum BlockErr {
     InvalidReceiptRoot,
     InvalidSignature,
     InvalidPrevHash,
+    CheckpointMismatch,
+    InvalidReward,
     Tx(TxErr),
 }
 
@@ -26,10 +28,24 @@ pub enum TxErr {
     InvalidAmount,
     InvalidFeeAmount,
     TooManySignatures,
-    TxTooLarge,
+    ScriptTooLarge,
     TxProhibited,
     TxExpired,
     TxDupe,
+    MemoTooLarge,
+    DuplicateSignature,
+    /// The transfer exceeds the amount the account may transfer within the window of recent
+    /// blocks, see `Blockchain::set_spend_limit`.
+    RateLimited,
+    /// A pending transaction with the same account and nonce exists and the transaction does not
+    /// raise the fee enough to replace it, see `ReceiptPool::push`.
+    ReplacementUnderpriced,
+    /// A pending transaction with the same account and nonce exists and the transaction is not
+    /// signed to meet the permissions of the account, see `ReceiptPool::push`.
+    ReplacementUnauthorized,
+    /// The account created or updated by the transaction exceeds `MAX_ACCOUNT_BYTE_SIZE` when
+    /// serialized.
+    AccountTooLarge,
 }
 
 impl TxErr {
@@ -47,10 +63,16 @@ impl TxErr {
             TxErr::InvalidAmount => buf.push(0x05),
             TxErr::InvalidFeeAmount => buf.push(0x06),
             TxErr::TooManySignatures => buf.push(0x07),
-            TxErr::TxTooLarge => buf.push(0x08),
+            TxErr::ScriptTooLarge => buf.push(0x08),
             TxErr::TxProhibited => buf.push(0x09),
             TxErr::TxExpired => buf.push(0x0A),
             TxErr::TxDupe => buf.push(0x0B),
+            TxErr::MemoTooLarge => buf.push(0x0C),
+            TxErr::DuplicateSignature => buf.push(0x0D),
+            TxErr::RateLimited => buf.push(0x0E),
+            TxErr::ReplacementUnderpriced => buf.push(0x0F),
+            TxErr::ReplacementUnauthorized => buf.push(0x10),
+            TxErr::AccountTooLarge => buf.push(0x11),
         }
     }
 
@@ -74,10 +96,16 @@ impl TxErr {
             0x05 => TxErr::InvalidAmount,
             0x06 => TxErr::InvalidFeeAmount,
             0x07 => TxErr::TooManySignatures,
-            0x08 => TxErr::TxTooLarge,
+            0x08 => TxErr::ScriptTooLarge,
             0x09 => TxErr::TxProhibited,
             0x0A => TxErr::TxExpired,
             0x0B => TxErr::TxDupe,
+            0x0C => TxErr::MemoTooLarge,
+            0x0D => TxErr::DuplicateSignature,
+            0x0E => TxErr::RateLimited,
+            0x0F => TxErr::ReplacementUnderpriced,
+            0x10 => TxErr::ReplacementUnauthorized,
+            0x11 => TxErr::AccountTooLarge,
             _ => {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidData,
@@ -1,4 +1,4 @@
-use rocksdb::{ColumnFamilyDescriptor, DBRecoveryMode, IteratorMode, Options, DB};
+use rocksdb::{ColumnFamilyDescriptor, DBRecoveryMode, Direction, IteratorMode, Options, DB};
 use std::{
     collections::{hash_map::Entry, HashMap},
     convert::TryInto,
@@ -9,15 +9,16 @@ use std::{
 };
 
 use crate::{
-    account::{Account, AccountId},
+    account::{Account, AccountId, AccountIdBytes},
     asset::Asset,
     serializer::*,
-    tx::{TxId, TxVariant, TxVariantV0},
+    tx::{expiry_passed, TxId, TxVariant, TxVariantV0},
 };
 
 const CF_BLOCK_BYTE_POS: &str = "block_byte_pos";
 const CF_ACCOUNT: &str = "account";
 const CF_TX_EXPIRY: &str = "tx_expiry";
+const CF_PRUNED_ACCOUNT: &str = "pruned_account";
 
 const KEY_NET_OWNER: &[u8] = b"network_owner";
 const KEY_CHAIN_HEIGHT: &[u8] = b"chain_height";
@@ -26,6 +27,9 @@ const KEY_INDEX_STATUS: &[u8] = b"index_status";
 
 const TX_EXPIRY_ADJUSTMENT: u64 = 30;
 
+/// Column family name, key, and value of a raw index entry.
+pub type RawIndexEntry = (&'static str, Box<[u8]>, Box<[u8]>);
+
 #[derive(Debug)]
 pub struct Indexer {
     db: DB,
@@ -42,6 +46,7 @@ impl Indexer {
             ColumnFamilyDescriptor::new(CF_BLOCK_BYTE_POS, Options::default()),
             ColumnFamilyDescriptor::new(CF_ACCOUNT, Options::default()),
             ColumnFamilyDescriptor::new(CF_TX_EXPIRY, Options::default()),
+            ColumnFamilyDescriptor::new(CF_PRUNED_ACCOUNT, Options::default()),
         ];
         let db = DB::open_cf_descriptors(&db_opts, path, col_families).unwrap();
         Indexer { db }
@@ -103,12 +108,65 @@ impl Indexer {
         Some(account)
     }
 
+    /// Returns the indexed accounts with the specified IDs. IDs of accounts that don't exist are
+    /// absent from the returned map.
+    pub fn get_accounts(&self, ids: &[AccountId]) -> HashMap<AccountId, Account> {
+        let cf = self.db.cf_handle(CF_ACCOUNT).unwrap();
+        let mut accounts = HashMap::with_capacity(ids.len());
+        for &id in ids {
+            if let Some(buf) = self.db.get_pinned_cf(cf, id.to_be_bytes()).unwrap() {
+                let cur = &mut Cursor::<&[u8]>::new(&buf);
+                let account =
+                    Account::deserialize(cur).expect("failed to deserialize indexed account");
+                accounts.insert(id, account);
+            }
+        }
+        accounts
+    }
+
+    /// Returns up to `limit` accounts in ascending ID order, starting at the `start` ID inclusively
+    /// or the lowest ID when `None`.
+    pub fn iter_accounts(
+        &self,
+        start: Option<AccountId>,
+        limit: usize,
+    ) -> Vec<(AccountId, Account)> {
+        let cf = self.db.cf_handle(CF_ACCOUNT).unwrap();
+        let start = start.unwrap_or(0).to_be_bytes();
+        self.db
+            .iterator_cf(cf, IteratorMode::From(&start, Direction::Forward))
+            .take(limit)
+            .map(|(key, value)| {
+                let id = AccountId::from_slice(&key).unwrap();
+                let cur = &mut Cursor::<&[u8]>::new(&value);
+                let account =
+                    Account::deserialize(cur).expect("failed to deserialize indexed account");
+                (id, account)
+            })
+            .collect()
+    }
+
+    /// Returns the number of indexed accounts. This iterates every account and should be cached
+    /// by callers that need it frequently.
+    pub fn account_count(&self) -> u64 {
+        let cf = self.db.cf_handle(CF_ACCOUNT).unwrap();
+        self.db.iterator_cf(cf, IteratorMode::Start).count() as u64
+    }
+
     pub fn account_exists(&self, id: AccountId) -> bool {
         let cf = self.db.cf_handle(CF_ACCOUNT).unwrap();
         let acc_buf_opt = self.db.get_pinned_cf(cf, id.to_be_bytes()).unwrap();
         acc_buf_opt.is_some()
     }
 
+    /// Returns whether the account was destroyed and removed from the index. Pruned IDs remain
+    /// reserved so they can't be used to create a new account.
+    pub fn is_account_pruned(&self, id: AccountId) -> bool {
+        let cf = self.db.cf_handle(CF_PRUNED_ACCOUNT).unwrap();
+        let buf_opt = self.db.get_pinned_cf(cf, id.to_be_bytes()).unwrap();
+        buf_opt.is_some()
+    }
+
     pub fn get_token_supply(&self) -> Asset {
         let supply_buf = self.db.get_pinned(KEY_TOKEN_SUPPLY).unwrap();
         match supply_buf {
@@ -130,19 +188,67 @@ impl Indexer {
         self.db.put_cf(cf, id, expiry.to_be_bytes()).unwrap();
     }
 
-    pub fn purge_expired_txids(&self) {
+    /// Returns every indexed key and value pair in key order, grouped by column family. This is
+    /// intended for verifying that two indexes hold identical state.
+    pub fn raw_entries(&self) -> Vec<RawIndexEntry> {
+        let mut entries: Vec<_> = self
+            .db
+            .iterator(IteratorMode::Start)
+            .map(|(key, value)| ("default", key, value))
+            .collect();
+        for &name in &[
+            CF_BLOCK_BYTE_POS,
+            CF_ACCOUNT,
+            CF_TX_EXPIRY,
+            CF_PRUNED_ACCOUNT,
+        ] {
+            let cf = self.db.cf_handle(name).unwrap();
+            let iter = self.db.iterator_cf(cf, IteratorMode::Start);
+            entries.extend(iter.map(|(key, value)| (name, key, value)));
+        }
+        entries
+    }
+
+    /// Removes the txids of expired transactions, returning the number of txids removed. A txid is
+    /// expired under the same predicate as its transaction, see `TxVariant::is_expired`.
+    pub fn purge_expired_txids(&self) -> usize {
         let cf = self.db.cf_handle(CF_TX_EXPIRY).unwrap();
         // Pretend to be slightly in the past in case system time adjusts in the future.
         let current_time = crate::get_epoch_time() - TX_EXPIRY_ADJUSTMENT;
 
         let mut batch = rocksdb::WriteBatch::default();
+        let mut purged = 0;
         for (key, value) in self.db.iterator_cf(cf, IteratorMode::Start) {
             let expiry = u64::from_be_bytes(value.as_ref().try_into().unwrap());
-            if expiry < current_time {
+            if expiry_passed(expiry, current_time) {
                 batch.delete_cf(cf, key);
+                purged += 1;
+            }
+        }
+        self.db.write(batch).unwrap();
+        purged
+    }
+
+    /// Removes destroyed accounts with a zero balance from the index, except for the `keep`
+    /// account, returning the number of accounts removed. The IDs of removed accounts are recorded
+    /// as pruned.
+    pub fn prune_destroyed_accounts(&self, keep: AccountId) -> usize {
+        let cf_account = self.db.cf_handle(CF_ACCOUNT).unwrap();
+        let cf_pruned = self.db.cf_handle(CF_PRUNED_ACCOUNT).unwrap();
+
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut pruned = 0;
+        for (key, value) in self.db.iterator_cf(cf_account, IteratorMode::Start) {
+            let cur = &mut Cursor::<&[u8]>::new(&value);
+            let account = Account::deserialize(cur).expect("failed to deserialize indexed account");
+            if account.id != keep && account.destroyed && account.balance.amount == 0 {
+                batch.delete_cf(cf_account, key.clone());
+                batch.put_cf(cf_pruned, key, []);
+                pruned += 1;
             }
         }
         self.db.write(batch).unwrap();
+        pruned
     }
 }
 
@@ -153,6 +259,7 @@ pub struct WriteBatch {
     owner: Option<TxVariant>,
     accounts: HashMap<AccountId, Account>,
     token_supply: Option<Asset>,
+    txids: Vec<(TxId, u64)>,
 }
 
 impl WriteBatch {
@@ -164,6 +271,7 @@ impl WriteBatch {
             owner: None,
             accounts: HashMap::with_capacity(64),
             token_supply: None,
+            txids: Vec::new(),
         }
     }
 
@@ -211,6 +319,13 @@ impl WriteBatch {
             }
         }
 
+        {
+            let cf = self.indexer.db.cf_handle(CF_TX_EXPIRY).unwrap();
+            for (id, expiry) in self.txids {
+                batch.put_cf(cf, &id, expiry.to_be_bytes());
+            }
+        }
+
         self.indexer.db.write(batch).unwrap();
     }
 
@@ -262,11 +377,21 @@ impl WriteBatch {
         acc.balance = acc.balance.checked_sub(amount).unwrap();
     }
 
+    pub fn insert_txid(&mut self, id: TxId, expiry: u64) {
+        self.txids.push((id, expiry));
+    }
+
     #[inline]
     pub fn insert_or_update_account(&mut self, account: Account) {
         self.accounts.insert(account.id, account);
     }
 
+    /// Returns whether the batch holds uncommitted changes to the account.
+    #[inline]
+    pub fn has_account(&self, id: AccountId) -> bool {
+        self.accounts.contains_key(&id)
+    }
+
     pub fn get_account_mut(&mut self, id: AccountId) -> &mut Account {
         match self.accounts.entry(id) {
             Entry::Occupied(entry) => entry.into_mut(),
@@ -285,9 +410,12 @@ pub enum IndexStatus {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::crypto::Digest;
-    use sodiumoxide::randombytes;
-    use std::{env, fs, panic};
+    use crate::{
+        account::Permissions,
+        crypto::{Digest, KeyPair},
+        test_util::TmpDir,
+    };
+    use std::panic;
 
     #[test]
     fn get_block_pos() {
@@ -311,6 +439,41 @@ mod tests {
         });
     }
 
+    #[test]
+    fn iter_accounts() {
+        run_test(|indexer| {
+            assert!(indexer.iter_accounts(None, 10).is_empty());
+
+            let mut batch = WriteBatch::new(Arc::clone(&indexer));
+            for &id in &[7, 3, 1, 5] {
+                batch.insert_or_update_account(Account::create_default(
+                    id,
+                    Permissions {
+                        threshold: 1,
+                        keys: vec![KeyPair::gen().0],
+                    },
+                ));
+            }
+            batch.commit();
+
+            let ids = |accounts: Vec<(AccountId, Account)>| {
+                accounts
+                    .into_iter()
+                    .map(|(id, acc)| {
+                        assert_eq!(id, acc.id);
+                        id
+                    })
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(ids(indexer.iter_accounts(None, 10)), vec![1, 3, 5, 7]);
+            assert_eq!(ids(indexer.iter_accounts(None, 2)), vec![1, 3]);
+            assert_eq!(ids(indexer.iter_accounts(Some(3), 2)), vec![3, 5]);
+            assert_eq!(ids(indexer.iter_accounts(Some(6), 2)), vec![7]);
+            assert!(indexer.iter_accounts(Some(8), 2).is_empty());
+            assert!(indexer.iter_accounts(None, 0).is_empty());
+        });
+    }
+
     #[test]
     fn txid_expirations() {
         run_test(|indexer| {
@@ -325,7 +488,7 @@ mod tests {
             assert!(!indexer.has_txid(&id));
 
             indexer.insert_txid(&id, expiry - TX_EXPIRY_ADJUSTMENT + 1);
-            indexer.purge_expired_txids();
+            assert_eq!(indexer.purge_expired_txids(), 0);
             // The transaction has expired, but we give additional time before purging it.
             assert!(indexer.has_txid(&id));
 
@@ -334,9 +497,14 @@ mod tests {
             assert!(!indexer.has_txid(&id));
             indexer.insert_txid(&id, expiry - TX_EXPIRY_ADJUSTMENT - 1);
             assert!(indexer.has_txid(&id));
-            indexer.purge_expired_txids();
+            assert_eq!(indexer.purge_expired_txids(), 1);
             // Test that the expiry is completely over
             assert!(!indexer.has_txid(&id));
+
+            // A txid is purged once its expiry is reached
+            indexer.insert_txid(&id, expiry - TX_EXPIRY_ADJUSTMENT);
+            assert_eq!(indexer.purge_expired_txids(), 1);
+            assert!(!indexer.has_txid(&id));
         });
     }
 
@@ -344,22 +512,13 @@ mod tests {
     where
         F: FnOnce(Arc<Indexer>) -> () + panic::UnwindSafe,
     {
-        let mut tmp_dir = env::temp_dir();
-        {
-            let mut s = String::from("godcoin_test_");
-            let mut num: [u8; 8] = [0; 8];
-            randombytes::randombytes_into(&mut num);
-            s.push_str(&format!("{}", u64::from_be_bytes(num)));
-            tmp_dir.push(s);
-        }
-        fs::create_dir(&tmp_dir).expect(&format!("Could not create temp dir {:?}", &tmp_dir));
+        let tmp_dir = TmpDir::new();
 
         let result = panic::catch_unwind(|| {
             let indexer = Indexer::new(&tmp_dir);
             func(Arc::new(indexer));
         });
 
-        fs::remove_dir_all(&tmp_dir).expect("Failed to rm dir");
         assert!(result.is_ok());
     }
 }
@@ -1,20 +1,35 @@
-use parking_lot::Mutex;
-use std::{path::Path, sync::Arc};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem,
+    ops::Range,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 use tracing::info;
 
 pub mod block;
+pub mod bloom;
 pub mod error;
 pub mod index;
 pub mod receipt;
+pub mod reward;
 pub mod skip_flags;
+pub mod spend_limit;
 pub mod store;
 
 pub use self::{
     block::*,
+    bloom::BloomFilter,
     error::*,
     index::{IndexStatus, Indexer, WriteBatch},
     receipt::*,
-    store::{BlockStore, ReindexOpts},
+    reward::RewardSchedule,
+    spend_limit::SpendLimit,
+    store::{BlockRangeIter, BlockStore, ReindexOpts},
 };
 
 use crate::{
@@ -32,6 +47,10 @@ pub struct Properties {
     pub height: u64,
     pub owner: Box<TxVariant>,
     pub network_fee: Asset,
+    /// Height at which the current network fee was computed.
+    pub network_fee_height: u64,
+    /// Height at which the network fee is next recomputed.
+    pub next_fee_adjust_height: u64,
     pub token_supply: Asset,
 }
 
@@ -46,12 +65,52 @@ impl AccountInfo {
     pub fn total_fee(&self) -> Option<Asset> {
         self.net_fee.checked_add(self.account_fee)
     }
+
+    /// Returns the minimum fee and the minimum starting balance required for this account to
+    /// create a new account, or `None` if the computation overflows.
+    pub fn create_account_fees(&self) -> Option<(Asset, Asset)> {
+        let req_fee = self.total_fee()?.checked_mul(GRAEL_ACC_CREATE_FEE_MULT)?;
+        let min_bal = req_fee.checked_mul(GRAEL_ACC_CREATE_MIN_BAL_MULT)?;
+        Some((req_fee, min_bal))
+    }
+}
+
+/// Number of most recent blocks used to compute the transaction average in `ChainStats`.
+pub const STATS_WINDOW_BLOCKS: u64 = 100;
+
+/// Summary of the chain for explorers, see `Blockchain::stats`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainStats {
+    pub height: u64,
+    pub token_supply: Asset,
+    /// Account that receives the block rewards according to the current owner.
+    pub owner_id: AccountId,
+    pub account_count: u64,
+    /// Number of blocks in the window ending at `height`, up to `STATS_WINDOW_BLOCKS`.
+    pub window_blocks: u64,
+    /// Number of transactions in the blocks of the window.
+    pub window_txs: u64,
+}
+
+impl ChainStats {
+    /// Returns the average number of transactions per block over the window.
+    pub fn avg_txs_per_block(&self) -> f64 {
+        if self.window_blocks == 0 {
+            return 0.0;
+        }
+        self.window_txs as f64 / self.window_blocks as f64
+    }
 }
 
 #[derive(Debug)]
 pub struct Blockchain {
     indexer: Arc<Indexer>,
     store: Mutex<BlockStore>,
+    checkpoints: RwLock<BTreeMap<u64, Digest>>,
+    reward_schedule: RwLock<RewardSchedule>,
+    spend_limit: RwLock<Option<SpendLimit>>,
+    stats: Mutex<Option<ChainStats>>,
+    clock_skew_tolerance: AtomicU64,
 }
 
 impl Blockchain {
@@ -65,13 +124,102 @@ impl Blockchain {
         Blockchain {
             indexer,
             store: Mutex::new(store),
+            checkpoints: RwLock::new(BTreeMap::new()),
+            reward_schedule: RwLock::new(RewardSchedule::default()),
+            spend_limit: RwLock::new(None),
+            stats: Mutex::new(None),
+            clock_skew_tolerance: AtomicU64::new(0),
+        }
+    }
+
+    ///
+    /// Sets the trusted checkpoints as pairs of block height and block header hash.
+    ///
+    /// Blocks inserted at a checkpoint height must have a matching hash. A block matching its
+    /// checkpoint skips minter signature verification, along with the blocks inserted in the same
+    /// batch that it is proven to descend from by hash linkage, see `insert_block_batch`. All other
+    /// blocks are fully verified. Operators must only configure checkpoints from a trusted source
+    /// as a malicious checkpoint allows a forged chain to be accepted up to its height.
+    pub fn set_checkpoints(&self, checkpoints: Vec<(u64, Digest)>) {
+        *self.checkpoints.write() = checkpoints.into_iter().collect();
+    }
+
+    ///
+    /// Sets the schedule of the reward minted to the owner wallet for every block. No reward is
+    /// minted by default.
+    ///
+    /// The schedule is part of consensus, inserted blocks must have rewards equal to the fees plus
+    /// the scheduled reward at their height. All nodes of a network must use the same schedule and
+    /// it must not be changed once blocks have been produced with it.
+    ///
+    pub fn set_reward_schedule(&self, schedule: RewardSchedule) {
+        *self.reward_schedule.write() = schedule;
+    }
+
+    #[inline]
+    pub fn reward_schedule(&self) -> RewardSchedule {
+        *self.reward_schedule.read()
+    }
+
+    ///
+    /// Sets the limit of the amount every account may transfer within a window of recent blocks,
+    /// transfers are not limited when `None`. No limit is set by default.
+    ///
+    /// The limit is part of consensus, blocks containing transfers that exceed it are rejected.
+    /// All nodes of a network must use the same limit.
+    ///
+    /// Panics if the window of the limit exceeds `MAX_SPEND_LIMIT_WINDOW`.
+    ///
+    pub fn set_spend_limit(&self, limit: Option<SpendLimit>) {
+        if let Some(limit) = limit {
+            assert!(
+                limit.window <= spend_limit::MAX_SPEND_LIMIT_WINDOW,
+                "spend limit window exceeds {} blocks",
+                spend_limit::MAX_SPEND_LIMIT_WINDOW
+            );
         }
+        *self.spend_limit.write() = limit;
+    }
+
+    #[inline]
+    pub fn spend_limit(&self) -> Option<SpendLimit> {
+        *self.spend_limit.read()
+    }
+
+    ///
+    /// Sets the number of seconds a transaction may be past its expiry, or beyond the maximum
+    /// expiry time, and still be accepted into the receipt pool. This tolerates the local clock
+    /// being slightly out of sync with the clocks of the senders. Transaction IDs remain indexed
+    /// until the expiry plus the tolerance to prevent replays.
+    ///
+    pub fn set_clock_skew_tolerance(&self, secs: u64) {
+        self.clock_skew_tolerance.store(secs, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn clock_skew_tolerance(&self) -> u64 {
+        self.clock_skew_tolerance.load(Ordering::Acquire)
     }
 
     pub fn is_empty(&self) -> bool {
         self.store.lock().is_empty()
     }
 
+    ///
+    /// Pre-loads the most recent `count` blocks into the block cache so initial requests avoid
+    /// reading them from disk, returning the number of cached blocks. At most 100 blocks are
+    /// cached and a count of 0 clears the cache. Newly inserted blocks are always cached.
+    ///
+    pub fn warmup(&self, count: u64) -> usize {
+        self.store.lock().warmup(count)
+    }
+
+    /// Returns the heights of the blocks in the block cache in ascending order. The genesis block
+    /// is always held separately from the cache.
+    pub fn cached_block_heights(&self) -> Vec<u64> {
+        self.store.lock().cached_heights()
+    }
+
     #[inline]
     pub fn indexer(&self) -> Arc<Indexer> {
         Arc::clone(&self.indexer)
@@ -81,6 +229,17 @@ impl Blockchain {
         self.indexer.index_status()
     }
 
+    /// Removes destroyed accounts with no remaining balance from the index, returning the number of
+    /// accounts pruned. The owner wallet is never pruned. Pruned accounts are no longer returned by
+    /// `get_account`, while their IDs stay reserved so a new account can't be created with them.
+    pub fn prune_destroyed_accounts(&self) -> usize {
+        let owner_wallet = match self.get_owner() {
+            TxVariant::V0(TxVariantV0::OwnerTx(tx)) => tx.wallet,
+            _ => panic!("expected owner transaction"),
+        };
+        self.indexer.prune_destroyed_accounts(owner_wallet)
+    }
+
     pub fn reindex(&self, opts: ReindexOpts) {
         {
             let status = self.indexer.index_status();
@@ -88,32 +247,24 @@ impl Blockchain {
                 panic!("expected index status to be None, got: {:?}", status);
             }
         }
+        let current_time = crate::get_epoch_time();
+        let tolerance = self.clock_skew_tolerance();
+        // Transactions in blocks within the expiry window are collected in the same pass over the
+        // block log to rebuild the tx expiry index.
+        let mut unexpired_txids = vec![];
+
         let mut store = self.store.lock();
-        if store.get_chain_height() == 0 {
-            // Attempt to read the raw block stored at byte position 0, which must _always_ be the
-            // genesis block. Then, we find the owner wallet account creation and forcibly index it.
-            // This will prevent the receipt index process from choking when the creation account is
-            // non-existent since the genesis block is the beginning of the chain.
-            if let Ok(genesis_block) = store.raw_read_from_disk(0) {
-                let receipts = genesis_block.receipts();
-                // Two transactions: the first is the creation of the owner wallet, and the second
-                // is the configuration of the owner transaction.
-                assert_eq!(receipts.len(), 2);
-                for r in receipts {
-                    match &r.tx {
-                        TxVariant::V0(tx) => {
-                            if let TxVariantV0::CreateAccountTx(tx) = tx {
-                                let mut batch = WriteBatch::new(self.indexer());
-                                batch.insert_or_update_account(tx.account.clone());
-                                batch.commit();
-                            }
-                        }
-                    }
-                }
-            }
-        }
         store.reindex_blocks(opts, |batch, block| {
-            self.index_block(batch, block);
+            if current_time - block.timestamp() <= TX_MAX_EXPIRY_TIME + tolerance {
+                unexpired_txids.extend(self.unexpired_txids(block, current_time));
+            }
+            if block.height() == 0 {
+                // The genesis block creates the owner wallet without a creator to charge, index it
+                // the same way as when the chain was created.
+                Self::index_genesis_block(batch, block);
+            } else {
+                self.index_block(batch, block);
+            }
             if block.height() % 1000 == 0 {
                 info!("Indexed block {}", block.height());
             }
@@ -121,38 +272,74 @@ impl Blockchain {
 
         info!("Rebuilding tx expiry index");
         let indexer = self.indexer();
-        let current_time = crate::get_epoch_time();
-        // Iterate in reverse from head to genesis block
-        for height in (0..=self.get_chain_height()).rev() {
-            let block = store.get(height).unwrap();
-            if current_time - block.timestamp() <= TX_MAX_EXPIRY_TIME {
-                for receipt in block.receipts() {
-                    let data = TxPrecompData::from_tx(&receipt.tx);
-                    let expiry = data.tx().expiry();
-                    if expiry > current_time {
-                        indexer.insert_txid(data.txid(), expiry);
-                    }
-                }
-            } else {
-                // Break early as all transactions are guaranteed to be expired.
-                break;
-            }
+        for (txid, expiry) in unexpired_txids {
+            indexer.insert_txid(&txid, expiry);
         }
 
+        debug_assert_eq!(
+            Asset::checked_sum(
+                indexer
+                    .iter_accounts(None, usize::MAX)
+                    .into_iter()
+                    .map(|(_, acc)| acc.balance)
+            ),
+            Some(indexer.get_token_supply()),
+            "reindexed account balances must add up to the token supply"
+        );
+
         info!("Reindexing complete");
     }
 
     pub fn get_properties(&self) -> Properties {
+        let height = self.get_chain_height();
+        let network_fee_height = network_fee_height(height);
         Properties {
-            height: self.get_chain_height(),
+            height,
             owner: Box::new(self.get_owner()),
             token_supply: self.indexer.get_token_supply(),
             network_fee: self
                 .get_network_fee()
                 .expect("unexpected error retrieving network fee"),
+            network_fee_height,
+            next_fee_adjust_height: network_fee_height + NETWORK_FEE_ADJUST_INTERVAL,
         }
     }
 
+    /// Returns a summary of the chain at the current height. The summary requires counting every
+    /// account and scanning the most recent blocks so it is cached until the next block.
+    pub fn stats(&self) -> ChainStats {
+        let height = self.get_chain_height();
+        let mut cache = self.stats.lock();
+        if let Some(stats) = cache.as_ref() {
+            if stats.height == height {
+                return stats.clone();
+            }
+        }
+
+        let owner_id = match self.get_owner() {
+            TxVariant::V0(TxVariantV0::OwnerTx(owner)) => owner.wallet,
+            _ => unreachable!("indexed owner must be an owner tx"),
+        };
+        let window_start = (height + 1).saturating_sub(STATS_WINDOW_BLOCKS);
+        let mut window_txs = 0;
+        for h in window_start..=height {
+            if let Some(block) = self.get_block(h) {
+                window_txs += block.receipts().len() as u64;
+            }
+        }
+
+        let stats = ChainStats {
+            height,
+            token_supply: self.indexer.get_token_supply(),
+            owner_id,
+            account_count: self.indexer.account_count(),
+            window_blocks: height + 1 - window_start,
+            window_txs,
+        };
+        *cache = Some(stats.clone());
+        stats
+    }
+
     #[inline]
     pub fn get_owner(&self) -> TxVariant {
         self.indexer
@@ -160,11 +347,42 @@ impl Blockchain {
             .expect("Failed to retrieve owner from index")
     }
 
+    /// Returns the public configuration the chain was created with in the genesis block, or `None`
+    /// when the chain is empty. The current owner may differ if ownership was transferred since.
+    pub fn get_genesis_details(&self) -> Option<GenesisDetails> {
+        let genesis = self.get_block(0)?;
+        let mut owner_account = None;
+        let mut minter_key = None;
+        for receipt in genesis.receipts() {
+            match &receipt.tx {
+                TxVariant::V0(TxVariantV0::CreateAccountTx(tx)) => {
+                    owner_account = Some(tx.account.clone())
+                }
+                TxVariant::V0(TxVariantV0::OwnerTx(tx)) => minter_key = Some(tx.minter.clone()),
+                _ => {}
+            }
+        }
+        let owner_account = owner_account.expect("genesis block must create the owner account");
+        Some(GenesisDetails {
+            owner_id: owner_account.id,
+            minter_key: minter_key.expect("genesis block must set the owner"),
+            owner_permissions: owner_account.permissions,
+        })
+    }
+
     #[inline]
     pub fn get_chain_height(&self) -> u64 {
         self.indexer.get_chain_height()
     }
 
+    /// Returns the height of the most recent block in the block store. While blocks are inserted
+    /// with `insert_block_batch`, this includes the blocks whose index updates are pending, unlike
+    /// `get_chain_height`. Fees and spend limits are derived from this height so that every block
+    /// is verified against the blocks before it.
+    fn get_store_height(&self) -> u64 {
+        self.store.lock().get_chain_height()
+    }
+
     pub fn get_chain_head(&self) -> Arc<Block> {
         let store = self.store.lock();
         let height = store.get_chain_height();
@@ -176,6 +394,45 @@ impl Blockchain {
         store.get(height)
     }
 
+    /// Returns the header of the block at the specified height without decoding the receipts of
+    /// blocks that have to be read from disk.
+    pub fn get_block_header(&self, height: u64) -> Option<BlockHeader> {
+        let store = self.store.lock();
+        store.get_header(height)
+    }
+
+    /// Returns the height of the last block with a timestamp at or before the specified timestamp,
+    /// or `None` when the chain is empty or the genesis block is after it. Block timestamps are
+    /// expected to be non-decreasing, which allows a binary search over the block headers. If the
+    /// timestamps of the chain regress, the returned height is a block at or before the timestamp
+    /// but may not be the last one.
+    pub fn height_at_or_before(&self, timestamp: u64) -> Option<u64> {
+        let store = self.store.lock();
+        if store.is_empty() {
+            return None;
+        }
+        let block_timestamp = |height| match store.get_header(height) {
+            Some(BlockHeader::V0(header)) => header.timestamp,
+            None => panic!("failed to read block header at height {}", height),
+        };
+        if block_timestamp(0) > timestamp {
+            return None;
+        }
+
+        // The block at the low height is always at or before the timestamp
+        let mut low = 0;
+        let mut high = store.get_chain_height();
+        while low < high {
+            let mid = high - (high - low) / 2;
+            if block_timestamp(mid) <= timestamp {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        Some(low)
+    }
+
     /// Gets a filtered block using the `filter` at the specified `height`. This does not match
     /// whether the `filter` contains an owner account to match block rewards.
     pub fn get_filtered_block(&self, height: u64, filter: &BlockFilter) -> Option<FilteredBlock> {
@@ -187,39 +444,10 @@ impl Blockchain {
                 let has_match = if filter.is_empty() {
                     false
                 } else {
-                    block.receipts().iter().any(|receipt| match &receipt.tx {
-                        TxVariant::V0(tx) => match tx {
-                            TxVariantV0::OwnerTx(owner_tx) => filter.contains(&owner_tx.wallet),
-                            TxVariantV0::MintTx(mint_tx) => filter.contains(&mint_tx.to),
-                            TxVariantV0::CreateAccountTx(create_acc_tx) => {
-                                filter.contains(&create_acc_tx.creator)
-                                    || filter.contains(&create_acc_tx.account.id)
-                            }
-                            TxVariantV0::UpdateAccountTx(update_acc_tx) => {
-                                filter.contains(&update_acc_tx.account_id)
-                            }
-                            TxVariantV0::TransferTx(transfer_tx) => {
-                                if filter.contains(&transfer_tx.from) {
-                                    return true;
-                                }
-                                for entry in &receipt.log {
-                                    match entry {
-                                        LogEntry::Transfer(to_acc, _) => {
-                                            if filter.contains(to_acc) {
-                                                return true;
-                                            }
-                                        }
-                                        LogEntry::Destroy(to_acc) => {
-                                            if filter.contains(to_acc) {
-                                                return true;
-                                            }
-                                        }
-                                    }
-                                }
-                                false
-                            }
-                        },
-                    })
+                    block
+                        .receipts()
+                        .iter()
+                        .any(|receipt| receipt.matches_filter(filter))
                 };
                 if has_match {
                     Some(FilteredBlock::Block(block))
@@ -275,6 +503,8 @@ impl Blockchain {
                                         acc.balance = acc.balance.checked_add(*amount)?;
                                     }
                                 }
+                                // Fees are already subtracted from the sender above
+                                LogEntry::Fee(_) => {}
                                 LogEntry::Destroy(_to_acc) => {
                                     if tx.from == id {
                                         acc.destroyed = true;
@@ -298,6 +528,12 @@ impl Blockchain {
         Some(acc)
     }
 
+    /// Returns the indexed accounts with the specified IDs. Unlike `get_account`, pending receipts
+    /// are not applied. Accounts that don't exist are absent from the returned map.
+    pub fn get_accounts(&self, ids: &[AccountId]) -> HashMap<AccountId, Account> {
+        self.indexer.get_accounts(ids)
+    }
+
     pub fn get_account_info(
         &self,
         id: AccountId,
@@ -340,7 +576,7 @@ impl Blockchain {
             handle_receipt_match!(r);
         }
 
-        for i in (0..=self.get_chain_height()).rev() {
+        for i in (0..=self.get_store_height()).rev() {
             delta += 1;
             let block = self.get_block(i).unwrap();
             for r in block.receipts() {
@@ -354,17 +590,38 @@ impl Blockchain {
         GRAEL_FEE_MIN.checked_mul(GRAEL_FEE_MULT.checked_pow(count as u16)?)
     }
 
+    /// Returns the total amount transferred from the account by the additional receipts and the
+    /// receipts of the `window` most recent blocks. Every block within the window is read, see
+    /// `spend_limit::MAX_SPEND_LIMIT_WINDOW`.
+    pub fn get_spent_amount(
+        &self,
+        id: AccountId,
+        additional_receipts: &[Receipt],
+        window: u64,
+    ) -> Option<Asset> {
+        let height = self.get_store_height();
+        let min_height = (height + 1).saturating_sub(window);
+        let blocks: Vec<_> = (min_height..=height)
+            .map(|h| self.get_block(h).unwrap())
+            .collect();
+
+        let receipts = additional_receipts
+            .iter()
+            .chain(blocks.iter().flat_map(|block| block.receipts()));
+        Asset::checked_sum(receipts.filter_map(|r| match &r.tx {
+            TxVariant::V0(TxVariantV0::TransferTx(tx)) if tx.from == id => Some(tx.amount),
+            _ => None,
+        }))
+    }
+
     pub fn get_network_fee(&self) -> Option<Asset> {
         // The network fee adjusts every 5 blocks so that users have a bigger time
         // frame to confirm the fee they want to spend without suddenly changing.
-        use crate::constants::*;
-        let max_height = self.get_chain_height();
-        let max_height = max_height - (max_height % 5);
-        let min_height = if max_height > NETWORK_FEE_AVG_WINDOW {
-            max_height - NETWORK_FEE_AVG_WINDOW
-        } else {
-            0
-        };
+        //
+        // Chains below a height of 5 round down to the genesis block, which is always included in
+        // the window until the chain grows past the averaging window.
+        let max_height = network_fee_height(self.get_store_height());
+        let min_height = max_height.saturating_sub(NETWORK_FEE_AVG_WINDOW);
 
         let mut count: u64 = 1;
         for i in min_height..=max_height {
@@ -379,22 +636,114 @@ impl Blockchain {
     }
 
     pub fn insert_block(&self, block: Block) -> Result<(), BlockErr> {
-        static SKIP_FLAGS: SkipFlags = SKIP_NONE;
-        self.verify_block(&block, &self.get_chain_head(), SKIP_FLAGS)?;
+        self.insert_block_batch(vec![block])
+    }
+
+    /// Inserts the blocks in order, verifying each block against the block inserted before it.
+    /// Every block is written to the block log as soon as it is verified, while the index updates
+    /// of consecutive blocks are committed together to reduce the number of index writes.
+    ///
+    /// Fees and spend limits are derived from the inserted blocks including the pending ones, see
+    /// `get_store_height`. Accounts are read from the committed index, so the pending updates are
+    /// committed and the block verified again when its executed transactions involve an account
+    /// changed by them or it fails verification. The pending updates are also committed after a
+    /// block that changes the owner or an account's script, permissions or existence. At most
+    /// `MAX_CACHE_SIZE` blocks are pending so their positions in the block log are never needed
+    /// before being committed.
+    ///
+    /// When a block fails verification, the blocks before it remain inserted and the error is
+    /// returned.
+    pub fn insert_block_batch(&self, blocks: Vec<Block>) -> Result<(), BlockErr> {
         let mut batch = WriteBatch::new(Arc::clone(&self.indexer));
-        self.index_block(&mut batch, &block);
-        self.store.lock().insert(&mut batch, block);
-        batch.commit();
+        let mut pending = 0;
+        let mut commit_before_next = false;
+        let mut res = Ok(());
+
+        let proven = self.checkpoint_ancestors(&blocks);
+        for (i, block) in blocks.into_iter().enumerate() {
+            if pending > 0 && (commit_before_next || pending >= store::MAX_CACHE_SIZE) {
+                mem::replace(&mut batch, WriteBatch::new(Arc::clone(&self.indexer))).commit();
+                pending = 0;
+                commit_before_next = false;
+            }
 
-        Ok(())
+            let skip_flags = if proven.contains(&i) {
+                SKIP_BLOCK_SIGNATURE
+            } else {
+                SKIP_NONE
+            };
+            let mut verified = self.verify_block(&block, &self.get_chain_head(), skip_flags);
+            let depends_on_batch = match &verified {
+                Ok(logs) => block
+                    .receipts()
+                    .iter()
+                    .zip(logs)
+                    .any(|(r, log)| tx_touches_any(&r.tx, log, |id| batch.has_account(id))),
+                Err(_) => true,
+            };
+            if pending > 0 && depends_on_batch {
+                mem::replace(&mut batch, WriteBatch::new(Arc::clone(&self.indexer))).commit();
+                pending = 0;
+                verified = self.verify_block(&block, &self.get_chain_head(), skip_flags);
+            }
+            let logs = match verified {
+                Ok(logs) => logs,
+                Err(e) => {
+                    res = Err(e);
+                    break;
+                }
+            };
+
+            self.index_block(&mut batch, &block);
+            // Transactions broadcasted through the receipt pool are already indexed, but blocks may
+            // contain transactions that never passed through the pool.
+            for (txid, expiry) in self.unexpired_txids(&block, crate::get_epoch_time()) {
+                batch.insert_txid(txid, expiry);
+            }
+            commit_before_next |= block
+                .receipts()
+                .iter()
+                .zip(&logs)
+                .any(|(r, log)| changes_account_state(&r.tx, log));
+            self.store.lock().insert(&mut batch, block);
+            pending += 1;
+        }
+
+        if pending > 0 {
+            batch.commit();
+        }
+        res
+    }
+
+    /// Returns the range of blocks that are proven to be ancestors of a checkpoint. Walking back
+    /// from the last block matching its checkpoint hash, each block is proven by the previous hash
+    /// of the block after it. Only these blocks may skip minter signature verification.
+    fn checkpoint_ancestors(&self, blocks: &[Block]) -> Range<usize> {
+        let checkpoints = self.checkpoints.read();
+        let end = blocks.iter().rposition(|block| {
+            checkpoints.contains_key(&block.height())
+                && checkpoints.get(&block.height()) == Some(&block.calc_header_hash())
+        });
+        match end {
+            Some(end) => {
+                let mut start = end;
+                while start > 0 && blocks[start].verify_previous_hash(&blocks[start - 1]) {
+                    start -= 1;
+                }
+                start..end + 1
+            }
+            None => 0..0,
+        }
     }
 
+    /// Verifies the block against the previous block, returning the logs of its executed
+    /// transactions.
     fn verify_block(
         &self,
         block: &Block,
         prev_block: &Block,
         skip_flags: SkipFlags,
-    ) -> Result<(), BlockErr> {
+    ) -> Result<Vec<Vec<LogEntry>>, BlockErr> {
         if prev_block.height() + 1 != block.height() {
             return Err(BlockErr::InvalidBlockHeight);
         } else if !block.verify_receipt_root() {
@@ -403,6 +752,18 @@ impl Blockchain {
             return Err(BlockErr::InvalidPrevHash);
         }
 
+        if let Some(hash) = self.checkpoints.read().get(&block.height()) {
+            if &block.calc_header_hash() != hash {
+                return Err(BlockErr::CheckpointMismatch);
+            }
+        }
+
+        let reward = self.reward_schedule().reward_at(block.height());
+        match calc_fees(block.receipts()).and_then(|fees| fees.checked_add(reward)) {
+            Some(rewards) if rewards == block.rewards() => {}
+            _ => return Err(BlockErr::InvalidReward),
+        }
+
         let block_signer = block.signer().ok_or(BlockErr::InvalidSignature)?;
         match self.get_owner() {
             TxVariant::V0(tx) => match tx {
@@ -415,28 +776,47 @@ impl Blockchain {
             },
         }
 
-        if !block_signer.verify(block.calc_header_hash().as_ref()) {
+        if skip_flags & SKIP_BLOCK_SIGNATURE == 0
+            && !block_signer.verify(block.calc_header_hash().as_ref())
+        {
             return Err(BlockErr::InvalidSignature);
         }
 
         let block_receipts = block.receipts();
         let len = block_receipts.len();
+        let cache = ScriptCache::new();
+        let mut logs = Vec::with_capacity(len);
         for i in 0..len {
             let r = &block_receipts[i];
             let receipts = &block_receipts[0..i];
-            if let Err(e) = self.execute_tx(&TxPrecompData::from_tx(&r.tx), receipts, skip_flags) {
-                return Err(BlockErr::Tx(e));
+            let data = TxPrecompData::from_tx(&r.tx);
+            match self.execute_tx_with_cache(&data, receipts, skip_flags, &cache) {
+                Ok(log) => logs.push(log),
+                Err(e) => return Err(BlockErr::Tx(e)),
             }
         }
 
-        Ok(())
+        Ok(logs)
     }
 
     pub fn execute_tx(
+        &self,
+        data: &TxPrecompData,
+        additional_receipts: &[Receipt],
+        skip_flags: SkipFlags,
+    ) -> Result<Vec<LogEntry>, TxErr> {
+        let cache = ScriptCache::new();
+        self.execute_tx_with_cache(data, additional_receipts, skip_flags, &cache)
+    }
+
+    /// Executes the transaction reusing decoded scripts from the cache. The cache should only be
+    /// shared between transactions that are executed together, such as the receipts of a block.
+    pub fn execute_tx_with_cache(
         &self,
         data: &TxPrecompData,
         additional_receipts: &[Receipt],
         _skip_flags: SkipFlags,
+        cache: &ScriptCache,
     ) -> Result<Vec<LogEntry>, TxErr> {
         macro_rules! check_zero_fee {
             ($asset:expr) => {
@@ -459,6 +839,8 @@ impl Blockchain {
 
         if tx.sigs().len() > MAX_TX_SIGNATURES {
             return Err(TxErr::TooManySignatures);
+        } else if tx.has_duplicate_sig_keys() {
+            return Err(TxErr::DuplicateSignature);
         }
 
         match tx {
@@ -487,7 +869,7 @@ impl Blockchain {
                         chain: self,
                         additional_receipts,
                     };
-                    if let Err(e) = ScriptEngine::new(data).eval() {
+                    if let Err(e) = ScriptEngine::with_cache(data, cache).eval() {
                         return Err(TxErr::ScriptEval(e));
                     }
                     Ok(vec![])
@@ -495,6 +877,10 @@ impl Blockchain {
                 TxVariantV0::MintTx(mint_tx) => {
                     check_zero_fee!(tx.fee);
                     check_pos_amt!(mint_tx.amount);
+                    match self.get_account(mint_tx.to, additional_receipts) {
+                        Some(ref acc) if !acc.destroyed => {}
+                        _ => return Err(TxErr::AccountNotFound),
+                    }
 
                     let owner = match self.get_owner() {
                         TxVariant::V0(tx) => match tx {
@@ -511,7 +897,7 @@ impl Blockchain {
                         chain: self,
                         additional_receipts,
                     };
-                    if let Err(e) = ScriptEngine::new(data).eval() {
+                    if let Err(e) = ScriptEngine::with_cache(data, cache).eval() {
                         return Err(TxErr::ScriptEval(e));
                     }
 
@@ -527,12 +913,16 @@ impl Blockchain {
                     let new_acc = &create_account_tx.account;
 
                     if new_acc.script.len() > MAX_SCRIPT_BYTE_SIZE {
-                        return Err(TxErr::TxTooLarge);
+                        return Err(TxErr::ScriptTooLarge);
                     } else if new_acc.destroyed {
                         return Err(TxErr::TxProhibited);
                     } else if !new_acc.permissions.is_valid() {
                         return Err(TxErr::InvalidAccountPermissions);
-                    } else if self.indexer.account_exists(new_acc.id) {
+                    } else if new_acc.serialized_size() > MAX_ACCOUNT_BYTE_SIZE {
+                        return Err(TxErr::AccountTooLarge);
+                    } else if self.indexer.account_exists(new_acc.id)
+                        || self.indexer.is_account_pruned(new_acc.id)
+                    {
                         return Err(TxErr::AccountAlreadyExists);
                     }
 
@@ -556,13 +946,8 @@ impl Blockchain {
                     };
 
                     {
-                        let req_fee = creator_acc_info
-                            .total_fee()
-                            .ok_or(TxErr::Arithmetic)?
-                            .checked_mul(GRAEL_ACC_CREATE_FEE_MULT)
-                            .ok_or(TxErr::Arithmetic)?;
-                        let min_bal = req_fee
-                            .checked_mul(GRAEL_ACC_CREATE_MIN_BAL_MULT)
+                        let (req_fee, min_bal) = creator_acc_info
+                            .create_account_fees()
                             .ok_or(TxErr::Arithmetic)?;
 
                         if tx.fee < req_fee {
@@ -594,7 +979,7 @@ impl Blockchain {
                         )));
                     }
 
-                    Ok(vec![])
+                    Ok(with_fee_entry(tx.fee, vec![]))
                 }
                 TxVariantV0::UpdateAccountTx(update_acc_tx) => {
                     let acc_info = match self
@@ -608,7 +993,7 @@ impl Blockchain {
                         return Err(TxErr::TxProhibited);
                     } else if let Some(script) = &update_acc_tx.new_script {
                         if script.len() > MAX_SCRIPT_BYTE_SIZE {
-                            return Err(TxErr::TxTooLarge);
+                            return Err(TxErr::ScriptTooLarge);
                         }
                     } else if let Some(perms) = &update_acc_tx.new_permissions {
                         if !perms.is_valid() {
@@ -616,6 +1001,19 @@ impl Blockchain {
                         }
                     }
 
+                    {
+                        let mut updated_acc = acc_info.account.clone();
+                        if let Some(script) = &update_acc_tx.new_script {
+                            updated_acc.script = script.clone();
+                        }
+                        if let Some(perms) = &update_acc_tx.new_permissions {
+                            updated_acc.permissions = perms.clone();
+                        }
+                        if updated_acc.serialized_size() > MAX_ACCOUNT_BYTE_SIZE {
+                            return Err(TxErr::AccountTooLarge);
+                        }
+                    }
+
                     {
                         let req_fee = acc_info
                             .total_fee()
@@ -648,11 +1046,11 @@ impl Blockchain {
                         )));
                     }
 
-                    Ok(vec![])
+                    Ok(with_fee_entry(tx.fee, vec![]))
                 }
                 TxVariantV0::TransferTx(transfer) => {
                     if transfer.memo.len() > MAX_MEMO_BYTE_SIZE {
-                        return Err(TxErr::TxTooLarge);
+                        return Err(TxErr::MemoTooLarge);
                     }
                     check_pos_amt!(transfer.amount);
 
@@ -672,14 +1070,26 @@ impl Blockchain {
                         .ok_or(TxErr::Arithmetic)?;
                     check_pos_amt!(bal);
 
+                    if let Some(limit) = self.spend_limit() {
+                        let spent = self
+                            .get_spent_amount(transfer.from, additional_receipts, limit.window)
+                            .and_then(|spent| spent.checked_add(transfer.amount))
+                            .ok_or(TxErr::Arithmetic)?;
+                        if spent > limit.max_amount {
+                            return Err(TxErr::RateLimited);
+                        }
+                    }
+
                     let data = EngineData {
                         script: info.account.script.into(),
                         tx_data: data.into(),
                         chain: self,
                         additional_receipts,
                     };
-                    let log = ScriptEngine::new(data).eval().map_err(TxErr::ScriptEval)?;
-                    Ok(log)
+                    let log = ScriptEngine::with_cache(data, cache)
+                        .eval()
+                        .map_err(TxErr::ScriptEval)?;
+                    Ok(with_fee_entry(tx.fee, log))
                 }
             },
         }
@@ -699,6 +1109,45 @@ impl Blockchain {
             }
             _ => panic!("expected owner transaction"),
         };
+        // Fees are moved from the senders to the owner wallet, only the block reward is minted
+        let fees = calc_fees(block.receipts()).unwrap();
+        let reward = block.rewards().checked_sub(fees).unwrap();
+        if reward.amount != 0 {
+            batch.add_token_supply(reward);
+        }
+    }
+
+    fn index_genesis_block(batch: &mut WriteBatch, block: &Block) {
+        for r in block.receipts() {
+            match &r.tx {
+                TxVariant::V0(TxVariantV0::OwnerTx(_)) => batch.set_owner(r.tx.clone()),
+                TxVariant::V0(TxVariantV0::CreateAccountTx(tx)) => {
+                    batch.insert_or_update_account(tx.account.clone());
+                }
+                _ => panic!("unexpected genesis transaction: {:?}", r.tx),
+            }
+        }
+    }
+
+    /// Returns the IDs of the block's transactions that have not expired at the current time along
+    /// with their expiry, including the clock skew tolerance, to be indexed for replay protection.
+    fn unexpired_txids(&self, block: &Block, current_time: u64) -> Vec<(TxId, u64)> {
+        let tolerance = self.clock_skew_tolerance();
+        block
+            .receipts()
+            .iter()
+            .filter_map(|receipt| {
+                if !receipt
+                    .tx
+                    .is_expired(current_time.saturating_sub(tolerance))
+                {
+                    let expiry = receipt.tx.expiry().saturating_add(tolerance);
+                    Some((receipt.tx.calc_txid(), expiry))
+                } else {
+                    None
+                }
+            })
+            .collect()
     }
 
     fn index_receipt(batch: &mut WriteBatch, receipt: &Receipt) {
@@ -731,6 +1180,8 @@ impl Blockchain {
                     for entry in &receipt.log {
                         match entry {
                             LogEntry::Transfer(to_acc, amount) => batch.add_bal(*to_acc, *amount),
+                            // Fees are already subtracted from the sender above
+                            LogEntry::Fee(_) => {}
                             LogEntry::Destroy(to_acc) => {
                                 let from_acc = batch.get_account_mut(tx.from);
                                 let from_cur_bal = from_acc.balance;
@@ -747,8 +1198,19 @@ impl Blockchain {
 
     pub fn create_genesis_block(&self, minter_key: KeyPair) -> GenesisBlockInfo {
         let info = GenesisBlockInfo::new(minter_key, 0);
-        let timestamp = crate::get_epoch_time();
+        self.create_genesis_block_at(info, crate::get_epoch_time())
+    }
 
+    ///
+    /// Creates the genesis block from the provided keys and timestamp rather than randomly
+    /// generated wallet keys and the current time. Chains created with the same info and timestamp
+    /// have identical genesis blocks, allowing reproducible test fixtures.
+    ///
+    pub fn create_genesis_block_at(
+        &self,
+        info: GenesisBlockInfo,
+        timestamp: u64,
+    ) -> GenesisBlockInfo {
         let owner_wallet = Account {
             id: info.owner_id,
             balance: Asset::default(),
@@ -767,7 +1229,7 @@ impl Blockchain {
                 fee: Asset::default(),
                 signature_pairs: Vec::new(),
             },
-            account: owner_wallet.clone(),
+            account: owner_wallet,
             creator: 0,
         }));
 
@@ -788,7 +1250,7 @@ impl Blockchain {
                 log: vec![],
             },
             Receipt {
-                tx: owner_tx.clone(),
+                tx: owner_tx,
                 log: vec![],
             },
         ];
@@ -808,9 +1270,12 @@ impl Blockchain {
         block.sign(&info.minter_key);
 
         let mut batch = WriteBatch::new(Arc::clone(&self.indexer));
+        Self::index_genesis_block(&mut batch, &block);
+        for (txid, expiry) in self.unexpired_txids(&block, crate::get_epoch_time()) {
+            batch.insert_txid(txid, expiry);
+        }
+        batch.set_chain_height(0);
         self.store.lock().insert_genesis(&mut batch, block);
-        batch.set_owner(owner_tx);
-        batch.insert_or_update_account(owner_wallet);
         batch.commit();
         self.indexer.set_index_status(IndexStatus::Complete);
 
@@ -818,6 +1283,49 @@ impl Blockchain {
     }
 }
 
+/// Returns whether indexing the transaction with its executed log changes the owner or an account's
+/// script, permissions or existence, which any later transaction may depend on.
+fn changes_account_state(tx: &TxVariant, log: &[LogEntry]) -> bool {
+    match tx {
+        TxVariant::V0(tx) => match tx {
+            TxVariantV0::OwnerTx(_)
+            | TxVariantV0::CreateAccountTx(_)
+            | TxVariantV0::UpdateAccountTx(_) => true,
+            TxVariantV0::MintTx(_) => false,
+            TxVariantV0::TransferTx(_) => log
+                .iter()
+                .any(|entry| matches!(entry, LogEntry::Destroy(_))),
+        },
+    }
+}
+
+/// Returns the height the network fee is computed at for a chain at the specified height.
+fn network_fee_height(height: u64) -> u64 {
+    height - (height % NETWORK_FEE_ADJUST_INTERVAL)
+}
+
+/// Prepends a fee entry to the log of a fee-paying transaction so the log describes every balance
+/// change of the transaction. Transactions without a fee have no fee entry.
+fn with_fee_entry(fee: Asset, log: Vec<LogEntry>) -> Vec<LogEntry> {
+    if fee.amount == 0 {
+        return log;
+    }
+    let mut entries = Vec::with_capacity(log.len() + 1);
+    entries.push(LogEntry::Fee(fee));
+    entries.extend(log);
+    entries
+}
+
+/// Public configuration of a chain recorded in its genesis block. Private keys are never stored in
+/// the chain and are not included.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenesisDetails {
+    pub owner_id: AccountId,
+    pub minter_key: PublicKey,
+    pub owner_permissions: Permissions,
+}
+
+#[derive(Clone)]
 pub struct GenesisBlockInfo {
     pub owner_id: AccountId,
     pub minter_key: KeyPair,
@@ -860,3 +1368,176 @@ impl GenesisBlockInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn insert_block_batch_matches_insert_block() {
+        let accounts: Vec<_> = (1..=3)
+            .map(|id| fixture_account(id, "100.00000 TEST"))
+            .collect();
+        let source = fixture_chain(0);
+        let batched = fixture_chain(0);
+        for fixture in &[&source, &batched] {
+            for (acc, _) in &accounts {
+                fixture.insert_account(acc.clone());
+            }
+        }
+
+        // Transfers in consecutive blocks depend on the index updates of the previous block
+        let mut txs = vec![];
+        for i in 0..12 {
+            let tx = match i % 4 {
+                0 | 1 => {
+                    let from = &accounts[i % 2];
+                    let to = accounts[i % 2 + 1].0.id;
+                    let amount = format!("{}.00000 TEST", i + 1);
+                    signed_transfer(from, to, &amount, "1.00000 TEST", i as u32)
+                }
+                2 => {
+                    let info = &source.genesis_info;
+                    let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+                        base: tx_header(i as u32, FIXTURE_TX_EXPIRY, "0.00000 TEST"),
+                        to: accounts[2].0.id,
+                        amount: get_asset("10.00000 TEST"),
+                        attachment: vec![],
+                        attachment_name: "".to_owned(),
+                    }));
+                    tx.append_sign(&info.wallet_keys[1]);
+                    tx.append_sign(&info.wallet_keys[0]);
+                    tx
+                }
+                _ => {
+                    source.produce_block(vec![]).unwrap();
+                    continue;
+                }
+            };
+            let data = tx.clone().precompute();
+            let log = source.chain.execute_tx(&data, &[], SKIP_NONE).unwrap();
+            source
+                .produce_block(vec![Receipt {
+                    tx: tx.clone(),
+                    log,
+                }])
+                .unwrap();
+            txs.push(tx);
+        }
+
+        let height = source.chain.get_chain_height();
+        let blocks = (1..=height)
+            .map(|h| source.chain.get_block(h).unwrap().as_ref().clone())
+            .collect();
+        batched.chain.insert_block_batch(blocks).unwrap();
+
+        assert_eq!(batched.chain.get_chain_height(), height);
+        assert_eq!(batched.chain.indexer.get_chain_height(), height);
+        for h in 0..=height {
+            assert_eq!(batched.chain.get_block(h), source.chain.get_block(h));
+            assert_eq!(
+                batched.chain.indexer.get_block_byte_pos(h),
+                source.chain.indexer.get_block_byte_pos(h)
+            );
+        }
+        for id in 0..=3 {
+            assert_eq!(
+                batched.chain.get_account(id, &[]),
+                source.chain.get_account(id, &[])
+            );
+        }
+        assert_eq!(
+            batched.chain.indexer.get_token_supply(),
+            source.chain.indexer.get_token_supply()
+        );
+        for tx in txs {
+            assert!(batched.chain.indexer.has_txid(&tx.calc_txid()));
+        }
+    }
+
+    #[test]
+    fn insert_block_batch_keeps_blocks_before_invalid_block() {
+        let source = fixture_chain(3);
+        let batched = fixture_chain(0);
+
+        let block = |h| source.chain.get_block(h).unwrap().as_ref().clone();
+        let res = batched
+            .chain
+            .insert_block_batch(vec![block(1), block(2), block(2), block(3)]);
+        assert_eq!(res, Err(BlockErr::InvalidBlockHeight));
+
+        assert_eq!(batched.chain.get_chain_height(), 2);
+        assert_eq!(batched.chain.indexer.get_chain_height(), 2);
+        assert_eq!(batched.chain.get_block(2), source.chain.get_block(2));
+        batched.chain.insert_block(block(3)).unwrap();
+    }
+
+    #[test]
+    fn insert_block_batch_uses_pending_network_fee() {
+        // Every block transfers from distinct accounts, so no block depends on the accounts
+        // changed by the blocks pending in the batch
+        let senders: Vec<_> = (1..=50)
+            .map(|id| fixture_account(id, "100.00000 TEST"))
+            .collect();
+        let receivers: Vec<_> = (101..=150)
+            .map(|id| fixture_account(id, "0.00000 TEST").0)
+            .collect();
+        let source = fixture_chain(0);
+        let batched = fixture_chain(0);
+        for fixture in &[&source, &batched] {
+            for acc in senders.iter().map(|(acc, _)| acc).chain(&receivers) {
+                fixture.insert_account(acc.clone());
+            }
+        }
+
+        let transfer = |from: &(Account, KeyPair), to: &Account, receipts: &[Receipt]| {
+            let info = source.chain.get_account_info(from.0.id, receipts).unwrap();
+            let fee = info.total_fee().unwrap().to_string();
+            let tx = signed_transfer(from, to.id, "1.00000 TEST", &fee, 0);
+            let log = source
+                .chain
+                .execute_tx(&tx.clone().precompute(), receipts, SKIP_NONE)
+                .unwrap();
+            Receipt { tx, log }
+        };
+        let last = senders.len() - 1;
+        let genesis_fee = source.chain.get_account_info(senders[last].0.id, &[]);
+
+        // Fill the network fee window up to the next adjustment with transfers paying the fee
+        let mut pairs = senders.iter().zip(&receivers);
+        for _ in 0..NETWORK_FEE_ADJUST_INTERVAL {
+            let mut receipts = vec![];
+            for (from, to) in pairs.by_ref().take(8) {
+                receipts.push(transfer(from, to, &receipts));
+            }
+            source.produce_block(receipts).unwrap();
+        }
+        assert!(source.chain.get_network_fee() > genesis_fee.as_ref().map(|info| info.net_fee));
+
+        // The fee of the first block after the adjustment is computed with the previous window,
+        // which is only accepted by the chain without the adjustment
+        let fee = genesis_fee.unwrap().total_fee().unwrap().to_string();
+        let tx = signed_transfer(&senders[last], receivers[last].id, "1.00000 TEST", &fee, 0);
+        let log = batched
+            .chain
+            .execute_tx(&tx.clone().precompute(), &[], SKIP_NONE)
+            .unwrap();
+        let underpriced = source.new_block(vec![Receipt { tx, log }]);
+        let res = source.chain.insert_block(underpriced.clone());
+        assert_eq!(res, Err(BlockErr::Tx(TxErr::InvalidFeeAmount)));
+
+        let height = source.chain.get_chain_height();
+        let mut blocks: Vec<_> = (1..=height)
+            .map(|h| source.chain.get_block(h).unwrap().as_ref().clone())
+            .collect();
+        blocks.push(underpriced);
+        let res = batched.chain.insert_block_batch(blocks);
+        assert_eq!(res, Err(BlockErr::Tx(TxErr::InvalidFeeAmount)));
+        assert_eq!(batched.chain.get_chain_height(), height);
+        assert_eq!(
+            batched.chain.get_network_fee(),
+            source.chain.get_network_fee()
+        );
+    }
+}
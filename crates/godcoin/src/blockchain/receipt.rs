@@ -1,15 +1,20 @@
-use super::{skip_flags, AccountInfo, Blockchain, Indexer, TxErr};
+use super::{skip_flags, AccountInfo, BlockFilter, Blockchain, Indexer, TxErr};
 use crate::{
     account::AccountId,
     asset::Asset,
-    constants::TX_MAX_EXPIRY_TIME,
+    constants::{GRAEL_FEE_MIN, TX_MAX_EXPIRY_TIME},
     serializer::*,
-    tx::{TxPrecompData, TxVariant},
+    tx::{TxId, TxPrecompData, TxVariant, TxVariantV0},
 };
 use std::{io::Cursor, mem, sync::Arc};
+use tracing::warn;
 
 const DEFAULT_RECEIPT_CAPACITY: usize = 1024;
 
+/// Minimum fee increase of a replacement transaction, in percent of the fee of the replaced
+/// transaction. The increase is never less than `GRAEL_FEE_MIN`.
+const REPLACEMENT_FEE_BUMP_PERCENT: i64 = 10;
+
 pub struct ReceiptPool {
     chain: Arc<Blockchain>,
     indexer: Arc<Indexer>,
@@ -31,23 +36,92 @@ impl ReceiptPool {
         self.chain.get_account_info(id, &self.receipts)
     }
 
+    /// Executes the transaction against the pending state and adds it to the pool.
+    ///
+    /// A transaction signed by an account replaces the pending transaction with the same account
+    /// and nonce when it raises the fee by at least `REPLACEMENT_FEE_BUMP_PERCENT` percent, and by
+    /// no less than `GRAEL_FEE_MIN`, otherwise it is rejected with
+    /// `TxErr::ReplacementUnderpriced`. The replacement must be signed to meet the permissions of
+    /// the account before the replaced transaction is touched, otherwise it is rejected with
+    /// `TxErr::ReplacementUnauthorized`. The replaced transaction is
+    /// removed as if by `remove` and the replacement is appended to the end of the pool. If the
+    /// replacement fails to execute the pool is left unchanged. Owner and mint transactions are
+    /// never replaced.
     pub fn push(
         &mut self,
         data: TxPrecompData,
         skip_flags: skip_flags::SkipFlags,
     ) -> Result<(), TxErr> {
         let current_time = crate::get_epoch_time();
+        let tolerance = self.chain.clock_skew_tolerance();
 
         let expiry = data.tx().expiry();
-        if expiry <= current_time || expiry - current_time > TX_MAX_EXPIRY_TIME {
+        if data.tx().is_expired(current_time.saturating_sub(tolerance))
+            || data
+                .tx()
+                .exceeds_max_expiry(current_time.saturating_add(tolerance))
+        {
             return Err(TxErr::TxExpired);
         } else if self.indexer.has_txid(data.txid()) {
             return Err(TxErr::TxDupe);
         }
 
-        let log = self.chain.execute_tx(&data, &self.receipts, skip_flags)?;
+        let replaced = replacement_key(data.tx()).and_then(|key| {
+            self.receipts
+                .iter()
+                .position(|receipt| replacement_key(&receipt.tx) == Some(key))
+                .map(|pos| (pos, key.0))
+        });
+        let evicted = match replaced {
+            Some((pos, account_id)) => {
+                match min_replacement_fee(tx_fee(&self.receipts[pos].tx)) {
+                    Some(min_fee) if tx_fee(data.tx()) >= min_fee => {}
+                    _ => return Err(TxErr::ReplacementUnderpriced),
+                }
+
+                // Check the replacement is authorized before the pool is modified
+                let account = self
+                    .chain
+                    .get_account(account_id, &self.receipts[..pos])
+                    .ok_or(TxErr::AccountNotFound)?;
+                if account
+                    .permissions
+                    .verify(data.txid().as_ref(), data.tx().sigs())
+                    .is_err()
+                {
+                    return Err(TxErr::ReplacementUnauthorized);
+                }
+
+                Some((pos, self.evict(pos)))
+            }
+            None => None,
+        };
+
+        let log = match self.chain.execute_tx(&data, &self.receipts, skip_flags) {
+            Ok(log) => log,
+            Err(e) => {
+                if let Some((pos, evicted)) = evicted {
+                    self.receipts.truncate(pos);
+                    self.receipts.extend(evicted);
+                }
+                return Err(e);
+            }
+        };
 
-        self.indexer.insert_txid(data.txid(), expiry);
+        if data.tx().is_expired(current_time) {
+            warn!(
+                "Accepted transaction {}s past its expiry, the local clock may be ahead",
+                current_time - expiry
+            );
+        } else if data.tx().exceeds_max_expiry(current_time) {
+            warn!(
+                "Accepted transaction {}s beyond the maximum expiry, the local clock may be behind",
+                expiry - current_time - TX_MAX_EXPIRY_TIME
+            );
+        }
+
+        self.indexer
+            .insert_txid(data.txid(), expiry.saturating_add(tolerance));
         self.receipts.push(Receipt {
             tx: data.take(),
             log,
@@ -55,14 +129,102 @@ impl ReceiptPool {
         Ok(())
     }
 
+    /// Returns the pending transactions in the order they were accepted.
+    pub fn txs(&self) -> Vec<TxVariant> {
+        self.receipts
+            .iter()
+            .map(|receipt| receipt.tx.clone())
+            .collect()
+    }
+
+    /// Removes the pending transaction with the specified txid, returning whether it was found.
+    /// Transactions accepted after it are executed again and dropped as well if they depended on
+    /// the removed transaction. The txids remain indexed so dropped transactions cannot be
+    /// broadcasted again until they expire.
+    pub fn remove(&mut self, txid: &TxId) -> bool {
+        let pos = match self
+            .receipts
+            .iter()
+            .position(|receipt| &receipt.tx.calc_txid() == txid)
+        {
+            Some(pos) => pos,
+            None => return false,
+        };
+
+        self.evict(pos);
+        true
+    }
+
+    /// Removes the pending transaction at the specified position and executes the transactions
+    /// after it again, dropping any that no longer execute. Returns the receipts that were in the
+    /// pool from the position onwards so the eviction can be undone.
+    fn evict(&mut self, pos: usize) -> Vec<Receipt> {
+        let pending = self.receipts.split_off(pos);
+        for receipt in pending.iter().skip(1) {
+            let data = receipt.tx.clone().precompute();
+            if let Ok(log) = self
+                .chain
+                .execute_tx(&data, &self.receipts, skip_flags::SKIP_NONE)
+            {
+                self.receipts.push(Receipt {
+                    tx: data.take(),
+                    log,
+                });
+            }
+        }
+        pending
+    }
+
+    /// Returns the pending receipts in the order they were accepted.
+    #[inline]
+    pub fn receipts(&self) -> &[Receipt] {
+        &self.receipts
+    }
+
+    /// Removes and returns the first `len` pending receipts, keeping the receipts accepted after
+    /// them. Used once the receipts are included in a block.
+    pub fn flush_front(&mut self, len: usize) -> Vec<Receipt> {
+        let remaining = self.receipts.split_off(len);
+        mem::replace(&mut self.receipts, remaining)
+    }
+
     pub fn flush(&mut self) -> Vec<Receipt> {
         let mut receipts = Vec::with_capacity(DEFAULT_RECEIPT_CAPACITY);
         mem::swap(&mut receipts, &mut self.receipts);
-        self.indexer.purge_expired_txids();
         receipts
     }
 }
 
+/// Returns the account that signed the transaction and its nonce. Pending transactions with the
+/// same key replace each other.
+fn replacement_key(tx: &TxVariant) -> Option<(AccountId, u32)> {
+    match tx {
+        TxVariant::V0(tx) => match tx {
+            TxVariantV0::OwnerTx(_) | TxVariantV0::MintTx(_) => None,
+            TxVariantV0::CreateAccountTx(tx) => Some((tx.creator, tx.nonce)),
+            TxVariantV0::UpdateAccountTx(tx) => Some((tx.account_id, tx.nonce)),
+            TxVariantV0::TransferTx(tx) => Some((tx.from, tx.nonce)),
+        },
+    }
+}
+
+/// Returns the lowest fee a transaction must pay to replace a pending transaction paying the
+/// provided fee, or `None` on overflow.
+fn min_replacement_fee(fee: Asset) -> Option<Asset> {
+    let bump = fee
+        .amount
+        .checked_mul(REPLACEMENT_FEE_BUMP_PERCENT)?
+        .checked_div(100)?
+        .max(GRAEL_FEE_MIN.amount);
+    fee.checked_add(Asset::new(bump))
+}
+
+fn tx_fee(tx: &TxVariant) -> Asset {
+    match tx {
+        TxVariant::V0(tx) => tx.fee,
+    }
+}
+
 /// A receipt represents a transaction that has been executed.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Receipt {
@@ -88,6 +250,47 @@ impl Receipt {
         }
         Some(Receipt { tx, log })
     }
+
+    /// Returns whether the transaction or its log entries involve the specified account.
+    pub fn touches_account(&self, id: AccountId) -> bool {
+        self.touches_any(|acc| acc == id)
+    }
+
+    /// Returns whether the transaction or its log entries involve any account in the filter.
+    pub fn matches_filter(&self, filter: &BlockFilter) -> bool {
+        self.touches_any(|acc| filter.contains(acc))
+    }
+
+    pub(crate) fn touches_any<F: Fn(AccountId) -> bool>(&self, matches: F) -> bool {
+        tx_touches_any(&self.tx, &self.log, matches)
+    }
+}
+
+/// Returns whether the transaction or the provided log of its execution involve any account in the
+/// filter.
+pub(crate) fn tx_touches_any<F: Fn(AccountId) -> bool>(
+    tx: &TxVariant,
+    log: &[LogEntry],
+    matches: F,
+) -> bool {
+    match tx {
+        TxVariant::V0(tx) => match tx {
+            TxVariantV0::OwnerTx(owner_tx) => matches(owner_tx.wallet),
+            TxVariantV0::MintTx(mint_tx) => matches(mint_tx.to),
+            TxVariantV0::CreateAccountTx(create_acc_tx) => {
+                matches(create_acc_tx.creator) || matches(create_acc_tx.account.id)
+            }
+            TxVariantV0::UpdateAccountTx(update_acc_tx) => matches(update_acc_tx.account_id),
+            TxVariantV0::TransferTx(transfer_tx) => {
+                matches(transfer_tx.from)
+                    || log.iter().any(|entry| match entry {
+                        LogEntry::Transfer(to_acc, _) => matches(*to_acc),
+                        LogEntry::Destroy(to_acc) => matches(*to_acc),
+                        LogEntry::Fee(_) => false,
+                    })
+            }
+        },
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -96,6 +299,9 @@ pub enum LogEntry {
     Transfer(AccountId, Asset), // To account, amount
     /// Destroys an account and sends any remaining funds to the specified account
     Destroy(AccountId),
+    /// Fee paid by the sender of the transaction. The fee is deducted by the transaction itself,
+    /// this entry only records it so the log describes every balance change
+    Fee(Asset),
 }
 
 impl LogEntry {
@@ -110,6 +316,10 @@ impl LogEntry {
                 buf.push(0x01);
                 buf.push_u64(*acc);
             }
+            Self::Fee(amt) => {
+                buf.push(0x02);
+                buf.push_asset(*amt);
+            }
         }
     }
 
@@ -125,6 +335,10 @@ impl LogEntry {
                 let acc = cur.take_u64().ok()?;
                 Some(Self::Destroy(acc))
             }
+            0x02 => {
+                let amt = cur.take_asset().ok()?;
+                Some(Self::Fee(amt))
+            }
             _ => None,
         }
     }
@@ -152,7 +366,11 @@ mod tests {
                 amount,
                 memo: vec![1, 2, 3, 4],
             })),
-            log: vec![LogEntry::Transfer(123456, amount)],
+            log: vec![
+                LogEntry::Fee("0.00001 TEST".parse().unwrap()),
+                LogEntry::Transfer(123456, amount),
+                LogEntry::Destroy(654321),
+            ],
         };
 
         let mut buf = Vec::with_capacity(4096);
@@ -160,4 +378,36 @@ mod tests {
         let deserialized_receipt = Receipt::deserialize(&mut Cursor::new(&buf)).unwrap();
         assert_eq!(receipt, deserialized_receipt);
     }
+
+    #[test]
+    fn receipt_touches_account() {
+        let amount = "1.00000 TEST".parse().unwrap();
+        let receipt = Receipt {
+            tx: TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+                base: Tx {
+                    nonce: 0,
+                    expiry: 1234567890,
+                    fee: Asset::default(),
+                    signature_pairs: Vec::new(),
+                },
+                from: 1,
+                call_fn: 0,
+                args: vec![],
+                amount,
+                memo: vec![],
+            })),
+            log: vec![LogEntry::Transfer(2, amount), LogEntry::Destroy(3)],
+        };
+
+        assert!(receipt.touches_account(1));
+        assert!(receipt.touches_account(2));
+        assert!(receipt.touches_account(3));
+        assert!(!receipt.touches_account(4));
+
+        let mut filter = BlockFilter::new();
+        filter.insert(4);
+        assert!(!receipt.matches_filter(&filter));
+        filter.insert(2);
+        assert!(receipt.matches_filter(&filter));
+    }
 }
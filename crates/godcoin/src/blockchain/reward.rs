@@ -0,0 +1,81 @@
+use crate::asset::Asset;
+
+/// Schedule of the reward minted to the owner wallet for every produced block, in addition to the
+/// fees of the transactions in the block.
+///
+/// The schedule is part of consensus: a block is only valid when its rewards equal the fees plus
+/// the scheduled reward at its height, so every node verifying the chain must be configured with
+/// the same schedule.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RewardSchedule {
+    /// Mints the same reward for every block.
+    Fixed(Asset),
+    /// Mints `initial` for blocks below `interval` and halves the reward every `interval` blocks
+    /// after that. An interval of 0 never halves the reward.
+    Halving { initial: Asset, interval: u64 },
+}
+
+impl RewardSchedule {
+    /// Returns the reward minted for the block at the provided height.
+    pub fn reward_at(&self, height: u64) -> Asset {
+        match *self {
+            RewardSchedule::Fixed(reward) => reward,
+            RewardSchedule::Halving { initial, interval } => {
+                let halvings = height.checked_div(interval).unwrap_or(0);
+                if halvings >= 64 {
+                    Asset::default()
+                } else {
+                    Asset::new(initial.amount >> halvings)
+                }
+            }
+        }
+    }
+}
+
+impl Default for RewardSchedule {
+    fn default() -> Self {
+        RewardSchedule::Fixed(Asset::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_reward() {
+        let schedule = RewardSchedule::Fixed(get_asset("1.00000 TEST"));
+        for &height in &[0, 1, 2, 1000, u64::max_value()] {
+            assert_eq!(schedule.reward_at(height), get_asset("1.00000 TEST"));
+        }
+        assert_eq!(RewardSchedule::default().reward_at(1), Asset::default());
+    }
+
+    #[test]
+    fn halving_reward() {
+        let schedule = RewardSchedule::Halving {
+            initial: get_asset("50.00000 TEST"),
+            interval: 100,
+        };
+        assert_eq!(schedule.reward_at(0), get_asset("50.00000 TEST"));
+        assert_eq!(schedule.reward_at(99), get_asset("50.00000 TEST"));
+        assert_eq!(schedule.reward_at(100), get_asset("25.00000 TEST"));
+        assert_eq!(schedule.reward_at(199), get_asset("25.00000 TEST"));
+        assert_eq!(schedule.reward_at(200), get_asset("12.50000 TEST"));
+        assert_eq!(schedule.reward_at(300), get_asset("6.25000 TEST"));
+        // The smallest unit is truncated once it can't be halved any further
+        assert_eq!(schedule.reward_at(2200), get_asset("0.00001 TEST"));
+        assert_eq!(schedule.reward_at(2300), get_asset("0.00000 TEST"));
+        assert_eq!(schedule.reward_at(u64::max_value()), Asset::default());
+
+        let schedule = RewardSchedule::Halving {
+            initial: get_asset("50.00000 TEST"),
+            interval: 0,
+        };
+        assert_eq!(schedule.reward_at(1000), get_asset("50.00000 TEST"));
+    }
+
+    fn get_asset(s: &str) -> Asset {
+        s.parse().unwrap()
+    }
+}
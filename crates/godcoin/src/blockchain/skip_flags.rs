@@ -2,3 +2,6 @@ pub type SkipFlags = u8;
 
 #[allow(clippy::identity_op)]
 pub const SKIP_NONE: u8 = 1 << 0;
+
+/// Skips verifying the cryptographic signature of the block minter.
+pub const SKIP_BLOCK_SIGNATURE: u8 = 1 << 1;
@@ -0,0 +1,21 @@
+use super::store::MAX_CACHE_SIZE;
+use crate::asset::Asset;
+
+/// Maximum window of a spend limit. Transfers are checked against every block within the window,
+/// which is bounded to the blocks kept in the block cache.
+pub const MAX_SPEND_LIMIT_WINDOW: u64 = MAX_CACHE_SIZE;
+
+/// Limits the total amount every account may transfer within a window of recent blocks.
+///
+/// The limit is part of consensus: transfers exceeding it are rejected when broadcasted and
+/// blocks containing them fail verification, so every node verifying the chain must be configured
+/// with the same limit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpendLimit {
+    /// Maximum amount an account may transfer within the window.
+    pub max_amount: Asset,
+    /// Number of the most recent blocks whose transfers count toward the limit, in addition to the
+    /// transactions that will be included in the next block. Cannot exceed
+    /// `MAX_SPEND_LIMIT_WINDOW`.
+    pub window: u64,
+}
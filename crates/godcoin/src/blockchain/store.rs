@@ -1,6 +1,6 @@
 use crc32c::*;
 use std::{
-    cell::RefCell,
+    cell::{RefCell, RefMut},
     collections::HashMap,
     convert::TryInto,
     fs::{File, OpenOptions},
@@ -12,7 +12,10 @@ use tracing::{debug, error, warn};
 
 use crate::blockchain::{block::*, index::*};
 
-const MAX_CACHE_SIZE: u64 = 100;
+pub(crate) const MAX_CACHE_SIZE: u64 = 100;
+/// Upper bound of the serialized size of a block header. Only this many bytes of a block are read
+/// from the block log when decoding its header.
+const MAX_HEADER_SIZE: usize = 256;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ReindexOpts {
@@ -56,6 +59,7 @@ impl BlockStore {
         };
 
         store.init_state();
+        store.trim_unindexed_blocks();
         store
     }
 
@@ -79,6 +83,23 @@ impl BlockStore {
         }
     }
 
+    /// Returns the header of the block at the specified height. Blocks that aren't cached only have
+    /// their header read from the block log, the receipts are never decoded.
+    pub fn get_header(&self, height: u64) -> Option<BlockHeader> {
+        if height > self.height {
+            return None;
+        } else if height == 0 {
+            if let Some(ref block) = self.genesis_block {
+                return Some(block.header());
+            }
+        }
+        if let Some(block) = self.blocks.get(&height) {
+            Some(block.header())
+        } else {
+            self.read_header_from_disk(height)
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         let meta = self.file.borrow().metadata().unwrap();
         meta.len() == 0
@@ -122,62 +143,77 @@ impl BlockStore {
     {
         let mut batch = WriteBatch::new(Arc::clone(&self.indexer));
         let mut last_known_good_height = 0;
-        let mut pos = 0;
-        loop {
-            match self.raw_read_from_disk(pos) {
-                Ok(block) => {
-                    let height = block.height();
-                    let new_pos = {
-                        let mut f = self.file.borrow_mut();
-                        f.seek(SeekFrom::Current(0)).unwrap()
-                    };
-                    if !(last_known_good_height == 0 || height == last_known_good_height + 1) {
-                        error!("Invalid height ({}) detected at byte pos {}", height, pos);
-                        if opts.auto_trim {
-                            warn!("Truncating block log");
-                            let f = self.file.borrow();
-                            f.set_len(pos).unwrap();
-                            self.byte_pos_tail = pos;
-                        } else {
-                            panic!("corruption detected, auto trim is disabled");
+        let mut trim_pos = None;
+        {
+            let mut iter = BlockRangeIter::new(self.file.borrow_mut(), 0, u64::max_value());
+            loop {
+                let pos = iter.pos();
+                match iter.next() {
+                    Some(Ok(block)) => {
+                        let height = block.height();
+                        if !(last_known_good_height == 0 || height == last_known_good_height + 1) {
+                            error!("Invalid height ({}) detected at byte pos {}", height, pos);
+                            if opts.auto_trim {
+                                trim_pos = Some(pos);
+                            } else {
+                                panic!("corruption detected, auto trim is disabled");
+                            }
+                            break;
                         }
-                        break;
-                    }
 
-                    batch.set_block_byte_pos(height, pos);
-                    batch.set_chain_height(height);
-                    index_fn(&mut batch, &block);
-                    debug!("Reindexed block {} at pos {}", height, pos);
+                        batch.set_block_byte_pos(height, pos);
+                        batch.set_chain_height(height);
+                        index_fn(&mut batch, &block);
+                        debug!("Reindexed block {} at pos {}", height, pos);
 
-                    pos = new_pos;
-                    last_known_good_height = height;
-                }
-                Err(e) => match e {
-                    ReadError::Eof => break,
-                    ReadError::CorruptBlock => {
+                        last_known_good_height = height;
+                    }
+                    Some(Err(ReadError::CorruptBlock)) => {
                         error!(
                             "(last known good height: {}, block end byte pos: {})",
                             last_known_good_height, pos
                         );
                         if opts.auto_trim {
-                            warn!("Truncating block log");
-                            let f = self.file.borrow();
-                            f.set_len(pos).unwrap();
-                            self.byte_pos_tail = pos;
+                            trim_pos = Some(pos);
                             break;
                         } else {
                             panic!("corrupt block detected, auto trim is disabled");
                         }
                     }
-                },
+                    Some(Err(ReadError::Eof)) | None => break,
+                }
             }
         }
 
+        if let Some(pos) = trim_pos {
+            warn!("Truncating block log");
+            let f = self.file.borrow();
+            f.set_len(pos).unwrap();
+            self.byte_pos_tail = pos;
+        }
+
         batch.commit();
         self.indexer.set_index_status(IndexStatus::Complete);
         self.init_state();
     }
 
+    /// Returns an iterator over the blocks from the `start` height up to and including the `end`
+    /// height, which is capped to the chain height. Blocks are decoded reading the block log
+    /// sequentially rather than seeking to each height, avoiding the block cache. The block log is
+    /// borrowed until the iterator is dropped and must not be read from in the meantime.
+    pub fn iter_range(&self, start: u64, end: u64) -> BlockRangeIter<'_> {
+        let end = end.min(self.height);
+        let pos = if start <= end {
+            self.indexer.get_block_byte_pos(start)
+        } else {
+            None
+        };
+        match pos {
+            Some(pos) => BlockRangeIter::new(self.file.borrow_mut(), pos, end - start + 1),
+            None => BlockRangeIter::new(self.file.borrow_mut(), 0, 0),
+        }
+    }
+
     pub fn read_from_disk(&self, height: u64) -> Option<Block> {
         if height > self.height {
             return None;
@@ -187,32 +223,32 @@ impl BlockStore {
         self.raw_read_from_disk(pos).ok()
     }
 
-    pub fn raw_read_from_disk(&self, pos: u64) -> Result<Block, ReadError> {
+    /// Reads the header of the block at the specified height from disk. The block checksum is not
+    /// verified since the rest of the block is never read.
+    pub fn read_header_from_disk(&self, height: u64) -> Option<BlockHeader> {
+        if height > self.height {
+            return None;
+        }
+
+        let pos = self.indexer.get_block_byte_pos(height)?;
         let mut f = self.file.borrow_mut();
         f.seek(SeekFrom::Start(pos)).unwrap();
 
-        let (block_len, crc) = {
+        let block_len = {
             let mut meta = [0u8; 8];
-            f.read_exact(&mut meta).map_err(|_| ReadError::Eof)?;
-            let (len_buf, crc_buf) = meta.split_at(4);
-            let len = u32::from_be_bytes(len_buf.try_into().unwrap()) as usize;
-            let crc = u32::from_be_bytes(crc_buf.try_into().unwrap());
-            (len, crc)
+            f.read_exact(&mut meta).ok()?;
+            u32::from_be_bytes(meta[0..4].try_into().unwrap()) as usize
         };
 
-        let block_vec = {
-            let mut buf = Vec::with_capacity(block_len);
-            unsafe {
-                buf.set_len(block_len);
-            }
-            f.read_exact(&mut buf)
-                .map_err(|_| ReadError::CorruptBlock)?;
-            assert_eq!(crc, crc32c(&buf));
-            buf
-        };
+        let mut buf = vec![0u8; block_len.min(MAX_HEADER_SIZE)];
+        f.read_exact(&mut buf).ok()?;
+        BlockHeader::deserialize(&mut Cursor::<&[u8]>::new(&buf))
+    }
 
-        let mut cursor = Cursor::<&[u8]>::new(&block_vec);
-        Block::deserialize(&mut cursor).ok_or(ReadError::CorruptBlock)
+    pub fn raw_read_from_disk(&self, pos: u64) -> Result<Block, ReadError> {
+        let mut f = self.file.borrow_mut();
+        f.seek(SeekFrom::Start(pos)).unwrap();
+        read_block(&mut f).map(|(block, _)| block)
     }
 
     fn write_to_disk(&mut self, block: &Block) {
@@ -249,21 +285,67 @@ impl BlockStore {
         self.byte_pos_tail += u64::from(len) + 8;
     }
 
+    /// Replaces the block cache with the most recent `count` blocks read from disk, returning the
+    /// number of cached blocks. The count is capped to the maximum cache size of 100 blocks.
+    pub fn warmup(&mut self, count: u64) -> usize {
+        self.blocks.clear();
+        if self.is_empty() || self.indexer.index_status() != IndexStatus::Complete {
+            return 0;
+        }
+
+        let max = self.height;
+        let min = (max + 1).saturating_sub(count.min(MAX_CACHE_SIZE));
+        for height in min..=max {
+            let block = self
+                .read_from_disk(height)
+                .unwrap_or_else(|| panic!("Failed to read block {} from disk", height));
+            self.blocks.insert(height, Arc::new(block));
+        }
+        self.blocks.len()
+    }
+
+    /// Returns the heights of the blocks in the cache in ascending order.
+    pub fn cached_heights(&self) -> Vec<u64> {
+        let mut heights: Vec<u64> = self.blocks.keys().copied().collect();
+        heights.sort_unstable();
+        heights
+    }
+
+    /// Truncates the blocks at the end of the block log that are past the indexed chain height.
+    /// Blocks are written to the block log before their index updates are committed, so a node
+    /// stopping while inserting blocks leaves them in the block log without being indexed. Keeping
+    /// them would place the next inserted blocks after them.
+    fn trim_unindexed_blocks(&mut self) {
+        if self.indexer.index_status() != IndexStatus::Complete {
+            return;
+        }
+        let pos = match self.indexer.get_block_byte_pos(self.height) {
+            Some(pos) => pos,
+            None => return,
+        };
+        let end = {
+            let mut f = self.file.borrow_mut();
+            f.seek(SeekFrom::Start(pos)).unwrap();
+            match read_block(&mut f) {
+                Ok((_, len)) => pos + len,
+                Err(_) => return,
+            }
+        };
+        if end < self.byte_pos_tail {
+            warn!(
+                height = self.height,
+                "Truncating {} bytes of unindexed blocks from the block log",
+                self.byte_pos_tail - end
+            );
+            self.file.borrow().set_len(end).unwrap();
+            self.byte_pos_tail = end;
+        }
+    }
+
     fn init_state(&mut self) {
         self.height = self.indexer.get_chain_height();
         self.genesis_block = self.get(0);
-        if !self.is_empty() && self.indexer.index_status() == IndexStatus::Complete {
-            // Init block cache
-            self.blocks.clear();
-            let max = self.height;
-            let min = max.saturating_sub(MAX_CACHE_SIZE);
-            for height in min..=max {
-                let block = self
-                    .read_from_disk(height)
-                    .unwrap_or_else(|| panic!("Failed to read block {} from disk", height));
-                self.blocks.insert(height, Arc::new(block));
-            }
-        }
+        self.blocks.clear();
     }
 }
 
@@ -272,3 +354,244 @@ pub enum ReadError {
     Eof,
     CorruptBlock,
 }
+
+/// Iterator over blocks read sequentially from the block log, see `BlockStore::iter_range`.
+///
+/// Iteration ends after the requested number of blocks or at the end of the block log. A corrupt
+/// block is yielded as an error and ends the iteration.
+pub struct BlockRangeIter<'a> {
+    file: RefMut<'a, File>,
+    pos: u64,
+    remaining: u64,
+}
+
+impl<'a> BlockRangeIter<'a> {
+    fn new(mut file: RefMut<'a, File>, pos: u64, remaining: u64) -> Self {
+        if remaining > 0 {
+            file.seek(SeekFrom::Start(pos)).unwrap();
+        }
+        Self {
+            file,
+            pos,
+            remaining,
+        }
+    }
+
+    /// Returns the byte position in the block log of the next block.
+    #[inline]
+    pub fn pos(&self) -> u64 {
+        self.pos
+    }
+}
+
+impl<'a> Iterator for BlockRangeIter<'a> {
+    type Item = Result<Block, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        match read_block(&mut self.file) {
+            Ok((block, len)) => {
+                self.pos += len;
+                self.remaining -= 1;
+                Some(Ok(block))
+            }
+            Err(ReadError::Eof) => {
+                self.remaining = 0;
+                None
+            }
+            Err(e) => {
+                self.remaining = 0;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Reads the block at the current position of the file, returning the block and the number of
+/// bytes read.
+fn read_block(f: &mut File) -> Result<(Block, u64), ReadError> {
+    let (block_len, crc) = {
+        let mut meta = [0u8; 8];
+        f.read_exact(&mut meta).map_err(|_| ReadError::Eof)?;
+        let (len_buf, crc_buf) = meta.split_at(4);
+        let len = u32::from_be_bytes(len_buf.try_into().unwrap()) as usize;
+        let crc = u32::from_be_bytes(crc_buf.try_into().unwrap());
+        (len, crc)
+    };
+
+    let block_vec = {
+        let mut buf = Vec::with_capacity(block_len);
+        unsafe {
+            buf.set_len(block_len);
+        }
+        f.read_exact(&mut buf)
+            .map_err(|_| ReadError::CorruptBlock)?;
+        assert_eq!(crc, crc32c(&buf));
+        buf
+    };
+
+    let mut cursor = Cursor::<&[u8]>::new(&block_vec);
+    let block = Block::deserialize(&mut cursor).ok_or(ReadError::CorruptBlock)?;
+    Ok((block, block_len as u64 + 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::Asset,
+        blockchain::Receipt,
+        crypto::{double_sha256, Digest, KeyPair},
+        test_util::TmpDir,
+        tx::*,
+    };
+
+    #[test]
+    fn iter_range_matches_get() {
+        let tmp_dir = TmpDir::new();
+        let indexer = Arc::new(Indexer::new(&tmp_dir.join("index")));
+        let mut store = BlockStore::new(&tmp_dir.join("blklog"), Arc::clone(&indexer));
+
+        let key = KeyPair::gen();
+        let mut head = {
+            let mut block = Block::V0(BlockV0 {
+                header: BlockHeaderV0 {
+                    previous_hash: Digest::from_slice(&[0; 32]).unwrap(),
+                    height: 0,
+                    timestamp: 0,
+                    receipt_root: double_sha256(&[0; 0]),
+                },
+                signer: None,
+                rewards: Asset::default(),
+                receipts: vec![],
+            });
+            block.sign(&key);
+            let mut batch = WriteBatch::new(Arc::clone(&indexer));
+            store.insert_genesis(&mut batch, block.clone());
+            batch.commit();
+            block
+        };
+        for nonce in 0..5 {
+            // Blocks of varying sizes
+            let receipts = (0..nonce)
+                .map(|nonce| Receipt {
+                    tx: TxVariant::V0(TxVariantV0::MintTx(MintTx {
+                        base: Tx {
+                            nonce,
+                            expiry: 1234567890,
+                            fee: Asset::default(),
+                            signature_pairs: Vec::new(),
+                        },
+                        to: 0,
+                        amount: Asset::new(100),
+                        attachment: vec![],
+                        attachment_name: "".to_owned(),
+                    })),
+                    log: vec![],
+                })
+                .collect();
+            let mut block = match &head {
+                Block::V0(block) => block.new_child(receipts),
+            };
+            block.sign(&key);
+            let mut batch = WriteBatch::new(Arc::clone(&indexer));
+            store.insert(&mut batch, block.clone());
+            batch.commit();
+            head = block;
+        }
+        assert_eq!(store.get_chain_height(), 5);
+
+        let collect = |start: u64, end: u64| -> Vec<Block> {
+            store.iter_range(start, end).map(Result::unwrap).collect()
+        };
+        let get = |start: u64, end: u64| -> Vec<Block> {
+            (start..=end)
+                .map(|height| store.get(height).unwrap().as_ref().clone())
+                .collect()
+        };
+        assert_eq!(collect(0, 5), get(0, 5));
+        assert_eq!(collect(2, 4), get(2, 4));
+        assert_eq!(collect(5, 5), get(5, 5));
+        // The end is capped to the chain height
+        assert_eq!(collect(3, 100), get(3, 5));
+        assert!(collect(6, 10).is_empty());
+        assert!(collect(4, 2).is_empty());
+
+        // The block log is available again once the iterator is dropped
+        assert_eq!(store.read_from_disk(1), Some(get(1, 1).remove(0)));
+
+        // Headers read from disk without the receipts match the full blocks
+        for height in 0..=5 {
+            let header = store.get(height).unwrap().header();
+            assert_eq!(store.read_header_from_disk(height), Some(header.clone()));
+            assert_eq!(store.get_header(height), Some(header));
+        }
+        assert!(store.get_header(6).is_none());
+    }
+
+    #[test]
+    fn trim_unindexed_blocks_on_open() {
+        let tmp_dir = TmpDir::new();
+        let indexer = Arc::new(Indexer::new(&tmp_dir.join("index")));
+        let mut store = BlockStore::new(&tmp_dir.join("blklog"), Arc::clone(&indexer));
+
+        let key = KeyPair::gen();
+        let mut head = Block::V0(BlockV0 {
+            header: BlockHeaderV0 {
+                previous_hash: Digest::from_slice(&[0; 32]).unwrap(),
+                height: 0,
+                timestamp: 0,
+                receipt_root: double_sha256(&[0; 0]),
+            },
+            signer: None,
+            rewards: Asset::default(),
+            receipts: vec![],
+        });
+        head.sign(&key);
+        let mut batch = WriteBatch::new(Arc::clone(&indexer));
+        store.insert_genesis(&mut batch, head.clone());
+        batch.commit();
+        indexer.set_index_status(IndexStatus::Complete);
+
+        let new_child = |head: &Block| {
+            let mut block = match head {
+                Block::V0(block) => block.new_child(vec![]),
+            };
+            block.sign(&key);
+            block
+        };
+        let block = new_child(&head);
+        let mut batch = WriteBatch::new(Arc::clone(&indexer));
+        store.insert(&mut batch, block.clone());
+        batch.commit();
+        let indexed_tail = store.byte_pos_tail;
+        head = block;
+
+        // The index updates of these blocks are never committed
+        let mut batch = WriteBatch::new(Arc::clone(&indexer));
+        for _ in 0..2 {
+            let block = new_child(&head);
+            store.insert(&mut batch, block.clone());
+            head = block;
+        }
+        assert!(store.byte_pos_tail > indexed_tail);
+        drop(batch);
+        drop(store);
+
+        let mut store = BlockStore::new(&tmp_dir.join("blklog"), Arc::clone(&indexer));
+        assert_eq!(store.get_chain_height(), 1);
+        assert_eq!(store.byte_pos_tail, indexed_tail);
+        assert_eq!(store.file.borrow().metadata().unwrap().len(), indexed_tail);
+
+        // Blocks inserted after reopening are placed right after the indexed blocks
+        let head = store.get(1).unwrap();
+        let block = new_child(&head);
+        let mut batch = WriteBatch::new(Arc::clone(&indexer));
+        store.insert(&mut batch, block.clone());
+        batch.commit();
+        assert_eq!(indexer.get_block_byte_pos(2), Some(indexed_tail));
+        assert_eq!(store.read_from_disk(2), Some(block));
+    }
+}
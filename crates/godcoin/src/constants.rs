@@ -9,13 +9,19 @@ pub const GRAEL_ACC_CREATE_FEE_MULT: Asset = Asset::new(200_000);
 pub const GRAEL_ACC_CREATE_MIN_BAL_MULT: Asset = Asset::new(200_000);
 
 pub const NETWORK_FEE_AVG_WINDOW: u64 = 10;
+pub const NETWORK_FEE_ADJUST_INTERVAL: u64 = 5;
 pub const FEE_RESET_WINDOW: usize = 4;
 
 pub const TX_MAX_EXPIRY_TIME: u64 = 60 * 60 * 24 * 30;
+pub const MINTER_AUTH_MAX_EXPIRY_TIME: u64 = 60;
 pub const BLOCK_PROD_TIME: u64 = 3;
 
 pub const MAX_MEMO_BYTE_SIZE: usize = 1024;
 pub const MAX_SCRIPT_BYTE_SIZE: usize = 2048;
+/// Upper bound of the serialized size of an account created or updated by a transaction, see
+/// `Account::serialized_size`. An account with a script of the maximum size has room for up to 7
+/// permission keys.
+pub const MAX_ACCOUNT_BYTE_SIZE: usize = 2304;
 pub const MAX_TX_SIGNATURES: usize = 8;
 
 #[cfg(not(any(test, feature = "testnet")))]
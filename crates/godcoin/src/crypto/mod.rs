@@ -1,14 +1,16 @@
 use sodiumoxide::crypto::{hash::sha256, sign};
 use std::{
-    fmt::{self, Debug, Formatter},
+    fmt::{self, Debug, Display, Formatter},
     ops::Deref,
 };
 
 pub mod key;
+pub mod signer;
 pub mod sigpair;
 pub mod wif;
 
 pub use self::key::*;
+pub use self::signer::*;
 pub use self::sigpair::*;
 pub use self::wif::*;
 
@@ -55,6 +57,29 @@ macro_rules! impl_wrapper {
 impl_wrapper!(Digest, sha256::Digest);
 impl_wrapper!(Signature, sign::Signature);
 
+impl Digest {
+    #[inline]
+    pub fn to_hex(&self) -> String {
+        faster_hex::hex_string(self.as_ref()).unwrap()
+    }
+
+    /// Decodes a digest from a hex string, which must be exactly `DIGEST_BYTES * 2` characters.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != DIGEST_BYTES * 2 {
+            return None;
+        }
+        let mut bytes = [0; DIGEST_BYTES];
+        faster_hex::hex_decode(hex.as_bytes(), &mut bytes).ok()?;
+        Self::from_slice(&bytes)
+    }
+}
+
+impl Display for Digest {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
 #[inline]
 pub fn double_sha256(buf: &[u8]) -> Digest {
     Digest(sha256::hash(sha256::hash(buf).as_ref()))
@@ -97,4 +122,22 @@ mod test {
         };
         assert_eq!(digest_a, digest_b);
     }
+
+    #[test]
+    fn digest_hex_round_trip() {
+        let digest = double_sha256(&[1, 2, 3, 4, 5]);
+        let hex = digest.to_hex();
+        assert_eq!(hex.len(), DIGEST_BYTES * 2);
+        assert_eq!(digest.to_string(), hex);
+        assert_eq!(Digest::from_hex(&hex), Some(digest));
+    }
+
+    #[test]
+    fn digest_invalid_hex() {
+        let hex = double_sha256(&[1, 2, 3, 4, 5]).to_hex();
+        assert_eq!(Digest::from_hex(&hex[..hex.len() - 2]), None);
+        assert_eq!(Digest::from_hex(&format!("{}00", hex)), None);
+        assert_eq!(Digest::from_hex(""), None);
+        assert_eq!(Digest::from_hex(&"zz".repeat(DIGEST_BYTES)), None);
+    }
 }
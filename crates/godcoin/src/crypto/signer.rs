@@ -0,0 +1,22 @@
+use super::{KeyPair, PublicKey, SigPair};
+
+/// Produces signatures on behalf of a public key. Implementations may hold the private key in
+/// process (e.g. `KeyPair`) or delegate signing to an external device or service.
+pub trait Signer {
+    /// The public key that signatures produced by this signer can be verified against.
+    fn public_key(&self) -> &PublicKey;
+
+    fn sign(&self, msg: &[u8]) -> SigPair;
+}
+
+impl Signer for KeyPair {
+    #[inline]
+    fn public_key(&self) -> &PublicKey {
+        &self.0
+    }
+
+    #[inline]
+    fn sign(&self, msg: &[u8]) -> SigPair {
+        KeyPair::sign(self, msg)
+    }
+}
@@ -1,8 +1,11 @@
 use super::{double_sha256, key::*};
-use crate::{account::AccountId, serializer::BufWrite};
+use crate::{
+    account::{AccountId, AccountIdBytes},
+    constants::CHAIN_ID,
+    serializer::BufWrite,
+};
 use sodiumoxide::crypto::sign;
 use std::{
-    convert::TryInto,
     error::Error,
     fmt::{self, Display},
 };
@@ -12,6 +15,11 @@ const PRIV_BUF_PREFIX: u8 = 0x01;
 const PUB_BUF_PREFIX: u8 = 0x02;
 const ACCOUNT_ID_BUF_PREFIX: u8 = 0x03;
 
+/// Chain ID of addresses encoded without a network. Addresses of other networks include the chain
+/// ID after the buffer prefix so they cannot be decoded on the wrong network. Addresses without a
+/// network predate the chain ID tag and are accepted on every network.
+const DEFAULT_NETWORK: [u8; 2] = [0x00, 0x00];
+
 pub trait Wif<T, U> {
     fn from_wif(s: &str) -> Result<T, WifError>;
     fn to_wif(&self) -> U;
@@ -23,6 +31,7 @@ pub enum WifErrorKind {
     InvalidPrefix,
     InvalidChecksum,
     InvalidBs58Encoding,
+    WrongNetwork,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -45,6 +54,7 @@ impl Display for WifError {
             WifErrorKind::InvalidPrefix => "invalid prefix",
             WifErrorKind::InvalidChecksum => "invalid checksum",
             WifErrorKind::InvalidBs58Encoding => "invalid bs58 encoding",
+            WifErrorKind::WrongNetwork => "address belongs to a different network",
         };
         write!(f, "{}", desc)
     }
@@ -52,46 +62,68 @@ impl Display for WifError {
 
 impl Wif<AccountId, Box<str>> for AccountId {
     fn from_wif(s: &str) -> Result<AccountId, WifError> {
-        if s.len() < 3 || &s[0..3] != PUB_ADDRESS_PREFIX {
-            return Err(WifError::new(WifErrorKind::InvalidPrefix));
+        account_id_from_wif(s, CHAIN_ID)
+    }
+
+    fn to_wif(&self) -> Box<str> {
+        account_id_to_wif(*self, CHAIN_ID)
+    }
+}
+
+/// Decodes an account ID address. Addresses tagged with a chain ID must match the provided chain ID,
+/// while legacy untagged addresses are accepted on every network. Only account ID addresses carry
+/// a network, public and private key WIFs are network independent. Script hash addresses have been
+/// replaced by account IDs and are not supported.
+fn account_id_from_wif(s: &str, chain_id: [u8; 2]) -> Result<AccountId, WifError> {
+    if s.len() < 3 || &s[0..3] != PUB_ADDRESS_PREFIX {
+        return Err(WifError::new(WifErrorKind::InvalidPrefix));
+    }
+    let raw = match bs58::decode(&s[3..]).into_vec() {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return Err(WifError::new(WifErrorKind::InvalidBs58Encoding));
         }
-        let raw = match bs58::decode(&s[3..]).into_vec() {
-            Ok(bytes) => bytes,
-            Err(_) => {
-                return Err(WifError::new(WifErrorKind::InvalidBs58Encoding));
-            }
-        };
-        if raw.len() != 13 {
-            return Err(WifError::new(WifErrorKind::InvalidLen));
-        } else if raw[0] != ACCOUNT_ID_BUF_PREFIX {
-            return Err(WifError::new(WifErrorKind::InvalidPrefix));
+    };
+    if raw.len() != 13 && raw.len() != 15 {
+        return Err(WifError::new(WifErrorKind::InvalidLen));
+    } else if raw[0] != ACCOUNT_ID_BUF_PREFIX {
+        return Err(WifError::new(WifErrorKind::InvalidPrefix));
+    }
+
+    let prefixed_id = &raw[0..raw.len() - 4];
+    {
+        let checksum_a = &raw[raw.len() - 4..raw.len()];
+        let checksum_b = &double_sha256(prefixed_id)[0..4];
+        if checksum_a != checksum_b {
+            return Err(WifError::new(WifErrorKind::InvalidChecksum));
         }
+    }
 
-        let prefixed_id = &raw[0..raw.len() - 4];
-        {
-            let checksum_a = &raw[raw.len() - 4..raw.len()];
-            let checksum_b = &double_sha256(prefixed_id)[0..4];
-            if checksum_a != checksum_b {
-                return Err(WifError::new(WifErrorKind::InvalidChecksum));
-            }
+    let id = if prefixed_id.len() == 9 {
+        &prefixed_id[1..]
+    } else {
+        if prefixed_id[1..3] != chain_id {
+            return Err(WifError::new(WifErrorKind::WrongNetwork));
         }
+        &prefixed_id[3..]
+    };
+    Ok(AccountId::from_slice(id).unwrap())
+}
 
-        let id = &prefixed_id[1..prefixed_id.len()];
-        Ok(u64::from_be_bytes(id.try_into().unwrap()))
+fn account_id_to_wif(id: AccountId, chain_id: [u8; 2]) -> Box<str> {
+    let mut buf: Vec<u8> = Vec::<u8>::with_capacity(15);
+    buf.push(ACCOUNT_ID_BUF_PREFIX);
+    if chain_id != DEFAULT_NETWORK {
+        buf.extend_from_slice(&chain_id);
     }
+    buf.push_u64(id);
 
-    fn to_wif(&self) -> Box<str> {
-        let mut buf: Vec<u8> = Vec::<u8>::with_capacity(13);
-        buf.push(ACCOUNT_ID_BUF_PREFIX);
-        buf.push_u64(*self);
+    let checksum = &double_sha256(&buf)[0..4];
+    buf.extend_from_slice(checksum);
 
-        let checksum = &double_sha256(&buf)[0..4];
-        buf.extend_from_slice(checksum);
-
-        let mut s = bs58::encode(buf).into_string();
-        s.insert_str(0, PUB_ADDRESS_PREFIX);
-        s.into_boxed_str()
-    }
+    let mut s = bs58::encode(buf).into_string();
+    s.insert_str(0, PUB_ADDRESS_PREFIX);
+    s.into_boxed_str()
 }
 
 impl Wif<PublicKey, Box<str>> for PublicKey {
@@ -235,6 +267,47 @@ mod tests {
         assert_eq!(AccountId::from_wif("GODFVarNr3nEqUnvquCn"), Ok(0));
     }
 
+    #[test]
+    fn create_and_recover_account_id() {
+        for &id in &[0, 1, u64::max_value()] {
+            let wif = id.to_wif();
+            assert_eq!(AccountId::from_wif(&wif), Ok(id));
+        }
+    }
+
+    #[test]
+    fn reject_account_id_from_other_network() {
+        let mainnet = account_id_to_wif(1234, DEFAULT_NETWORK);
+        let testnet = account_id_to_wif(1234, [0x00, 0x01]);
+        assert_ne!(mainnet, testnet);
+
+        assert_eq!(
+            &*account_id_to_wif(0, DEFAULT_NETWORK),
+            "GODFVarNr3nEqUnvquCn"
+        );
+        assert_eq!(account_id_from_wif(&mainnet, DEFAULT_NETWORK), Ok(1234));
+        assert_eq!(account_id_from_wif(&testnet, [0x00, 0x01]), Ok(1234));
+        assert_eq!(
+            account_id_from_wif(&testnet, DEFAULT_NETWORK)
+                .unwrap_err()
+                .kind,
+            WifErrorKind::WrongNetwork
+        );
+        assert_eq!(
+            account_id_from_wif(&testnet, [0x00, 0x02])
+                .unwrap_err()
+                .kind,
+            WifErrorKind::WrongNetwork
+        );
+
+        // Legacy addresses without a network are accepted on every network
+        assert_eq!(account_id_from_wif(&mainnet, [0x00, 0x01]), Ok(1234));
+
+        // Tests are built for the testnet
+        assert_eq!(AccountId::from_wif(&mainnet), Ok(1234));
+        assert_eq!(AccountId::from_wif(&testnet), Ok(1234));
+    }
+
     #[test]
     fn invalid_prefix_account_id() {
         let mut bytes = bs58::decode("FVarNr3nEqUnvquCn").into_vec().unwrap();
@@ -11,6 +11,9 @@ pub mod net;
 pub mod script;
 pub mod serializer;
 
+#[cfg(test)]
+pub(crate) mod test_util;
+
 pub fn init() -> Result<(), ()> {
     sodiumoxide::init()
 }
@@ -24,16 +27,17 @@ pub fn get_epoch_time() -> u64 {
 
 pub mod prelude {
     pub use super::account::{
-        Account, AccountId, Permissions, PermsSigVerifyErr, IMMUTABLE_ACCOUNT_THRESHOLD,
-        MAX_PERM_KEYS,
+        Account, AccountId, AccountIdBytes, DeriveAccountId, Permissions, PermsSigVerifyErr,
+        IMMUTABLE_ACCOUNT_THRESHOLD, MAX_PERM_KEYS,
     };
     pub use super::asset::{self, Asset, AssetError, AssetErrorKind};
     pub use super::blockchain::{
         self, index::IndexStatus, AccountInfo, Block, BlockFilter, BlockHeader, BlockHeaderV0,
-        BlockV0, Blockchain, FilteredBlock, LogEntry, Properties, Receipt, ReceiptPool,
+        BlockV0, Blockchain, BloomFilter, ChainStats, FilteredBlock, LogEntry, Properties, Receipt,
+        ReceiptPool,
     };
     pub use super::crypto::{
-        DoubleSha256, KeyPair, PrivateKey, PublicKey, SigPair, Wif, WifError, WifErrorKind,
+        DoubleSha256, KeyPair, PrivateKey, PublicKey, SigPair, Signer, Wif, WifError, WifErrorKind,
     };
     pub use super::net::{self, rpc, Body, Msg};
     pub use super::script::{self, OpFrame, Script, ScriptEngine};
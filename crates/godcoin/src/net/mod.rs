@@ -87,6 +87,10 @@ pub enum ErrorKind {
     InvalidRequest,
     InvalidHeight,
     TxValidation(TxErr),
+    /// The request requires minter authentication that is missing, expired, or invalid.
+    Unauthorized,
+    /// The client has too many requests in progress and must wait for them to complete.
+    Overloaded,
 }
 
 impl ErrorKind {
@@ -101,6 +105,8 @@ impl ErrorKind {
                 buf.push(0x04);
                 err.serialize(buf);
             }
+            Self::Unauthorized => buf.push(0x05),
+            Self::Overloaded => buf.push(0x06),
         }
     }
 
@@ -112,6 +118,8 @@ impl ErrorKind {
             0x02 => Self::InvalidRequest,
             0x03 => Self::InvalidHeight,
             0x04 => Self::TxValidation(TxErr::deserialize(cursor)?),
+            0x05 => Self::Unauthorized,
+            0x06 => Self::Overloaded,
             _ => {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidData,
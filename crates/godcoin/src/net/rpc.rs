@@ -5,6 +5,10 @@ use std::{
     sync::Arc,
 };
 
+/// Maximum number of heights in a `GetBlocks` request, and of blocks in its response. The count
+/// is serialized as a u16.
+pub const MAX_GET_BLOCKS: usize = 0xFFFF;
+
 #[repr(u8)]
 pub enum RpcType {
     // Operations that can update the connection or blockchain state
@@ -15,6 +19,14 @@ pub enum RpcType {
     Subscribe = 0x13,
     /// Unsubscribe from receiving block updates.
     Unsubscribe = 0x14,
+    /// Subscribe to receive transactions as they are accepted into the memory pool.
+    SubscribeTxs = 0x15,
+    /// Subscribe to receive only the receipts of new blocks that match the block filter set on the
+    /// connection when subscribing. Replaces any full block subscription and requires a block
+    /// filter to be set.
+    SubscribeReceipts = 0x16,
+    /// Removes a transaction from the minter's pending pool. Requires minter authentication.
+    DropPending = 0x17,
 
     // Getters
     GetProperties = 0x20,
@@ -22,6 +34,58 @@ pub enum RpcType {
     GetFullBlock = 0x22,
     GetBlockRange = 0x23,
     GetAccountInfo = 0x24,
+    GetBlocks = 0x25,
+    /// Response only, sent before the blocks of a `GetBlockRange` request.
+    GetBlockRangeStart = 0x26,
+    /// Response only, pushed to transaction subscribers when a transaction is accepted.
+    TxAccepted = 0x27,
+    /// Response only, pushed to receipt subscribers when a block is produced.
+    FilteredReceipts = 0x28,
+    /// Retrieves the transactions in the minter's pending pool. Requires minter authentication.
+    GetPending = 0x29,
+    ListAccounts = 0x2A,
+    /// Retrieves a summary of the chain for explorers.
+    GetStats = 0x2B,
+}
+
+/// Authenticates a request that may only be made by the operator of the minter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinterAuth {
+    /// Epoch time in seconds when the request is no longer accepted, limiting how long a captured
+    /// request can be replayed. Must not be further than `MINTER_AUTH_MAX_EXPIRY_TIME` away.
+    pub expiry: u64,
+    /// Signature by the owner's minter key over the request type, the expiry, and any request
+    /// arguments.
+    pub signature: SigPair,
+}
+
+impl MinterAuth {
+    fn sign(minter_key: &KeyPair, expiry: u64, rpc_type: RpcType, args: &[u8]) -> Self {
+        let data = minter_auth_data(expiry, rpc_type, args);
+        Self {
+            expiry,
+            signature: minter_key.sign(&data),
+        }
+    }
+
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        buf.push_u64(self.expiry);
+        buf.push_sig_pair(&self.signature);
+    }
+
+    fn deserialize(cursor: &mut Cursor<&[u8]>) -> io::Result<Self> {
+        let expiry = cursor.take_u64()?;
+        let signature = cursor.take_sig_pair()?;
+        Ok(Self { expiry, signature })
+    }
+}
+
+fn minter_auth_data(expiry: u64, rpc_type: RpcType, args: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(9 + args.len());
+    buf.push(rpc_type as u8);
+    buf.push_u64(expiry);
+    buf.extend_from_slice(args);
+    buf
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -31,14 +95,66 @@ pub enum Request {
     ClearBlockFilter,
     Subscribe,
     Unsubscribe,
+    SubscribeTxs,
+    SubscribeReceipts,
     GetProperties,
     GetBlock(u64),           // height
     GetFullBlock(u64),       // height
     GetBlockRange(u64, u64), // min height, max height
     GetAccountInfo(AccountId),
+    /// Requests the blocks at the heights. At most `MAX_GET_BLOCKS` heights may be requested,
+    /// serializing more panics.
+    GetBlocks(Vec<u64>),
+    GetPending(MinterAuth),
+    DropPending(TxId, MinterAuth),
+    /// Lists indexed accounts in ascending ID order starting at the `start` ID inclusively, or the
+    /// lowest ID when `None`. The server may return fewer accounts than the limit.
+    ListAccounts {
+        start: Option<AccountId>,
+        limit: u16,
+    },
+    GetStats,
 }
 
 impl Request {
+    /// Creates a `GetPending` request authenticated with the minter key.
+    pub fn new_get_pending(minter_key: &KeyPair, expiry: u64) -> Self {
+        let auth = MinterAuth::sign(minter_key, expiry, RpcType::GetPending, &[]);
+        Self::GetPending(auth)
+    }
+
+    /// Creates a `DropPending` request authenticated with the minter key.
+    pub fn new_drop_pending(txid: TxId, minter_key: &KeyPair, expiry: u64) -> Self {
+        let auth = MinterAuth::sign(minter_key, expiry, RpcType::DropPending, txid.as_ref());
+        Self::DropPending(txid, auth)
+    }
+
+    /// Returns the authentication of requests that may only be made by the minter.
+    pub fn minter_auth(&self) -> Option<&MinterAuth> {
+        match self {
+            Self::GetPending(auth) => Some(auth),
+            Self::DropPending(_, auth) => Some(auth),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the request is authenticated with a valid signature by the minter key. The
+    /// expiry is not checked against the current time.
+    pub fn verify_minter_auth(&self, minter: &PublicKey) -> bool {
+        let (auth, data) = match self {
+            Self::GetPending(auth) => (
+                auth,
+                minter_auth_data(auth.expiry, RpcType::GetPending, &[]),
+            ),
+            Self::DropPending(txid, auth) => (
+                auth,
+                minter_auth_data(auth.expiry, RpcType::DropPending, txid.as_ref()),
+            ),
+            _ => return false,
+        };
+        auth.signature.pub_key == *minter && auth.signature.verify(&data)
+    }
+
     pub fn serialize(&self, buf: &mut Vec<u8>) {
         match self {
             Self::Broadcast(tx) => {
@@ -46,17 +162,29 @@ impl Request {
                 buf.push(RpcType::Broadcast as u8);
                 tx.serialize(buf);
             }
-            Self::SetBlockFilter(filter) => {
-                buf.reserve_exact(1 + (filter.len() * mem::size_of::<AccountId>()));
-                buf.push(RpcType::SetBlockFilter as u8);
-                buf.push(filter.len() as u8);
-                for acc in filter {
-                    buf.push_u64(*acc);
+            Self::SetBlockFilter(filter) => match filter {
+                BlockFilter::Accounts(accounts) => {
+                    buf.reserve_exact(3 + (accounts.len() * mem::size_of::<AccountId>()));
+                    buf.push(RpcType::SetBlockFilter as u8);
+                    buf.push(0);
+                    buf.push(accounts.len() as u8);
+                    for acc in accounts {
+                        buf.push_u64(*acc);
+                    }
                 }
-            }
+                BlockFilter::Bloom(bloom) => {
+                    buf.reserve_exact(7 + bloom.bits().len());
+                    buf.push(RpcType::SetBlockFilter as u8);
+                    buf.push(1);
+                    buf.push(bloom.hash_count());
+                    buf.push_bytes(bloom.bits());
+                }
+            },
             Self::ClearBlockFilter => buf.push(RpcType::ClearBlockFilter as u8),
             Self::Subscribe => buf.push(RpcType::Subscribe as u8),
             Self::Unsubscribe => buf.push(RpcType::Unsubscribe as u8),
+            Self::SubscribeTxs => buf.push(RpcType::SubscribeTxs as u8),
+            Self::SubscribeReceipts => buf.push(RpcType::SubscribeReceipts as u8),
             Self::GetProperties => buf.push(RpcType::GetProperties as u8),
             Self::GetBlock(height) => {
                 buf.reserve_exact(9);
@@ -79,6 +207,37 @@ impl Request {
                 buf.push(RpcType::GetAccountInfo as u8);
                 buf.push_u64(*acc);
             }
+            Self::GetBlocks(heights) => {
+                assert!(
+                    heights.len() <= MAX_GET_BLOCKS,
+                    "GetBlocks request exceeds {} heights",
+                    MAX_GET_BLOCKS
+                );
+                buf.reserve_exact(3 + (heights.len() * mem::size_of::<u64>()));
+                buf.push(RpcType::GetBlocks as u8);
+                buf.push_u16(heights.len() as u16);
+                for height in heights {
+                    buf.push_u64(*height);
+                }
+            }
+            Self::GetPending(auth) => {
+                buf.reserve_exact(1 + 8 + 96);
+                buf.push(RpcType::GetPending as u8);
+                auth.serialize(buf);
+            }
+            Self::DropPending(txid, auth) => {
+                buf.reserve_exact(1 + 32 + 8 + 96);
+                buf.push(RpcType::DropPending as u8);
+                buf.extend_from_slice(txid.as_ref());
+                auth.serialize(buf);
+            }
+            Self::ListAccounts { start, limit } => {
+                buf.reserve_exact(12);
+                buf.push(RpcType::ListAccounts as u8);
+                serialize_opt_account_id(buf, *start);
+                buf.push_u16(*limit);
+            }
+            Self::GetStats => buf.push(RpcType::GetStats as u8),
         }
     }
 
@@ -90,17 +249,33 @@ impl Request {
                     .ok_or_else(|| Error::new(io::ErrorKind::InvalidData, "failed to decode tx"))?;
                 Ok(Self::Broadcast(tx))
             }
-            t if t == RpcType::SetBlockFilter as u8 => {
-                let acc_len = usize::from(cursor.take_u8()?);
-                let mut filter = BlockFilter::new();
-                for _ in 0..acc_len {
-                    filter.insert(cursor.take_u64()?);
+            t if t == RpcType::SetBlockFilter as u8 => match cursor.take_u8()? {
+                0 => {
+                    let acc_len = usize::from(cursor.take_u8()?);
+                    let mut filter = BlockFilter::new();
+                    for _ in 0..acc_len {
+                        filter.insert(cursor.take_u64()?);
+                    }
+                    Ok(Self::SetBlockFilter(filter))
                 }
-                Ok(Self::SetBlockFilter(filter))
-            }
+                1 => {
+                    let hash_count = cursor.take_u8()?;
+                    let bits = cursor.take_bytes()?;
+                    let bloom = BloomFilter::from_parts(bits, hash_count).ok_or_else(|| {
+                        Error::new(io::ErrorKind::InvalidData, "invalid bloom filter")
+                    })?;
+                    Ok(Self::SetBlockFilter(BlockFilter::Bloom(bloom)))
+                }
+                _ => Err(Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid block filter type",
+                )),
+            },
             t if t == RpcType::ClearBlockFilter as u8 => Ok(Self::ClearBlockFilter),
             t if t == RpcType::Subscribe as u8 => Ok(Self::Subscribe),
             t if t == RpcType::Unsubscribe as u8 => Ok(Self::Unsubscribe),
+            t if t == RpcType::SubscribeTxs as u8 => Ok(Self::SubscribeTxs),
+            t if t == RpcType::SubscribeReceipts as u8 => Ok(Self::SubscribeReceipts),
             t if t == RpcType::GetProperties as u8 => Ok(Self::GetProperties),
             t if t == RpcType::GetBlock as u8 => {
                 let height = cursor.take_u64()?;
@@ -119,6 +294,29 @@ impl Request {
                 let acc = cursor.take_u64()?;
                 Ok(Self::GetAccountInfo(acc))
             }
+            t if t == RpcType::GetBlocks as u8 => {
+                let len = usize::from(cursor.take_u16()?);
+                let mut heights = Vec::with_capacity(len);
+                for _ in 0..len {
+                    heights.push(cursor.take_u64()?);
+                }
+                Ok(Self::GetBlocks(heights))
+            }
+            t if t == RpcType::GetPending as u8 => {
+                let auth = MinterAuth::deserialize(cursor)?;
+                Ok(Self::GetPending(auth))
+            }
+            t if t == RpcType::DropPending as u8 => {
+                let txid = TxId::from_digest(cursor.take_digest()?);
+                let auth = MinterAuth::deserialize(cursor)?;
+                Ok(Self::DropPending(txid, auth))
+            }
+            t if t == RpcType::ListAccounts as u8 => {
+                let start = deserialize_opt_account_id(cursor)?;
+                let limit = cursor.take_u16()?;
+                Ok(Self::ListAccounts { start, limit })
+            }
+            t if t == RpcType::GetStats as u8 => Ok(Self::GetStats),
             _ => Err(Error::new(
                 io::ErrorKind::InvalidData,
                 "invalid rpc request",
@@ -134,11 +332,38 @@ pub enum Response {
     ClearBlockFilter,
     Subscribe,
     Unsubscribe,
+    SubscribeTxs,
+    /// Transaction accepted into the memory pool, pushed to clients subscribed with `SubscribeTxs`.
+    TxAccepted(TxVariant),
+    SubscribeReceipts,
+    /// Receipts of a new block matching the block filter, pushed to clients subscribed with
+    /// `SubscribeReceipts`.
+    FilteredReceipts {
+        height: u64,
+        receipts: Vec<Receipt>,
+    },
     GetProperties(Properties),
     GetBlock(FilteredBlock),
     GetFullBlock(Arc<Block>),
     GetBlockRange,
+    /// Precedes the blocks streamed back from a `GetBlockRange` request with the number of blocks
+    /// that will be sent before the `GetBlockRange` finalizer.
+    GetBlockRangeStart {
+        count: u64,
+    },
     GetAccountInfo(AccountInfo),
+    /// Blocks in the same order as the requested heights, `None` for heights that don't exist.
+    GetBlocks(Vec<Option<FilteredBlock>>),
+    /// Transactions in the minter's pending pool in the order they were accepted.
+    GetPending(Vec<TxVariant>),
+    DropPending,
+    /// Accounts in ascending ID order. `next` is the ID to start the following page at, or `None`
+    /// when there are no more accounts.
+    ListAccounts {
+        accounts: Vec<Account>,
+        next: Option<AccountId>,
+    },
+    GetStats(ChainStats),
 }
 
 impl Response {
@@ -149,6 +374,22 @@ impl Response {
             Self::ClearBlockFilter => buf.push(RpcType::ClearBlockFilter as u8),
             Self::Subscribe => buf.push(RpcType::Subscribe as u8),
             Self::Unsubscribe => buf.push(RpcType::Unsubscribe as u8),
+            Self::SubscribeTxs => buf.push(RpcType::SubscribeTxs as u8),
+            Self::SubscribeReceipts => buf.push(RpcType::SubscribeReceipts as u8),
+            Self::FilteredReceipts { height, receipts } => {
+                buf.reserve_exact(65536);
+                buf.push(RpcType::FilteredReceipts as u8);
+                buf.push_u64(*height);
+                buf.push_u32(receipts.len() as u32);
+                for receipt in receipts {
+                    receipt.serialize(buf);
+                }
+            }
+            Self::TxAccepted(tx) => {
+                buf.reserve_exact(4096);
+                buf.push(RpcType::TxAccepted as u8);
+                tx.serialize(buf);
+            }
             Self::GetProperties(props) => {
                 buf.reserve_exact(4096 + mem::size_of::<Properties>());
                 buf.push(RpcType::GetProperties as u8);
@@ -159,22 +400,14 @@ impl Response {
                     buf.extend_from_slice(&tx_buf);
                 }
                 buf.push_asset(props.network_fee);
+                buf.push_u64(props.network_fee_height);
+                buf.push_u64(props.next_fee_adjust_height);
                 buf.push_asset(props.token_supply);
             }
             Self::GetBlock(block) => {
                 buf.reserve_exact(1_048_576);
                 buf.push(RpcType::GetBlock as u8);
-                match block {
-                    FilteredBlock::Header((header, signer)) => {
-                        buf.push(0);
-                        header.serialize(buf);
-                        buf.push_sig_pair(signer);
-                    }
-                    FilteredBlock::Block(block) => {
-                        buf.push(1);
-                        block.serialize(buf);
-                    }
-                }
+                serialize_filtered_block(buf, block);
             }
             Self::GetFullBlock(block) => {
                 buf.reserve_exact(1_048_576);
@@ -182,13 +415,69 @@ impl Response {
                 block.serialize(buf);
             }
             Self::GetBlockRange => buf.push(RpcType::GetBlockRange as u8),
+            Self::GetBlockRangeStart { count } => {
+                buf.reserve_exact(9);
+                buf.push(RpcType::GetBlockRangeStart as u8);
+                buf.push_u64(*count);
+            }
             Self::GetAccountInfo(info) => {
-                buf.reserve_exact(1 + mem::size_of::<Account>());
+                buf.reserve_exact(
+                    1 + info.account.serialized_size()
+                        + var_i64_size(info.net_fee.amount)
+                        + var_i64_size(info.account_fee.amount),
+                );
                 buf.push(RpcType::GetAccountInfo as u8);
                 info.account.serialize(buf);
                 buf.push_asset(info.net_fee);
                 buf.push_asset(info.account_fee);
             }
+            Self::GetBlocks(blocks) => {
+                assert!(
+                    blocks.len() <= MAX_GET_BLOCKS,
+                    "GetBlocks response exceeds {} blocks",
+                    MAX_GET_BLOCKS
+                );
+                buf.reserve_exact(1_048_576);
+                buf.push(RpcType::GetBlocks as u8);
+                buf.push_u16(blocks.len() as u16);
+                for block in blocks {
+                    match block {
+                        Some(block) => {
+                            buf.push(1);
+                            serialize_filtered_block(buf, block);
+                        }
+                        None => buf.push(0),
+                    }
+                }
+            }
+            Self::GetPending(txs) => {
+                buf.reserve_exact(65536);
+                buf.push(RpcType::GetPending as u8);
+                buf.push_u32(txs.len() as u32);
+                for tx in txs {
+                    tx.serialize(buf);
+                }
+            }
+            Self::DropPending => buf.push(RpcType::DropPending as u8),
+            Self::ListAccounts { accounts, next } => {
+                buf.reserve_exact(65536);
+                buf.push(RpcType::ListAccounts as u8);
+                buf.push_u16(accounts.len() as u16);
+                for account in accounts {
+                    account.serialize(buf);
+                }
+                serialize_opt_account_id(buf, *next);
+            }
+            Self::GetStats(stats) => {
+                buf.reserve_exact(1 + (5 * mem::size_of::<u64>()) + 10);
+                buf.push(RpcType::GetStats as u8);
+                buf.push_u64(stats.height);
+                buf.push_asset(stats.token_supply);
+                buf.push_u64(stats.owner_id);
+                buf.push_u64(stats.account_count);
+                buf.push_u64(stats.window_blocks);
+                buf.push_u64(stats.window_txs);
+            }
         }
     }
 
@@ -200,6 +489,25 @@ impl Response {
             t if t == RpcType::ClearBlockFilter as u8 => Ok(Self::ClearBlockFilter),
             t if t == RpcType::Subscribe as u8 => Ok(Self::Subscribe),
             t if t == RpcType::Unsubscribe as u8 => Ok(Self::Unsubscribe),
+            t if t == RpcType::SubscribeTxs as u8 => Ok(Self::SubscribeTxs),
+            t if t == RpcType::SubscribeReceipts as u8 => Ok(Self::SubscribeReceipts),
+            t if t == RpcType::FilteredReceipts as u8 => {
+                let height = cursor.take_u64()?;
+                let len = cursor.take_u32()?;
+                let mut receipts = Vec::with_capacity(len as usize);
+                for _ in 0..len {
+                    let receipt = Receipt::deserialize(cursor).ok_or_else(|| {
+                        Error::new(io::ErrorKind::InvalidData, "failed to decode receipt")
+                    })?;
+                    receipts.push(receipt);
+                }
+                Ok(Self::FilteredReceipts { height, receipts })
+            }
+            t if t == RpcType::TxAccepted as u8 => {
+                let tx = TxVariant::deserialize(cursor)
+                    .ok_or_else(|| Error::new(io::ErrorKind::InvalidData, "failed to decode tx"))?;
+                Ok(Self::TxAccepted(tx))
+            }
             t if t == RpcType::GetProperties as u8 => {
                 let height = cursor.take_u64()?;
                 let owner = {
@@ -219,33 +527,21 @@ impl Response {
                     }
                 };
                 let network_fee = cursor.take_asset()?;
+                let network_fee_height = cursor.take_u64()?;
+                let next_fee_adjust_height = cursor.take_u64()?;
                 let token_supply = cursor.take_asset()?;
                 Ok(Self::GetProperties(Properties {
                     height,
                     owner,
                     network_fee,
+                    network_fee_height,
+                    next_fee_adjust_height,
                     token_supply,
                 }))
             }
             t if t == RpcType::GetBlock as u8 => {
-                let filtered_type = cursor.take_u8()?;
-                match filtered_type {
-                    0 => {
-                        let header = BlockHeader::deserialize(cursor)
-                            .ok_or_else(|| Error::from(io::ErrorKind::UnexpectedEof))?;
-                        let signer = cursor.take_sig_pair()?;
-                        Ok(Self::GetBlock(FilteredBlock::Header((header, signer))))
-                    }
-                    1 => {
-                        let block = Block::deserialize(cursor)
-                            .ok_or_else(|| Error::from(io::ErrorKind::UnexpectedEof))?;
-                        Ok(Self::GetBlock(FilteredBlock::Block(Arc::new(block))))
-                    }
-                    _ => Err(Error::new(
-                        io::ErrorKind::InvalidData,
-                        "invalid GetBlock response",
-                    )),
-                }
+                let block = deserialize_filtered_block(cursor)?;
+                Ok(Self::GetBlock(block))
             }
             t if t == RpcType::GetFullBlock as u8 => {
                 let block = Block::deserialize(cursor)
@@ -253,6 +549,10 @@ impl Response {
                 Ok(Self::GetFullBlock(Arc::new(block)))
             }
             t if t == RpcType::GetBlockRange as u8 => Ok(Self::GetBlockRange),
+            t if t == RpcType::GetBlockRangeStart as u8 => {
+                let count = cursor.take_u64()?;
+                Ok(Self::GetBlockRangeStart { count })
+            }
             t if t == RpcType::GetAccountInfo as u8 => {
                 let account = Account::deserialize(cursor)?;
                 let net_fee = cursor.take_asset()?;
@@ -263,6 +563,60 @@ impl Response {
                     account_fee,
                 }))
             }
+            t if t == RpcType::GetBlocks as u8 => {
+                let len = usize::from(cursor.take_u16()?);
+                let mut blocks = Vec::with_capacity(len);
+                for _ in 0..len {
+                    match cursor.take_u8()? {
+                        0 => blocks.push(None),
+                        1 => blocks.push(Some(deserialize_filtered_block(cursor)?)),
+                        _ => {
+                            return Err(Error::new(
+                                io::ErrorKind::InvalidData,
+                                "invalid GetBlocks response",
+                            ))
+                        }
+                    }
+                }
+                Ok(Self::GetBlocks(blocks))
+            }
+            t if t == RpcType::GetPending as u8 => {
+                let len = cursor.take_u32()?;
+                let mut txs = Vec::with_capacity(len as usize);
+                for _ in 0..len {
+                    let tx = TxVariant::deserialize(cursor).ok_or_else(|| {
+                        Error::new(io::ErrorKind::InvalidData, "failed to decode tx")
+                    })?;
+                    txs.push(tx);
+                }
+                Ok(Self::GetPending(txs))
+            }
+            t if t == RpcType::DropPending as u8 => Ok(Self::DropPending),
+            t if t == RpcType::ListAccounts as u8 => {
+                let len = usize::from(cursor.take_u16()?);
+                let mut accounts = Vec::with_capacity(len);
+                for _ in 0..len {
+                    accounts.push(Account::deserialize(cursor)?);
+                }
+                let next = deserialize_opt_account_id(cursor)?;
+                Ok(Self::ListAccounts { accounts, next })
+            }
+            t if t == RpcType::GetStats as u8 => {
+                let height = cursor.take_u64()?;
+                let token_supply = cursor.take_asset()?;
+                let owner_id = cursor.take_u64()?;
+                let account_count = cursor.take_u64()?;
+                let window_blocks = cursor.take_u64()?;
+                let window_txs = cursor.take_u64()?;
+                Ok(Self::GetStats(ChainStats {
+                    height,
+                    token_supply,
+                    owner_id,
+                    account_count,
+                    window_blocks,
+                    window_txs,
+                }))
+            }
             _ => Err(Error::new(
                 io::ErrorKind::InvalidData,
                 "invalid rpc response",
@@ -270,3 +624,59 @@ impl Response {
         }
     }
 }
+
+fn serialize_opt_account_id(buf: &mut Vec<u8>, id: Option<AccountId>) {
+    match id {
+        Some(id) => {
+            buf.push(1);
+            buf.push_u64(id);
+        }
+        None => buf.push(0),
+    }
+}
+
+fn deserialize_opt_account_id(cursor: &mut Cursor<&[u8]>) -> io::Result<Option<AccountId>> {
+    match cursor.take_u8()? {
+        0 => Ok(None),
+        1 => Ok(Some(cursor.take_u64()?)),
+        _ => Err(Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid optional account id",
+        )),
+    }
+}
+
+fn serialize_filtered_block(buf: &mut Vec<u8>, block: &FilteredBlock) {
+    match block {
+        FilteredBlock::Header((header, signer)) => {
+            buf.push(0);
+            header.serialize(buf);
+            buf.push_sig_pair(signer);
+        }
+        FilteredBlock::Block(block) => {
+            buf.push(1);
+            block.serialize(buf);
+        }
+    }
+}
+
+fn deserialize_filtered_block(cursor: &mut Cursor<&[u8]>) -> io::Result<FilteredBlock> {
+    let filtered_type = cursor.take_u8()?;
+    match filtered_type {
+        0 => {
+            let header = BlockHeader::deserialize(cursor)
+                .ok_or_else(|| Error::from(io::ErrorKind::UnexpectedEof))?;
+            let signer = cursor.take_sig_pair()?;
+            Ok(FilteredBlock::Header((header, signer)))
+        }
+        1 => {
+            let block = Block::deserialize(cursor)
+                .ok_or_else(|| Error::from(io::ErrorKind::UnexpectedEof))?;
+            Ok(FilteredBlock::Block(Arc::new(block)))
+        }
+        _ => Err(Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid filtered block",
+        )),
+    }
+}
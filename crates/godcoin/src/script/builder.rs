@@ -1,5 +1,5 @@
 use super::{op::*, Script};
-use crate::{constants::MAX_SCRIPT_BYTE_SIZE, serializer::*};
+use crate::{account::AccountIdBytes, constants::MAX_SCRIPT_BYTE_SIZE, serializer::*};
 
 type FnRef = (u8, u32); // ID, pointer
 
@@ -99,7 +99,7 @@ impl FnBuilder {
             OpFrame::True => self.byte_code.push(Operand::PushTrue.into()),
             OpFrame::AccountId(acc) => {
                 self.byte_code.push(Operand::PushAccountId.into());
-                self.byte_code.extend(&acc.to_be_bytes());
+                self.byte_code.extend(&acc.to_bytes());
             }
             OpFrame::Asset(asset) => {
                 self.byte_code.push(Operand::PushAsset.into());
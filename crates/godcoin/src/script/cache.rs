@@ -0,0 +1,39 @@
+use super::{OpFrame, Script};
+use crate::crypto::{double_sha256, Digest};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+/// Decoded operations of a script keyed by byte position, along with the byte position of the
+/// following operation.
+pub(crate) type DecodedOps = Rc<RefCell<HashMap<usize, (OpFrame, usize)>>>;
+
+/// Caches decoded script operations across evaluations, keyed by the hash of the script. Sharing a
+/// cache when evaluating multiple transactions in a block avoids decoding the same script again
+/// when an account sends many transactions.
+#[derive(Debug, Default)]
+pub struct ScriptCache {
+    scripts: RefCell<HashMap<Digest, DecodedOps>>,
+}
+
+impl ScriptCache {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct scripts cached.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.scripts.borrow().len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.scripts.borrow().is_empty()
+    }
+
+    pub(crate) fn get_ops(&self, script: &Script) -> DecodedOps {
+        let hash = double_sha256(script);
+        let mut scripts = self.scripts.borrow_mut();
+        Rc::clone(scripts.entry(hash).or_default())
+    }
+}
@@ -1,6 +1,6 @@
-use std::{borrow::Cow, convert::TryInto, mem};
+use std::{borrow::Cow, mem};
 
-use super::{stack::*, *};
+use super::{cache::DecodedOps, stack::*, *};
 use crate::{
     account::{AccountId, PermsSigVerifyErr},
     asset::Asset,
@@ -26,6 +26,7 @@ pub struct EngineData<'a> {
 #[derive(Debug)]
 pub struct ScriptEngine<'a> {
     data: EngineData<'a>,
+    ops: DecodedOps,
     pos: usize,
     stack: Stack,
     log: Vec<LogEntry>,
@@ -35,6 +36,17 @@ pub struct ScriptEngine<'a> {
 
 impl<'a> ScriptEngine<'a> {
     pub fn new(data: EngineData<'a>) -> Self {
+        Self::with_ops(data, DecodedOps::default())
+    }
+
+    /// Creates an engine that reuses and populates the decoded operations of the script from the
+    /// provided cache.
+    pub fn with_cache(data: EngineData<'a>, cache: &ScriptCache) -> Self {
+        let ops = cache.get_ops(&data.script);
+        Self::with_ops(data, ops)
+    }
+
+    fn with_ops(data: EngineData<'a>, ops: DecodedOps) -> Self {
         let total_amt = match data.tx_data.tx() {
             TxVariant::V0(tx) => match tx {
                 TxVariantV0::TransferTx(tx) => tx.amount,
@@ -44,6 +56,7 @@ impl<'a> ScriptEngine<'a> {
 
         Self {
             data,
+            ops,
             pos: 0,
             stack: Stack::new(),
             log: vec![],
@@ -379,112 +392,22 @@ impl<'a> ScriptEngine<'a> {
     }
 
     fn consume_op(&mut self) -> Result<Option<OpFrame>, EvalErr> {
-        macro_rules! read_bytes {
-            ($self:expr, $len:expr) => {
-                match $self.data.script.get($self.pos..$self.pos + $len) {
-                    Some(b) => {
-                        $self.pos += $len;
-                        b
-                    }
-                    None => {
-                        return Err($self.new_err(EvalErrKind::UnexpectedEOF));
-                    }
-                }
-            };
-            ($self:expr) => {
-                match $self.data.script.get($self.pos) {
-                    Some(b) => {
-                        $self.pos += 1;
-                        *b
-                    }
-                    None => {
-                        return Err($self.new_err(EvalErrKind::UnexpectedEOF));
-                    }
-                }
-            };
+        let pos = self.pos;
+        if let Some((op, next_pos)) = self.ops.borrow().get(&pos) {
+            self.pos = *next_pos;
+            return Ok(Some(op.clone()));
         }
 
-        if self.pos == self.data.script.len() {
-            return Ok(None);
-        }
-        let byte = self.data.script[self.pos];
-        self.pos += 1;
-
-        match byte {
-            // Function definition
-            o if o == Operand::OpDefine as u8 => {
-                let arg_cnt = read_bytes!(self);
-                let mut args = Vec::with_capacity(usize::from(arg_cnt));
-                for _ in 0..arg_cnt {
-                    let tag_byte = read_bytes!(self);
-                    let arg = tag_byte
-                        .try_into()
-                        .map_err(|_| self.new_err(EvalErrKind::UnknownArgType))?;
-                    args.push(arg);
-                }
-                Ok(Some(OpFrame::OpDefine(args)))
-            }
-            // Events
-            o if o == Operand::OpTransfer as u8 => Ok(Some(OpFrame::OpTransfer)),
-            o if o == Operand::OpDestroy as u8 => Ok(Some(OpFrame::OpDestroy)),
-            // Push value
-            o if o == Operand::PushFalse as u8 => Ok(Some(OpFrame::False)),
-            o if o == Operand::PushTrue as u8 => Ok(Some(OpFrame::True)),
-            o if o == Operand::PushAccountId as u8 => {
-                let slice = read_bytes!(self, mem::size_of::<u64>());
-                let id = u64::from_be_bytes(slice.try_into().unwrap());
-                Ok(Some(OpFrame::AccountId(id)))
-            }
-            o if o == Operand::PushAsset as u8 => {
-                let slice = read_bytes!(self, mem::size_of::<i64>());
-                let amt = i64::from_be_bytes(slice.try_into().unwrap());
-                let amt = Asset::new(amt);
-                Ok(Some(OpFrame::Asset(amt)))
-            }
-            // Arithmetic
-            o if o == Operand::OpLoadAmt as u8 => Ok(Some(OpFrame::OpLoadAmt)),
-            o if o == Operand::OpLoadRemAmt as u8 => Ok(Some(OpFrame::OpLoadRemAmt)),
-            o if o == Operand::OpAdd as u8 => Ok(Some(OpFrame::OpAdd)),
-            o if o == Operand::OpSub as u8 => Ok(Some(OpFrame::OpSub)),
-            o if o == Operand::OpMul as u8 => Ok(Some(OpFrame::OpMul)),
-            o if o == Operand::OpDiv as u8 => Ok(Some(OpFrame::OpDiv)),
-            // Logic
-            o if o == Operand::OpNot as u8 => Ok(Some(OpFrame::OpNot)),
-            o if o == Operand::OpIf as u8 => Ok(Some(OpFrame::OpIf)),
-            o if o == Operand::OpElse as u8 => Ok(Some(OpFrame::OpElse)),
-            o if o == Operand::OpEndIf as u8 => Ok(Some(OpFrame::OpEndIf)),
-            o if o == Operand::OpReturn as u8 => Ok(Some(OpFrame::OpReturn)),
-            o if o == Operand::OpAbort as u8 => Ok(Some(OpFrame::OpAbort)),
-            // Crypto
-            o if o == Operand::OpCheckPerms as u8 => Ok(Some(OpFrame::OpCheckPerms)),
-            o if o == Operand::OpCheckPermsFastFail as u8 => {
-                Ok(Some(OpFrame::OpCheckPermsFastFail))
-            }
-            o if o == Operand::OpCheckMultiPerms as u8 => {
-                let threshold = read_bytes!(self);
-                let acc_count = read_bytes!(self);
-                Ok(Some(OpFrame::OpCheckMultiPerms(threshold, acc_count)))
-            }
-            o if o == Operand::OpCheckMultiPermsFastFail as u8 => {
-                let threshold = read_bytes!(self);
-                let acc_count = read_bytes!(self);
-                Ok(Some(OpFrame::OpCheckMultiPermsFastFail(
-                    threshold, acc_count,
-                )))
-            }
-            // Lock time
-            o if o == Operand::OpCheckTime as u8 => {
-                let slice = read_bytes!(self, mem::size_of::<u64>());
-                let time = u64::from_be_bytes(slice.try_into().unwrap());
-                Ok(Some(OpFrame::OpCheckTime(time)))
-            }
-            o if o == Operand::OpCheckTimeFastFail as u8 => {
-                let slice = read_bytes!(self, mem::size_of::<u64>());
-                let time = u64::from_be_bytes(slice.try_into().unwrap());
-                Ok(Some(OpFrame::OpCheckTimeFastFail(time)))
-            }
-            _ => Err(self.new_err(EvalErrKind::UnknownOp)),
+        let op = self.decode_op()?;
+        if let Some(op) = &op {
+            self.ops.borrow_mut().insert(pos, (op.clone(), self.pos));
         }
+        Ok(op)
+    }
+
+    #[inline]
+    fn decode_op(&mut self) -> Result<Option<OpFrame>, EvalErr> {
+        decode_op(&self.data.script, &mut self.pos)
     }
 
     fn check_acc_perms(&mut self, threshold: usize, accs: &[AccountId]) -> Result<bool, EvalErr> {
@@ -589,6 +512,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn disassemble_script() {
+        let script = Builder::new()
+            .push(
+                FnBuilder::new(0, OpFrame::OpDefine(vec![]))
+                    .push(OpFrame::AccountId(1))
+                    .push(OpFrame::OpCheckPerms),
+            )
+            .push(
+                FnBuilder::new(1, OpFrame::OpDefine(vec![Arg::AccountId, Arg::Asset]))
+                    .push(OpFrame::OpTransfer)
+                    .push(OpFrame::True),
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(script.fn_ptrs().unwrap(), vec![(0, 11), (1, 23)]);
+        assert_eq!(
+            script.disassemble().unwrap(),
+            vec![
+                (11, OpFrame::OpDefine(vec![])),
+                (13, OpFrame::AccountId(1)),
+                (22, OpFrame::OpCheckPerms),
+                (23, OpFrame::OpDefine(vec![Arg::AccountId, Arg::Asset])),
+                (27, OpFrame::OpTransfer),
+                (28, OpFrame::True),
+            ]
+        );
+
+        let truncated = Script::new(script[..script.len() - 2].to_vec());
+        assert!(truncated.disassemble().is_ok());
+        let truncated = Script::new(script[..15].to_vec());
+        assert_eq!(
+            truncated.disassemble().unwrap_err().err,
+            EvalErrKind::UnexpectedEOF
+        );
+        assert_eq!(
+            Script::new(vec![2, 0]).disassemble().unwrap_err().err,
+            EvalErrKind::HeaderReadErr
+        );
+    }
+
     #[test]
     fn arithmetic_loadamt() {
         TestEngine::new().get(
@@ -1826,6 +1791,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn shared_cache_preserves_results() {
+        let test = TestEngine::new();
+        let script = Builder::new()
+            .push(
+                FnBuilder::new(0, OpFrame::OpDefine(vec![]))
+                    .push(OpFrame::True)
+                    .push(OpFrame::OpIf)
+                    .push(OpFrame::True)
+                    .push(OpFrame::OpElse)
+                    .push(OpFrame::False)
+                    .push(OpFrame::OpEndIf),
+            )
+            .build()
+            .unwrap();
+        let tx = test.new_transfer_tx(0, vec![], &[test.from_key.clone()]);
+        let new_data = || EngineData {
+            script: (&script).into(),
+            tx_data: TxPrecompData::from_tx(&tx).into(),
+            chain: &test.chain,
+            additional_receipts: &[],
+        };
+
+        let expected = ScriptEngine::new(new_data()).eval();
+        assert_eq!(
+            expected,
+            Ok(vec![test.from_transfer_entry("10.00000 TEST")])
+        );
+
+        let cache = ScriptCache::new();
+        for _ in 0..3 {
+            let res = ScriptEngine::with_cache(new_data(), &cache).eval();
+            assert_eq!(res, expected);
+            assert_eq!(cache.len(), 1);
+        }
+    }
+
     struct TestEngine {
         tmp_dir: PathBuf,
         chain: Blockchain,
@@ -7,12 +7,14 @@ use std::{
 };
 
 pub mod builder;
+pub mod cache;
 pub mod engine;
 pub mod error;
 pub mod op;
 mod stack;
 
 pub use self::builder::*;
+pub use self::cache::*;
 pub use self::engine::*;
 pub use self::error::*;
 pub use self::op::*;
@@ -42,6 +44,38 @@ impl Script {
 
         Ok(None)
     }
+
+    /// Returns the function IDs defined in the script header along with the byte position of each
+    /// function.
+    pub fn fn_ptrs(&self) -> io::Result<Vec<(u8, u32)>> {
+        let mut cur = Cursor::<&[u8]>::new(&self.0);
+        let fn_count = cur.take_u8()?;
+        let mut ptrs = Vec::with_capacity(usize::from(fn_count));
+        for _ in 0..fn_count {
+            let fn_id = cur.take_u8()?;
+            let pos = cur.take_u32()?;
+            ptrs.push((fn_id, pos));
+        }
+        Ok(ptrs)
+    }
+
+    /// Decodes every operation following the script header along with its byte position.
+    pub fn disassemble(&self) -> Result<Vec<(usize, OpFrame)>, EvalErr> {
+        let fn_count = self
+            .fn_ptrs()
+            .map_err(|_| EvalErr::new(0, EvalErrKind::HeaderReadErr))?
+            .len();
+        let mut pos = 1 + fn_count * 5;
+        let mut ops = vec![];
+        loop {
+            let op_pos = pos;
+            match decode_op(&self.0, &mut pos)? {
+                Some(op) => ops.push((op_pos, op)),
+                None => break,
+            }
+        }
+        Ok(ops)
+    }
 }
 
 impl Debug for Script {
@@ -1,5 +1,12 @@
-use crate::{account::AccountId, asset::Asset};
-use std::convert::TryFrom;
+use super::{EvalErr, EvalErrKind};
+use crate::{
+    account::{AccountId, AccountIdBytes},
+    asset::Asset,
+};
+use std::{
+    convert::{TryFrom, TryInto},
+    mem,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
@@ -127,3 +134,113 @@ impl Into<u8> for Arg {
         self as u8
     }
 }
+
+/// Decodes the operation at the byte position of the script, advancing the position past the
+/// operation. Returns `None` when the position is at the end of the script.
+pub fn decode_op(script: &[u8], pos: &mut usize) -> Result<Option<OpFrame>, EvalErr> {
+    let new_err = |pos: usize, err: EvalErrKind| EvalErr::new(pos as u32, err);
+    macro_rules! read_bytes {
+        ($len:expr) => {
+            match script.get(*pos..*pos + $len) {
+                Some(b) => {
+                    *pos += $len;
+                    b
+                }
+                None => {
+                    return Err(new_err(*pos, EvalErrKind::UnexpectedEOF));
+                }
+            }
+        };
+        () => {
+            match script.get(*pos) {
+                Some(b) => {
+                    *pos += 1;
+                    *b
+                }
+                None => {
+                    return Err(new_err(*pos, EvalErrKind::UnexpectedEOF));
+                }
+            }
+        };
+    }
+
+    if *pos == script.len() {
+        return Ok(None);
+    }
+    let byte = script[*pos];
+    *pos += 1;
+
+    match byte {
+        // Function definition
+        o if o == Operand::OpDefine as u8 => {
+            let arg_cnt = read_bytes!();
+            let mut args = Vec::with_capacity(usize::from(arg_cnt));
+            for _ in 0..arg_cnt {
+                let tag_byte = read_bytes!();
+                let arg = tag_byte
+                    .try_into()
+                    .map_err(|_| new_err(*pos, EvalErrKind::UnknownArgType))?;
+                args.push(arg);
+            }
+            Ok(Some(OpFrame::OpDefine(args)))
+        }
+        // Events
+        o if o == Operand::OpTransfer as u8 => Ok(Some(OpFrame::OpTransfer)),
+        o if o == Operand::OpDestroy as u8 => Ok(Some(OpFrame::OpDestroy)),
+        // Push value
+        o if o == Operand::PushFalse as u8 => Ok(Some(OpFrame::False)),
+        o if o == Operand::PushTrue as u8 => Ok(Some(OpFrame::True)),
+        o if o == Operand::PushAccountId as u8 => {
+            let slice = read_bytes!(mem::size_of::<u64>());
+            let id = AccountId::from_slice(slice).unwrap();
+            Ok(Some(OpFrame::AccountId(id)))
+        }
+        o if o == Operand::PushAsset as u8 => {
+            let slice = read_bytes!(mem::size_of::<i64>());
+            let amt = i64::from_be_bytes(slice.try_into().unwrap());
+            let amt = Asset::new(amt);
+            Ok(Some(OpFrame::Asset(amt)))
+        }
+        // Arithmetic
+        o if o == Operand::OpLoadAmt as u8 => Ok(Some(OpFrame::OpLoadAmt)),
+        o if o == Operand::OpLoadRemAmt as u8 => Ok(Some(OpFrame::OpLoadRemAmt)),
+        o if o == Operand::OpAdd as u8 => Ok(Some(OpFrame::OpAdd)),
+        o if o == Operand::OpSub as u8 => Ok(Some(OpFrame::OpSub)),
+        o if o == Operand::OpMul as u8 => Ok(Some(OpFrame::OpMul)),
+        o if o == Operand::OpDiv as u8 => Ok(Some(OpFrame::OpDiv)),
+        // Logic
+        o if o == Operand::OpNot as u8 => Ok(Some(OpFrame::OpNot)),
+        o if o == Operand::OpIf as u8 => Ok(Some(OpFrame::OpIf)),
+        o if o == Operand::OpElse as u8 => Ok(Some(OpFrame::OpElse)),
+        o if o == Operand::OpEndIf as u8 => Ok(Some(OpFrame::OpEndIf)),
+        o if o == Operand::OpReturn as u8 => Ok(Some(OpFrame::OpReturn)),
+        o if o == Operand::OpAbort as u8 => Ok(Some(OpFrame::OpAbort)),
+        // Crypto
+        o if o == Operand::OpCheckPerms as u8 => Ok(Some(OpFrame::OpCheckPerms)),
+        o if o == Operand::OpCheckPermsFastFail as u8 => Ok(Some(OpFrame::OpCheckPermsFastFail)),
+        o if o == Operand::OpCheckMultiPerms as u8 => {
+            let threshold = read_bytes!();
+            let acc_count = read_bytes!();
+            Ok(Some(OpFrame::OpCheckMultiPerms(threshold, acc_count)))
+        }
+        o if o == Operand::OpCheckMultiPermsFastFail as u8 => {
+            let threshold = read_bytes!();
+            let acc_count = read_bytes!();
+            Ok(Some(OpFrame::OpCheckMultiPermsFastFail(
+                threshold, acc_count,
+            )))
+        }
+        // Lock time
+        o if o == Operand::OpCheckTime as u8 => {
+            let slice = read_bytes!(mem::size_of::<u64>());
+            let time = u64::from_be_bytes(slice.try_into().unwrap());
+            Ok(Some(OpFrame::OpCheckTime(time)))
+        }
+        o if o == Operand::OpCheckTimeFastFail as u8 => {
+            let slice = read_bytes!(mem::size_of::<u64>());
+            let time = u64::from_be_bytes(slice.try_into().unwrap());
+            Ok(Some(OpFrame::OpCheckTimeFastFail(time)))
+        }
+        _ => Err(new_err(*pos, EvalErrKind::UnknownOp)),
+    }
+}
@@ -29,6 +29,24 @@ fn zigzag_decode(from: u64) -> i64 {
     ((from >> 1) ^ (-((from & 1) as i64)) as u64) as i64
 }
 
+/// Returns the number of bytes `push_var_i64` writes for the value.
+pub fn var_i64_size(value: i64) -> usize {
+    let mut num = zigzag_encode(value) >> 7;
+    let mut size = 1;
+    while num != 0 {
+        num >>= 7;
+        size += 1;
+    }
+    size
+}
+
+/// Serializes primitives in the wire format used by blocks, transactions, and the network
+/// protocol.
+///
+/// Fixed-width integers are big-endian (network byte order). Assets and `push_var_i64` values are
+/// zigzag encoded variable-length integers where each byte holds 7 bits of the value, least
+/// significant group first, with the high bit set on every byte except the last. Byte arrays are
+/// prefixed with their length as a big-endian u32.
 pub trait BufWrite {
     fn push_u16(&mut self, value: u16);
     fn push_u32(&mut self, value: u32);
@@ -108,6 +126,7 @@ impl BufWrite for Vec<u8> {
     }
 }
 
+/// Deserializes primitives written by `BufWrite` using the same byte layout.
 pub trait BufRead {
     fn take_u8(&mut self) -> Result<u8, Error>;
     fn take_u16(&mut self) -> Result<u16, Error>;
@@ -243,6 +262,62 @@ mod tests {
         assert_eq!(a.to_string(), b.to_string());
     }
 
+    #[test]
+    fn golden_byte_layout() {
+        let mut v = vec![];
+        v.push_u16(0x0102);
+        v.push_u32(0x0304_0506);
+        v.push_u64(0x0708_090A_0B0C_0D0E);
+        v.push_i64(-2);
+        v.push_asset("12.34567 TEST".parse().unwrap());
+        v.push_asset("-0.00001 TEST".parse().unwrap());
+        v.push_bytes(&[0xAA, 0xBB]);
+        assert_eq!(
+            v,
+            [
+                0x01, 0x02, // u16
+                0x03, 0x04, 0x05, 0x06, // u32
+                0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, // u64
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, // i64
+                0x8E, 0xDA, 0x96, 0x01, // asset (zigzag varint of 1234567)
+                0x01, // asset (zigzag varint of -1)
+                0x00, 0x00, 0x00, 0x02, 0xAA, 0xBB, // bytes
+            ]
+            .as_ref()
+        );
+
+        let mut c = Cursor::<&[u8]>::new(&v);
+        assert_eq!(c.take_u16().unwrap(), 0x0102);
+        assert_eq!(c.take_u32().unwrap(), 0x0304_0506);
+        assert_eq!(c.take_u64().unwrap(), 0x0708_090A_0B0C_0D0E);
+        assert_eq!(c.take_i64().unwrap(), -2);
+        assert_eq!(c.take_asset().unwrap().amount, 1_234_567);
+        assert_eq!(c.take_asset().unwrap().amount, -1);
+        assert_eq!(c.take_bytes().unwrap(), [0xAA, 0xBB]);
+        assert_eq!(c.position() as usize, v.len());
+    }
+
+    #[test]
+    fn var_i64_size_matches_serialization() {
+        for &num in &[
+            0,
+            1,
+            -1,
+            63,
+            -64,
+            64,
+            8191,
+            -8192,
+            8192,
+            i64::max_value(),
+            i64::min_value(),
+        ] {
+            let mut v = vec![];
+            v.push_var_i64(num);
+            assert_eq!(var_i64_size(num), v.len());
+        }
+    }
+
     #[test]
     fn zigzag() {
         fn cmp(decoded: i64, encoded: u64) {
@@ -0,0 +1,243 @@
+//! Deterministic fixtures shared by the unit tests of the crate. Keys are derived from fixed seeds
+//! while fixture blocks and transactions use fixed timestamps, so the same fixture always produces
+//! the same accounts, blocks, transactions and signatures.
+
+use crate::{
+    account::{Account, AccountId, Permissions},
+    asset::Asset,
+    blockchain::{index::WriteBatch, Block, BlockErr, Blockchain, GenesisBlockInfo, Receipt},
+    crypto::{KeyPair, PrivateKey, PublicKey},
+    serializer::BufWrite,
+    tx::{TransferTx, Tx, TxVariant, TxVariantV0},
+};
+use sodiumoxide::{crypto::sign, randombytes};
+use std::{
+    env, fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// Timestamp of the genesis block of fixture chains.
+pub const FIXTURE_GENESIS_TIME: u64 = 1_500_000_000;
+
+/// Seconds between the timestamps of consecutive fixture blocks.
+pub const FIXTURE_BLOCK_INTERVAL: u64 = 3;
+
+/// Expiry of fixture transactions, fixed far in the future so their txids are indexed when
+/// included in a block. The expiry is beyond the maximum expiry time so a `ReceiptPool` rejects
+/// fixture transactions, use `tx_header` with a current expiry for those.
+pub const FIXTURE_TX_EXPIRY: u64 = 4_000_000_000;
+
+// Seeds of the minter and owner wallet keys, kept apart from the seeds of fixture accounts
+const GENESIS_KEY_SEED: u64 = 1 << 63;
+
+/// Temporary directory that is removed when dropped.
+pub struct TmpDir(PathBuf);
+
+impl TmpDir {
+    pub fn new() -> Self {
+        let mut path = env::temp_dir();
+        let mut num: [u8; 8] = [0; 8];
+        randombytes::randombytes_into(&mut num);
+        path.push(format!("godcoin_test_{}", u64::from_be_bytes(num)));
+        fs::create_dir(&path).unwrap_or_else(|_| panic!("Could not create temp dir {:?}", &path));
+        Self(path)
+    }
+}
+
+impl Deref for TmpDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TmpDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.0).expect("Failed to rm dir");
+    }
+}
+
+/// A chain in a temporary directory along with the keys of its genesis block.
+pub struct FixtureChain {
+    pub chain: Blockchain,
+    pub genesis_info: GenesisBlockInfo,
+    // Declared last so the chain is closed before the directory is removed
+    _tmp_dir: TmpDir,
+}
+
+impl FixtureChain {
+    /// Indexes the account directly without a transaction.
+    pub fn insert_account(&self, account: Account) {
+        let mut batch = WriteBatch::new(self.chain.indexer());
+        batch.insert_or_update_account(account);
+        batch.commit();
+    }
+
+    /// Produces the next block with the provided receipts, see `new_block`.
+    pub fn produce_block(&self, receipts: Vec<Receipt>) -> Result<(), BlockErr> {
+        self.chain.insert_block(self.new_block(receipts))
+    }
+
+    /// Creates the next block with the provided receipts without inserting it. The block is signed
+    /// by the minter and timestamped `FIXTURE_BLOCK_INTERVAL` seconds after the current head.
+    pub fn new_block(&self, receipts: Vec<Receipt>) -> Block {
+        let head = self.chain.get_chain_head();
+        let mut block = match head.as_ref() {
+            Block::V0(head) => {
+                let reward = self.chain.reward_schedule().reward_at(head.height + 1);
+                head.new_child_with_reward(receipts, reward)
+            }
+        };
+        match &mut block {
+            Block::V0(child) => {
+                child.header.timestamp = head.timestamp() + FIXTURE_BLOCK_INTERVAL;
+            }
+        }
+        block.sign(&self.genesis_info.minter_key);
+        block
+    }
+}
+
+/// Returns the key pair derived from the seed. The same seed always returns the same key pair.
+pub fn fixture_key(seed: u64) -> KeyPair {
+    let mut seed_bytes = [0; sign::SEEDBYTES];
+    seed_bytes[..8].copy_from_slice(&seed.to_be_bytes());
+    // Keeps the seed nonzero, matching the keys generated by `KeyPair::gen`
+    seed_bytes[sign::SEEDBYTES - 1] = 1;
+
+    let seed = sign::Seed(seed_bytes);
+    let (pk, sk) = sign::keypair_from_seed(&seed);
+    KeyPair(
+        PublicKey::from_slice(pk.as_ref()).unwrap(),
+        PrivateKey::from_slice(&seed.0, &sk.0).unwrap(),
+    )
+}
+
+/// Creates a chain with a genesis block followed by `n_blocks` empty blocks. The genesis block is
+/// timestamped at `FIXTURE_GENESIS_TIME`, and the owner wallet is account 0.
+pub fn fixture_chain(n_blocks: u64) -> FixtureChain {
+    let tmp_dir = TmpDir::new();
+    let chain = Blockchain::new(&tmp_dir.join("blklog"), &tmp_dir.join("index"));
+
+    let mut info = GenesisBlockInfo::new(fixture_key(GENESIS_KEY_SEED), 0);
+    info.wallet_keys = [
+        fixture_key(GENESIS_KEY_SEED + 1),
+        fixture_key(GENESIS_KEY_SEED + 2),
+        fixture_key(GENESIS_KEY_SEED + 3),
+        fixture_key(GENESIS_KEY_SEED + 4),
+    ];
+    let genesis_info = chain.create_genesis_block_at(info, FIXTURE_GENESIS_TIME);
+
+    let fixture = FixtureChain {
+        chain,
+        genesis_info,
+        _tmp_dir: tmp_dir,
+    };
+    for _ in 0..n_blocks {
+        fixture.produce_block(vec![]).unwrap();
+    }
+    fixture
+}
+
+/// Creates an account with the default script and a single key derived from the account ID. The
+/// account is not indexed, see `FixtureChain::insert_account`.
+pub fn fixture_account(id: AccountId, balance: &str) -> (Account, KeyPair) {
+    let key = fixture_key(id);
+    let mut account = Account::create_default(
+        id,
+        Permissions {
+            threshold: 1,
+            keys: vec![key.0.clone()],
+        },
+    );
+    account.balance = get_asset(balance);
+    (account, key)
+}
+
+/// Creates a transaction header without signatures. Transactions from the same account need
+/// distinct nonces when they are otherwise identical or replace each other in a `ReceiptPool`.
+pub fn tx_header(nonce: u32, expiry: u64, fee: &str) -> Tx {
+    Tx {
+        nonce,
+        expiry,
+        fee: get_asset(fee),
+        signature_pairs: Vec::new(),
+    }
+}
+
+/// Creates a transfer calling the transfer function of the default account script, see
+/// `fixture_account`, signed by the provided key. The transfer expires at `FIXTURE_TX_EXPIRY`.
+pub fn signed_transfer(
+    from: &(Account, KeyPair),
+    to: AccountId,
+    amount: &str,
+    fee: &str,
+    nonce: u32,
+) -> TxVariant {
+    let amount = get_asset(amount);
+    let mut tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+        base: tx_header(nonce, FIXTURE_TX_EXPIRY, fee),
+        from: from.0.id,
+        call_fn: 0,
+        args: {
+            let mut args = vec![];
+            args.push_u64(to);
+            args.push_asset(amount);
+            args
+        },
+        amount,
+        memo: vec![],
+    }));
+    tx.append_sign(&from.1);
+    tx
+}
+
+pub fn get_asset(s: &str) -> Asset {
+    s.parse().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::skip_flags::SKIP_NONE;
+
+    #[test]
+    fn fixture_chains_are_identical() {
+        let a = fixture_chain(3);
+        let b = fixture_chain(3);
+        assert_eq!(a.chain.get_chain_height(), 3);
+        for height in 0..=3 {
+            assert_eq!(a.chain.get_block(height), b.chain.get_block(height));
+        }
+        assert_eq!(
+            fixture_account(1, "1.00000 TEST").0,
+            fixture_account(1, "1.00000 TEST").0
+        );
+        assert_ne!(fixture_key(1).0, fixture_key(2).0);
+
+        let from = fixture_account(1, "1.00000 TEST");
+        assert_eq!(
+            signed_transfer(&from, 2, "1.00000 TEST", "0.00100 TEST", 0),
+            signed_transfer(&from, 2, "1.00000 TEST", "0.00100 TEST", 0)
+        );
+    }
+
+    #[test]
+    fn signed_transfer_executes() {
+        let fixture = fixture_chain(0);
+        let from = fixture_account(1, "10.00000 TEST");
+        let (to, _) = fixture_account(2, "0.00000 TEST");
+        fixture.insert_account(from.0.clone());
+        fixture.insert_account(to.clone());
+
+        let tx = signed_transfer(&from, to.id, "2.00000 TEST", "1.00000 TEST", 0);
+        let data = tx.clone().precompute();
+        let log = fixture.chain.execute_tx(&data, &[], SKIP_NONE).unwrap();
+        fixture.produce_block(vec![Receipt { tx, log }]).unwrap();
+
+        let to = fixture.chain.get_account(to.id, &[]).unwrap();
+        assert_eq!(to.balance, get_asset("2.00000 TEST"));
+    }
+}
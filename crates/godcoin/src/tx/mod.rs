@@ -7,8 +7,8 @@ use std::{
 use crate::{
     account::{Account, AccountId, Permissions},
     asset::Asset,
-    constants::CHAIN_ID,
-    crypto::{Digest, DoubleSha256, KeyPair, PublicKey, SigPair},
+    constants::{CHAIN_ID, TX_MAX_EXPIRY_TIME},
+    crypto::{Digest, DoubleSha256, PublicKey, SigPair, Signer},
     script::Script,
     serializer::*,
 };
@@ -93,6 +93,13 @@ impl<'a> Into<Cow<'a, TxPrecompData<'a>>> for &'a TxPrecompData<'a> {
     }
 }
 
+/// Returns whether the expiry time has passed at the specified epoch time in seconds. Every expiry
+/// check goes through this predicate, see `TxVariant::is_expired`.
+#[inline]
+pub fn expiry_passed(expiry: u64, now: u64) -> bool {
+    expiry <= now
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TxVariant {
     V0(TxVariantV0),
@@ -111,6 +118,20 @@ impl TxVariant {
         }
     }
 
+    /// Returns whether the transaction is expired at the specified epoch time in seconds. A
+    /// transaction is valid up to but excluding its expiry time.
+    #[inline]
+    pub fn is_expired(&self, now: u64) -> bool {
+        expiry_passed(self.expiry(), now)
+    }
+
+    /// Returns whether the expiry time is more than `TX_MAX_EXPIRY_TIME` seconds after the
+    /// specified epoch time in seconds.
+    #[inline]
+    pub fn exceeds_max_expiry(&self, now: u64) -> bool {
+        self.expiry().saturating_sub(now) > TX_MAX_EXPIRY_TIME
+    }
+
     #[inline]
     pub fn sigs(&self) -> &[SigPair] {
         match self {
@@ -139,18 +160,41 @@ impl TxVariant {
         TxId(digest)
     }
 
+    /// Signs the txid using the provided signer, which may be a `KeyPair` or an external signer.
     #[inline]
-    pub fn sign(&self, key_pair: &KeyPair) -> SigPair {
+    pub fn sign(&self, signer: &dyn Signer) -> SigPair {
         let hash = self.calc_txid();
-        key_pair.sign(&hash.as_ref())
+        signer.sign(&hash.as_ref())
     }
 
     #[inline]
-    pub fn append_sign(&mut self, key_pair: &KeyPair) {
-        let pair = self.sign(key_pair);
+    pub fn append_sign(&mut self, signer: &dyn Signer) {
+        let pair = self.sign(signer);
         self.sigs_mut().push(pair);
     }
 
+    /// Verifies that the transaction is signed, and that every signature pair is from one of the
+    /// provided keys and signs the txid. Signatures from any other key, more than one signature
+    /// from the same key, or signatures that fail verification cause the check to fail.
+    /// Permission thresholds are not checked, see `Permissions::verify` for that.
+    pub fn verify_sigs(&self, keys: &[PublicKey]) -> bool {
+        if self.sigs().is_empty() || self.has_duplicate_sig_keys() {
+            return false;
+        }
+        let txid = self.calc_txid();
+        self.sigs()
+            .iter()
+            .all(|pair| keys.contains(&pair.pub_key) && pair.verify(txid.as_ref()))
+    }
+
+    /// Returns whether more than one signature pair is from the same key.
+    pub fn has_duplicate_sig_keys(&self) -> bool {
+        let sigs = self.sigs();
+        sigs.iter()
+            .enumerate()
+            .any(|(i, pair)| sigs[..i].iter().any(|prev| prev.pub_key == pair.pub_key))
+    }
+
     pub fn serialize(&self, buf: &mut Vec<u8>) {
         self.serialize_without_sigs(buf);
         match self {
@@ -339,6 +383,9 @@ impl DeserializeTx<OwnerTx> for OwnerTx {
     }
 }
 
+/// Mints new tokens into the `to` account, which may be any existing account that has not been
+/// destroyed. Minting is authorized by calling function 0 of the owner wallet's script regardless
+/// of the recipient, the genesis script requires the owner wallet's permissions to be met.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MintTx {
     pub base: Tx,
@@ -507,7 +554,7 @@ tx_deref!(TransferTx);
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::crypto;
+    use crate::crypto::{self, KeyPair};
 
     macro_rules! cmp_base_tx {
         ($id:ident, $expiry:expr, $fee:expr) => {
@@ -545,6 +592,101 @@ mod tests {
         assert_eq!(owner_tx.sigs()[1], dec.sigs()[1]);
     }
 
+    #[test]
+    fn verify_tx_sigs() {
+        let key_a = crypto::KeyPair::gen();
+        let key_b = crypto::KeyPair::gen();
+        let key_c = crypto::KeyPair::gen();
+        let keys = [key_a.0.clone(), key_b.0.clone()];
+
+        let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+            base: Tx {
+                nonce: 123,
+                expiry: 1230,
+                fee: get_asset("0.00000 TEST"),
+                signature_pairs: vec![],
+            },
+            to: 0,
+            amount: get_asset("10.00000 TEST"),
+            attachment: vec![],
+            attachment_name: "".to_owned(),
+        }));
+        tx.append_sign(&key_a);
+        tx.append_sign(&key_b);
+        assert!(tx.verify_sigs(&keys));
+
+        // Extra signature from a key that isn't provided
+        let mut extra_tx = tx.clone();
+        extra_tx.append_sign(&key_c);
+        assert!(!extra_tx.verify_sigs(&keys));
+
+        // Signature that doesn't match the txid
+        let mut invalid_tx = tx.clone();
+        invalid_tx.sigs_mut()[1] = key_b.sign(b"not the txid");
+        assert!(!invalid_tx.verify_sigs(&keys));
+
+        // Signatures no longer match once the tx is modified
+        let mut modified_tx = tx.clone();
+        match &mut modified_tx {
+            TxVariant::V0(TxVariantV0::MintTx(tx)) => tx.to = 1,
+            _ => unreachable!(),
+        }
+        assert!(!modified_tx.verify_sigs(&keys));
+
+        // Unsigned transactions are not verified
+        let mut unsigned_tx = tx.clone();
+        unsigned_tx.sigs_mut().clear();
+        assert!(!unsigned_tx.verify_sigs(&keys));
+
+        // More than one signature from the same key
+        let mut dup_tx = tx;
+        dup_tx.append_sign(&key_a);
+        assert!(dup_tx.has_duplicate_sig_keys());
+        assert!(!dup_tx.verify_sigs(&keys));
+    }
+
+    #[test]
+    fn sign_with_external_signer() {
+        struct MockSigner {
+            key_pair: KeyPair,
+            signed: std::cell::RefCell<Vec<Vec<u8>>>,
+        }
+
+        impl Signer for MockSigner {
+            fn public_key(&self) -> &PublicKey {
+                &self.key_pair.0
+            }
+
+            fn sign(&self, msg: &[u8]) -> SigPair {
+                self.signed.borrow_mut().push(msg.to_vec());
+                self.key_pair.sign(msg)
+            }
+        }
+
+        let signer = MockSigner {
+            key_pair: KeyPair::gen(),
+            signed: Default::default(),
+        };
+        let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+            base: Tx {
+                nonce: 123,
+                expiry: 1230,
+                fee: get_asset("0.00000 TEST"),
+                signature_pairs: vec![],
+            },
+            to: 0,
+            amount: get_asset("10.00000 TEST"),
+            attachment: vec![],
+            attachment_name: "".to_owned(),
+        }));
+        tx.append_sign(&signer);
+
+        let txid = tx.calc_txid();
+        assert_eq!(*signer.signed.borrow(), vec![txid.as_ref().to_vec()]);
+        assert_eq!(&tx.sigs()[0].pub_key, signer.public_key());
+        assert!(tx.verify_sigs(&[signer.key_pair.0.clone()]));
+    }
+
     #[test]
     fn serialize_owner() {
         let minter = crypto::KeyPair::gen();
@@ -731,6 +873,33 @@ mod tests {
         assert_ne!(tx_a, tx_b);
     }
 
+    #[test]
+    fn tx_expiry_boundary() {
+        let mut tx = TxVariant::V0(TxVariantV0::OwnerTx(OwnerTx {
+            base: Tx {
+                nonce: 0,
+                expiry: 1000,
+                fee: Asset::default(),
+                signature_pairs: vec![],
+            },
+            minter: KeyPair::gen().0,
+            wallet: 0,
+        }));
+        assert!(!tx.is_expired(0));
+        assert!(!tx.is_expired(999));
+        assert!(tx.is_expired(1000));
+        assert!(tx.is_expired(1001));
+
+        assert!(!tx.exceeds_max_expiry(0));
+
+        match &mut tx {
+            TxVariant::V0(tx) => tx.expiry = TX_MAX_EXPIRY_TIME + 1000,
+        }
+        assert!(tx.exceeds_max_expiry(999));
+        assert!(!tx.exceeds_max_expiry(1000));
+        assert!(!tx.exceeds_max_expiry(1001));
+    }
+
     #[test]
     fn precomp_data() {
         let tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
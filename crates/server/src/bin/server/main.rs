@@ -1,5 +1,10 @@
 use clap::{App, Arg};
-use godcoin::{blockchain::ReindexOpts, prelude::*};
+use godcoin::{
+    blockchain::{spend_limit::MAX_SPEND_LIMIT_WINDOW, ReindexOpts, RewardSchedule, SpendLimit},
+    crypto::Digest,
+    prelude::*,
+};
+use godcoin_server::prelude::*;
 use hyper::{
     service::{make_service_fn, service_fn},
     Body, Response, Server, StatusCode,
@@ -8,7 +13,9 @@ use prometheus::{Encoder, TextEncoder};
 use serde::Deserialize;
 use std::{
     env, fs,
+    net::SocketAddr,
     path::{Path, PathBuf},
+    time::Duration,
 };
 use tokio::runtime::Builder;
 use tracing::{error, info};
@@ -16,10 +23,50 @@ use tracing_subscriber::filter::{EnvFilter, LevelFilter};
 
 #[derive(Debug, Deserialize)]
 struct Config {
-    minter_key: String,
+    minter_key: Option<String>,
+    remote_signer: Option<RemoteSignerConfig>,
     enable_stale_production: bool,
     bind_address: Option<String>,
     metrics_bind_address: Option<String>,
+    max_connections: Option<usize>,
+    allowed_origins: Option<Vec<String>>,
+    max_get_blocks: Option<usize>,
+    checkpoints: Option<Vec<Checkpoint>>,
+    slow_request_threshold_ms: Option<u64>,
+    clock_skew_tolerance_secs: Option<u64>,
+    reward_schedule: Option<RewardScheduleConfig>,
+    spend_limit: Option<SpendLimitConfig>,
+    warmup_blocks: Option<u64>,
+    reuse_address: Option<bool>,
+    listen_backlog: Option<i32>,
+    tcp_nodelay: Option<bool>,
+    #[cfg(unix)]
+    unix_socket_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteSignerConfig {
+    address: String,
+    public_key: String,
+    timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RewardScheduleConfig {
+    amount: String,
+    halving_interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpendLimitConfig {
+    amount: String,
+    window: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Checkpoint {
+    height: u64,
+    hash: String,
 }
 
 fn main() {
@@ -66,6 +113,11 @@ fn main() {
                     .long("reindex-trim-corrupt")
                     .help("Trims any corruption detected in the block log during reindexing"),
             )
+            .arg(
+                Arg::with_name("export_genesis")
+                    .long("export-genesis")
+                    .help("Prints the public genesis configuration of the block log and exits"),
+            )
             .get_matches();
 
         let home = PathBuf::from(args.value_of("home").expect("Failed to obtain home path"));
@@ -82,12 +134,20 @@ fn main() {
             (blocklog_loc, index_loc)
         };
 
-        let config_file = Path::join(&home, "config.toml");
-        info!("Opening configuration file at {:?}", config_file);
-        let config_file = fs::read(config_file).expect("Failed to open config");
-        let config: Config = toml::from_str(&String::from_utf8(config_file).unwrap()).unwrap();
+        if args.is_present("export_genesis") {
+            let code = if export_genesis(&blocklog_loc, &index_loc) {
+                0
+            } else {
+                1
+            };
+            std::process::exit(code);
+        }
 
-        if let Some(bind_address) = config.metrics_bind_address {
+        let config_loc = Path::join(&home, "config.toml");
+        info!("Opening configuration file at {:?}", config_loc);
+        let config = read_config(&config_loc).unwrap();
+
+        if let Some(bind_address) = &config.metrics_bind_address {
             let service = make_service_fn(|_| async {
                 Ok::<_, hyper::Error>(service_fn(move |_req| async {
                     let encoder = TextEncoder::new();
@@ -116,8 +176,17 @@ fn main() {
             info!("Metrics monitoring is disabled");
         }
 
-        let minter_key =
-            PrivateKey::from_wif(&config.minter_key).expect("Provided minter key is invalid");
+        let minter_signer = parse_minter_signer(&config).unwrap();
+        let is_built_in_signer = match minter_signer {
+            MinterSigner::BuiltIn(_) => true,
+            MinterSigner::Remote(ref signer) => {
+                info!(
+                    "Signing blocks using the remote signer at {}",
+                    signer.addr()
+                );
+                false
+            }
+        };
         let bind_addr = config
             .bind_address
             .unwrap_or_else(|| "127.0.0.1:7777".to_string());
@@ -137,15 +206,82 @@ fn main() {
             None
         };
 
+        let checkpoints = config
+            .checkpoints
+            .unwrap_or_default()
+            .iter()
+            .map(parse_checkpoint)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let reward_schedule = match &config.reward_schedule {
+            Some(schedule) => parse_reward_schedule(schedule).unwrap(),
+            None => RewardSchedule::default(),
+        };
+
+        let spend_limit = config
+            .spend_limit
+            .as_ref()
+            .map(parse_spend_limit)
+            .transpose()
+            .unwrap();
+
+        let max_get_blocks = config
+            .max_get_blocks
+            .unwrap_or(godcoin_server::DEFAULT_MAX_GET_BLOCKS);
+        assert!(
+            max_get_blocks <= rpc::MAX_GET_BLOCKS,
+            "max_get_blocks must not exceed {}",
+            rpc::MAX_GET_BLOCKS
+        );
+
+        let socket_opts = {
+            let default = godcoin_server::SocketOpts::default();
+            godcoin_server::SocketOpts {
+                reuse_address: config.reuse_address.unwrap_or(default.reuse_address),
+                backlog: config.listen_backlog.unwrap_or(default.backlog),
+                nodelay: config.tcp_nodelay.unwrap_or(default.nodelay),
+            }
+        };
+
         let enable_stale_production = config.enable_stale_production;
-        godcoin_server::start(godcoin_server::ServerOpts {
+        let data = godcoin_server::start(godcoin_server::ServerOpts {
             blocklog_loc,
             index_loc,
-            minter_key,
+            minter_signer,
             bind_addr,
             reindex,
             enable_stale_production,
+            max_connections: config.max_connections,
+            allowed_origins: config.allowed_origins,
+            max_get_blocks,
+            checkpoints,
+            clock_skew_tolerance: config.clock_skew_tolerance_secs.unwrap_or(0),
+            reward_schedule,
+            spend_limit,
+            warmup_blocks: config
+                .warmup_blocks
+                .unwrap_or(godcoin_server::DEFAULT_WARMUP_BLOCKS),
+            slow_request_threshold: config.slow_request_threshold_ms.map(Duration::from_millis),
+            socket_opts,
+            #[cfg(unix)]
+            unix_socket_path: config.unix_socket_path,
         });
+
+        #[cfg(unix)]
+        {
+            if is_built_in_signer {
+                godcoin_server::reload_minter_key_on_sighup(data.minter.clone(), move || {
+                    let config = read_config(&config_loc)?;
+                    match parse_minter_signer(&config)? {
+                        MinterSigner::BuiltIn(key) => Ok(key),
+                        MinterSigner::Remote(_) => {
+                            Err("Cannot switch to a remote signer while running".to_string())
+                        }
+                    }
+                });
+            }
+        }
     });
 
     rt.block_on(async {
@@ -154,6 +290,108 @@ fn main() {
     });
 }
 
+fn export_genesis(blocklog_loc: &Path, index_loc: &Path) -> bool {
+    let blockchain = Blockchain::new(blocklog_loc, index_loc);
+    let details = match blockchain.get_genesis_details() {
+        Some(details) => details,
+        None => {
+            error!("Block log is empty, no genesis block to export");
+            return false;
+        }
+    };
+
+    println!(
+        "Owner address: {} (raw id: {})",
+        details.owner_id.to_wif(),
+        details.owner_id
+    );
+    println!("Minter key: {}", details.minter_key.to_wif());
+    println!(
+        "Owner permissions threshold: {}",
+        details.owner_permissions.threshold
+    );
+    for (index, key) in details.owner_permissions.keys.iter().enumerate() {
+        println!("Owner wallet key {}: {}", index + 1, key.to_wif());
+    }
+    true
+}
+
+fn read_config(path: &Path) -> Result<Config, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to open config: {}", e))?;
+    let s = String::from_utf8(bytes).map_err(|e| format!("Config is not valid UTF-8: {}", e))?;
+    toml::from_str(&s).map_err(|e| format!("Failed to parse config: {}", e))
+}
+
+fn parse_minter_signer(config: &Config) -> Result<MinterSigner, String> {
+    match (&config.minter_key, &config.remote_signer) {
+        (Some(minter_key), None) => {
+            let key = PrivateKey::from_wif(minter_key)
+                .map_err(|e| format!("Provided minter key is invalid: {}", e))?;
+            Ok(MinterSigner::BuiltIn(key))
+        }
+        (None, Some(remote)) => {
+            let addr = remote
+                .address
+                .parse::<SocketAddr>()
+                .map_err(|e| format!("Invalid remote signer address: {}", e))?;
+            let public_key = PublicKey::from_wif(&remote.public_key)
+                .map_err(|e| format!("Provided remote signer public key is invalid: {}", e))?;
+            let mut signer = RemoteSigner::new(addr, public_key);
+            if let Some(secs) = remote.timeout_secs {
+                signer = signer.with_timeout(Duration::from_secs(secs));
+            }
+            Ok(MinterSigner::Remote(signer))
+        }
+        (Some(_), Some(_)) => {
+            Err("Only one of minter_key or remote_signer may be configured".to_string())
+        }
+        (None, None) => Err("Either minter_key or remote_signer must be configured".to_string()),
+    }
+}
+
+fn parse_checkpoint(checkpoint: &Checkpoint) -> Result<(u64, Digest), String> {
+    let digest = Digest::from_hex(&checkpoint.hash)
+        .ok_or_else(|| format!("Invalid checkpoint hash: {}", checkpoint.hash))?;
+    Ok((checkpoint.height, digest))
+}
+
+fn parse_reward_schedule(config: &RewardScheduleConfig) -> Result<RewardSchedule, String> {
+    let amount = config
+        .amount
+        .parse::<Asset>()
+        .map_err(|e| format!("Invalid reward amount: {}", e))?;
+    if amount.amount < 0 {
+        return Err("Reward amount must not be negative".to_string());
+    }
+    match config.halving_interval {
+        None => Ok(RewardSchedule::Fixed(amount)),
+        Some(0) => Err("Reward halving interval must be greater than 0".to_string()),
+        Some(interval) => Ok(RewardSchedule::Halving {
+            initial: amount,
+            interval,
+        }),
+    }
+}
+
+fn parse_spend_limit(config: &SpendLimitConfig) -> Result<SpendLimit, String> {
+    let max_amount = config
+        .amount
+        .parse::<Asset>()
+        .map_err(|e| format!("Invalid spend limit amount: {}", e))?;
+    if max_amount.amount < 0 {
+        return Err("Spend limit amount must not be negative".to_string());
+    } else if config.window > MAX_SPEND_LIMIT_WINDOW {
+        return Err(format!(
+            "Spend limit window must not exceed {} blocks",
+            MAX_SPEND_LIMIT_WINDOW
+        ));
+    }
+    Ok(SpendLimit {
+        max_amount,
+        window: config.window,
+    })
+}
+
 fn install_panic_hook() {
     let default_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
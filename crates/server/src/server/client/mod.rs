@@ -1,23 +1,31 @@
 mod block_range;
 
-use crate::{metrics::*, ServerData};
+use crate::{metrics::*, ServerData, MAX_CONCURRENT_BLOCK_RANGES, MAX_LIST_ACCOUNTS};
 use block_range::AsyncBlockRange;
 use futures::{
     channel::mpsc::{self, Sender},
     prelude::*,
 };
-use godcoin::{get_epoch_time, net::*, prelude::*};
+use godcoin::{constants::MINTER_AUTH_MAX_EXPIRY_TIME, get_epoch_time, net::*, prelude::*};
+use prometheus::HistogramTimer;
 use std::{
     io::Cursor,
     net::SocketAddr,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
     time::Duration,
 };
-use tokio::{net::TcpStream, time};
-use tokio_tungstenite::tungstenite::{protocol, Message as WsMessage};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    time,
+};
+use tokio_tungstenite::tungstenite::{
+    handshake::server::{ErrorResponse, Request, Response},
+    http::{header, StatusCode},
+    protocol, Message as WsMessage,
+};
 use tracing::{debug, error, info, warn};
 use tracing_futures::Instrument;
 
@@ -26,6 +34,7 @@ pub struct WsClient {
     addr: SocketAddr,
     tx: Sender<WsMessage>,
     needs_pong: Arc<AtomicBool>,
+    active_block_ranges: Arc<AtomicUsize>,
 }
 
 impl WsClient {
@@ -36,6 +45,7 @@ impl WsClient {
             addr,
             tx,
             needs_pong: Arc::new(AtomicBool::new(false)),
+            active_block_ranges: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -63,9 +73,54 @@ impl WsClient {
     pub fn sender(&self) -> Sender<WsMessage> {
         self.tx.clone()
     }
+
+    /// Reserves a slot for streaming a block range, returns `None` when the client already has
+    /// the maximum number of block ranges in progress. The slot is released when the permit is
+    /// dropped.
+    fn try_acquire_block_range(&self) -> Option<BlockRangePermit> {
+        let active = &self.active_block_ranges;
+        if active.fetch_add(1, Ordering::AcqRel) >= MAX_CONCURRENT_BLOCK_RANGES {
+            active.fetch_sub(1, Ordering::AcqRel);
+            return None;
+        }
+        Some(BlockRangePermit(Arc::clone(active)))
+    }
+}
+
+struct BlockRangePermit(Arc<AtomicUsize>);
+
+impl Drop for BlockRangePermit {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
 }
 
-pub fn handle_new_client(stream: TcpStream, peer_addr: SocketAddr, data: Arc<ServerData>) {
+/// Rejects the WebSocket handshake with a 403 Forbidden response when the request's origin is not
+/// in the allowed list. Requests without an `Origin` header are not from browsers and are accepted.
+fn check_origin(
+    req: &Request,
+    res: Response,
+    allowed_origins: &[String],
+) -> Result<Response, ErrorResponse> {
+    let origin = match req.headers().get(header::ORIGIN) {
+        Some(origin) => origin,
+        None => return Ok(res),
+    };
+    match origin.to_str() {
+        Ok(origin) if allowed_origins.iter().any(|o| o == origin) => Ok(res),
+        _ => {
+            warn!("Rejecting connection from disallowed origin: {:?}", origin);
+            let mut err = ErrorResponse::new(Some("Origin not allowed".to_owned()));
+            *err.status_mut() = StatusCode::FORBIDDEN;
+            Err(err)
+        }
+    }
+}
+
+pub fn handle_new_client<S>(stream: S, peer_addr: SocketAddr, data: Arc<ServerData>)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
     let config = Some(protocol::WebSocketConfig {
         // # of protocol Message's
         max_send_queue: Some(16),
@@ -76,13 +131,22 @@ pub fn handle_new_client(stream: TcpStream, peer_addr: SocketAddr, data: Arc<Ser
     });
 
     let client_fut = async move {
-        let ws_stream = match tokio_tungstenite::accept_async_with_config(stream, config).await {
-            Ok(ws) => ws,
-            Err(e) => {
-                error!("WebSocket accept error: {:?}", e);
-                return;
-            }
+        let allowed_origins = data.allowed_origins.clone();
+        let origin_callback = move |req: &Request, res: Response| match allowed_origins {
+            Some(origins) => check_origin(req, res, &origins),
+            None => Ok(res),
         };
+        let ws_stream =
+            match tokio_tungstenite::accept_hdr_async_with_config(stream, origin_callback, config)
+                .await
+            {
+                Ok(ws) => ws,
+                Err(e) => {
+                    error!("WebSocket accept error: {:?}", e);
+                    data.active_connections.fetch_sub(1, Ordering::AcqRel);
+                    return;
+                }
+            };
         info!("Connection opened");
 
         let (tx, rx) = mpsc::channel(32);
@@ -143,7 +207,12 @@ pub fn handle_new_client(stream: TcpStream, peer_addr: SocketAddr, data: Arc<Ser
                 let mut buf = Vec::with_capacity(16);
                 msg.serialize(&mut buf);
 
-                if tx.clone().send(WsMessage::Binary(buf)).await.is_err() {
+                let mut tx = tx.clone();
+                if tx.send(WsMessage::Binary(buf)).await.is_err() {
+                    break;
+                }
+                let ping = WsMessage::Ping(nonce.to_be_bytes().to_vec());
+                if tx.send(ping).await.is_err() {
                     break;
                 }
             }
@@ -158,6 +227,8 @@ pub fn handle_new_client(stream: TcpStream, peer_addr: SocketAddr, data: Arc<Ser
         info!("Connection closed");
         // Remove block subscriptions if there are any
         data.sub_pool.remove(peer_addr);
+        data.tx_sub_pool.remove(peer_addr);
+        data.active_connections.fetch_sub(1, Ordering::AcqRel);
     };
 
     let span = tracing::info_span!("client_connection", peer_addr = ?peer_addr);
@@ -179,6 +250,7 @@ pub fn process_ws_msg(
                 Ok(msg) => {
                     let id = msg.id;
                     if cur.position() != buf.len() as u64 {
+                        NET_DESERIALIZE_ERRORS.inc();
                         Msg {
                             id,
                             body: Body::Error(ErrorKind::BytesRemaining),
@@ -191,6 +263,7 @@ pub fn process_ws_msg(
                     }
                 }
                 Err(e) => {
+                    NET_DESERIALIZE_ERRORS.inc();
                     error!("Error occurred during deserialization: {:?}", e);
                     Msg {
                         id: u32::max_value(),
@@ -207,6 +280,16 @@ pub fn process_ws_msg(
             code: protocol::frame::coding::CloseCode::Unsupported,
             reason: "text is not supported".into(),
         }))),
+        // Protocol level pings are used by proxies to keep connections alive, they coexist with
+        // the application level heartbeat. The websocket library already replies with a pong.
+        WsMessage::Ping(_) => {
+            state.set_needs_pong(false);
+            None
+        }
+        WsMessage::Pong(_) => {
+            state.set_needs_pong(false);
+            None
+        }
         _ => None,
     }
 }
@@ -241,68 +324,87 @@ fn handle_rpc_request(
     id: u32,
     req: rpc::Request,
 ) -> Option<Body> {
+    // Requests carrying minter authentication are rejected here, the handlers below assume the
+    // request is authorized
+    if req.minter_auth().is_some() && !is_minter_authorized(data, &req) {
+        warn!("Rejecting request with invalid minter authentication");
+        return Some(Body::Error(ErrorKind::Unauthorized));
+    }
+
     Some(match req {
         rpc::Request::Broadcast(tx) => {
             REQ_BROADCAST_TOTAL.inc();
             let req_timer = REQ_BROADCAST_DUR.start_timer();
             let res = data.minter.push_tx(tx);
-            req_timer.stop_and_record();
+            record_req_duration(data, "broadcast", req_timer);
             match res {
                 Ok(_) => Body::Response(rpc::Response::Broadcast),
                 Err(e) => {
                     REQ_BROADCAST_FAIL.inc();
-                    Body::Error(ErrorKind::TxValidation(e))
+                    Body::Error(e)
                 }
             }
         }
         rpc::Request::SetBlockFilter(filter) => {
             let req_timer = REQ_SET_BLOCK_FILTER_DUR.start_timer();
-            if filter.len() > 16 {
-                return Some(Body::Error(ErrorKind::InvalidRequest));
+            // Bloom filters are bounded by their size limits when deserialized
+            if let BlockFilter::Accounts(accounts) = &filter {
+                if accounts.len() > 16 {
+                    return Some(Body::Error(ErrorKind::InvalidRequest));
+                }
             }
             state.filter = Some(filter);
-            req_timer.stop_and_record();
+            record_req_duration(data, "set_block_filter", req_timer);
             Body::Response(rpc::Response::SetBlockFilter)
         }
         rpc::Request::ClearBlockFilter => {
             let req_timer = REQ_CLEAR_BLOCK_FILTER_DUR.start_timer();
             state.filter = None;
-            req_timer.stop_and_record();
+            record_req_duration(data, "clear_block_filter", req_timer);
             Body::Response(rpc::Response::ClearBlockFilter)
         }
         rpc::Request::Subscribe => {
             let req_timer = REQ_SUBSCRIBE_DUR.start_timer();
             data.sub_pool.insert(state.addr(), state.sender());
-            req_timer.stop_and_record();
+            record_req_duration(data, "subscribe", req_timer);
             Body::Response(rpc::Response::Subscribe)
         }
         rpc::Request::Unsubscribe => {
             let req_timer = REQ_UNSUBSCRIBE_DUR.start_timer();
             data.sub_pool.remove(state.addr());
-            req_timer.stop_and_record();
+            record_req_duration(data, "unsubscribe", req_timer);
             Body::Response(rpc::Response::Unsubscribe)
         }
+        rpc::Request::SubscribeReceipts => {
+            let req_timer = REQ_SUBSCRIBE_RECEIPTS_DUR.start_timer();
+            let filter = match state.filter() {
+                Some(filter) => filter.clone(),
+                None => return Some(Body::Error(ErrorKind::InvalidRequest)),
+            };
+            data.sub_pool
+                .insert_with_receipt_filter(state.addr(), state.sender(), filter);
+            record_req_duration(data, "subscribe_receipts", req_timer);
+            Body::Response(rpc::Response::SubscribeReceipts)
+        }
+        rpc::Request::SubscribeTxs => {
+            let req_timer = REQ_SUBSCRIBE_TXS_DUR.start_timer();
+            data.tx_sub_pool.insert(state.addr(), state.sender());
+            record_req_duration(data, "subscribe_txs", req_timer);
+            Body::Response(rpc::Response::SubscribeTxs)
+        }
         rpc::Request::GetProperties => {
             let req_timer = REQ_GET_PROPERTIES_DUR.start_timer();
             let props = data.chain.get_properties();
-            req_timer.stop_and_record();
+            record_req_duration(data, "get_properties", req_timer);
             Body::Response(rpc::Response::GetProperties(props))
         }
         rpc::Request::GetBlock(height) => {
             let req_timer = REQ_GET_BLOCK_DUR.start_timer();
-            let res = match &state.filter {
-                Some(filter) => match data.chain.get_filtered_block(height, filter) {
-                    Some(block) => Body::Response(rpc::Response::GetBlock(block)),
-                    None => Body::Error(ErrorKind::InvalidHeight),
-                },
-                None => match data.chain.get_block(height) {
-                    Some(block) => {
-                        Body::Response(rpc::Response::GetBlock(FilteredBlock::Block(block)))
-                    }
-                    None => Body::Error(ErrorKind::InvalidHeight),
-                },
+            let res = match get_block(data, state, height) {
+                Some(block) => Body::Response(rpc::Response::GetBlock(block)),
+                None => Body::Error(ErrorKind::InvalidHeight),
             };
-            req_timer.stop_and_record();
+            record_req_duration(data, "get_block", req_timer);
             res
         }
         rpc::Request::GetFullBlock(height) => {
@@ -311,7 +413,7 @@ fn handle_rpc_request(
                 Some(block) => Body::Response(rpc::Response::GetFullBlock(block)),
                 None => Body::Error(ErrorKind::InvalidHeight),
             };
-            req_timer.stop_and_record();
+            record_req_duration(data, "get_full_block", req_timer);
             res
         }
         rpc::Request::GetBlockRange(min_height, max_height) => {
@@ -319,13 +421,39 @@ fn handle_rpc_request(
             let range = AsyncBlockRange::try_new(Arc::clone(&data.chain), min_height, max_height);
             match range {
                 Some(mut range) => {
+                    let permit = match state.try_acquire_block_range() {
+                        Some(permit) => permit,
+                        None => {
+                            warn!("Rejecting block range request, too many ranges in progress");
+                            record_req_duration(data, "get_block_range", req_timer);
+                            return Some(Body::Error(ErrorKind::Overloaded));
+                        }
+                    };
                     if let Some(filter) = state.filter() {
                         range.set_filter(Some(filter.clone()));
                     }
 
                     {
+                        let count = max_height - min_height + 1;
                         let mut tx = state.sender();
                         let fut = async move {
+                            let ws_msg = {
+                                let msg = Msg {
+                                    id,
+                                    body: Body::Response(rpc::Response::GetBlockRangeStart {
+                                        count,
+                                    }),
+                                };
+
+                                let mut buf = Vec::with_capacity(32);
+                                msg.serialize(&mut buf);
+                                WsMessage::Binary(buf)
+                            };
+                            if tx.send(ws_msg).await.is_err() {
+                                warn!("Failed to send block range start");
+                                return;
+                            }
+
                             while let Some(block) = range.next().await {
                                 let ws_msg = {
                                     let msg = Msg {
@@ -356,15 +484,16 @@ fn handle_rpc_request(
                             if tx.send(ws_msg).await.is_err() {
                                 warn!("Failed to send block range finalizer");
                             }
+                            drop(permit);
                         };
                         tokio::spawn(fut.in_current_span());
                     }
 
-                    req_timer.stop_and_record();
+                    record_req_duration(data, "get_block_range", req_timer);
                     return None;
                 }
                 None => {
-                    req_timer.stop_and_record();
+                    record_req_duration(data, "get_block_range", req_timer);
                     Body::Error(ErrorKind::InvalidHeight)
                 }
             }
@@ -372,11 +501,104 @@ fn handle_rpc_request(
         rpc::Request::GetAccountInfo(acc) => {
             let req_timer = REQ_GET_ACC_INFO_DUR.start_timer();
             let res = data.minter.get_account_info(acc);
-            req_timer.stop_and_record();
+            record_req_duration(data, "get_account_info", req_timer);
             match res {
                 Ok(info) => Body::Response(rpc::Response::GetAccountInfo(info)),
                 Err(e) => Body::Error(ErrorKind::TxValidation(e)),
             }
         }
+        rpc::Request::GetBlocks(heights) => {
+            let req_timer = REQ_GET_BLOCKS_DUR.start_timer();
+            if heights.len() > data.max_get_blocks {
+                record_req_duration(data, "get_blocks", req_timer);
+                return Some(Body::Error(ErrorKind::InvalidRequest));
+            }
+            let blocks = heights
+                .into_iter()
+                .map(|height| get_block(data, state, height))
+                .collect();
+            record_req_duration(data, "get_blocks", req_timer);
+            Body::Response(rpc::Response::GetBlocks(blocks))
+        }
+        rpc::Request::ListAccounts { start, limit } => {
+            let req_timer = REQ_LIST_ACCOUNTS_DUR.start_timer();
+            let limit = usize::from(limit).min(MAX_LIST_ACCOUNTS);
+            // Retrieve an extra account to determine where the next page starts
+            let mut accounts: Vec<Account> = data
+                .chain
+                .indexer()
+                .iter_accounts(start, limit + 1)
+                .into_iter()
+                .map(|(_, account)| account)
+                .collect();
+            let next = if accounts.len() > limit {
+                accounts.pop().map(|account| account.id)
+            } else {
+                None
+            };
+            record_req_duration(data, "list_accounts", req_timer);
+            Body::Response(rpc::Response::ListAccounts { accounts, next })
+        }
+        rpc::Request::GetStats => {
+            let req_timer = REQ_GET_STATS_DUR.start_timer();
+            let stats = data.chain.stats();
+            record_req_duration(data, "get_stats", req_timer);
+            Body::Response(rpc::Response::GetStats(stats))
+        }
+        rpc::Request::GetPending(_) => {
+            let req_timer = REQ_GET_PENDING_DUR.start_timer();
+            let txs = data.minter.get_pending();
+            record_req_duration(data, "get_pending", req_timer);
+            Body::Response(rpc::Response::GetPending(txs))
+        }
+        rpc::Request::DropPending(txid, _) => {
+            let req_timer = REQ_DROP_PENDING_DUR.start_timer();
+            let dropped = data.minter.drop_pending(&txid);
+            record_req_duration(data, "drop_pending", req_timer);
+            if dropped {
+                Body::Response(rpc::Response::DropPending)
+            } else {
+                Body::Error(ErrorKind::InvalidRequest)
+            }
+        }
     })
 }
+
+/// Records the duration of a request and warns when it exceeds the slow request threshold.
+fn record_req_duration(data: &ServerData, req_type: &'static str, timer: HistogramTimer) {
+    let secs = timer.stop_and_record();
+    if let Some(threshold) = data.slow_request_threshold {
+        if secs > threshold.as_secs_f64() {
+            warn!(
+                request = req_type,
+                duration_ms = (secs * 1000.0) as u64,
+                "Slow request"
+            );
+        }
+    }
+}
+
+/// Returns whether the request is signed by the chain owner's minter key and has not expired.
+fn is_minter_authorized(data: &ServerData, req: &rpc::Request) -> bool {
+    let expiry = match req.minter_auth() {
+        Some(auth) => auth.expiry,
+        None => return false,
+    };
+    let current_time = get_epoch_time();
+    if expiry <= current_time || expiry - current_time > MINTER_AUTH_MAX_EXPIRY_TIME {
+        return false;
+    }
+
+    match data.chain.get_owner() {
+        TxVariant::V0(TxVariantV0::OwnerTx(owner)) => req.verify_minter_auth(&owner.minter),
+        _ => unreachable!(),
+    }
+}
+
+/// Retrieves the block at the specified height with the client's block filter applied if any.
+fn get_block(data: &ServerData, state: &WsClient, height: u64) -> Option<FilteredBlock> {
+    match &state.filter {
+        Some(filter) => data.chain.get_filtered_block(height, filter),
+        None => data.chain.get_block(height).map(FilteredBlock::Block),
+    }
+}
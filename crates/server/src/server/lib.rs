@@ -1,28 +1,95 @@
 pub mod client;
+pub mod metrics;
 pub mod minter;
 pub mod pool;
+pub mod remote_signer;
 
-mod metrics;
-
-use godcoin::{blockchain::ReindexOpts, prelude::*};
-use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+use godcoin::{
+    blockchain::{ReindexOpts, RewardSchedule, SpendLimit},
+    crypto::Digest,
+    prelude::*,
+};
+use net2::TcpBuilder;
+use std::{
+    net::{self, SocketAddr},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tokio::{net::TcpListener, prelude::*, time};
 use tracing::{error, info, warn};
 
 pub mod prelude {
     pub use super::minter::*;
     pub use super::pool::SubscriptionPool;
+    pub use super::remote_signer::RemoteSigner;
 }
 
 use prelude::*;
 
+/// Default maximum number of heights a client may request in a single `GetBlocks` request.
+pub const DEFAULT_MAX_GET_BLOCKS: usize = 100;
+pub const DEFAULT_WARMUP_BLOCKS: u64 = 100;
+
+/// Maximum number of accounts returned in a single `ListAccounts` response.
+pub const MAX_LIST_ACCOUNTS: usize = 100;
+
+/// Maximum number of `GetBlockRange` requests a single client may have streaming concurrently.
+pub const MAX_CONCURRENT_BLOCK_RANGES: usize = 4;
+
+/// Socket options applied to the server's listener and accepted connections.
+#[derive(Clone, Copy, Debug)]
+pub struct SocketOpts {
+    /// Allows binding to the address while connections from a previous run are in `TIME_WAIT`.
+    pub reuse_address: bool,
+    /// Maximum number of pending connections queued before they are accepted.
+    pub backlog: i32,
+    /// Disables Nagle's algorithm on accepted connections so small messages such as pings are sent
+    /// immediately.
+    pub nodelay: bool,
+}
+
+impl Default for SocketOpts {
+    fn default() -> Self {
+        Self {
+            reuse_address: true,
+            backlog: 1024,
+            nodelay: true,
+        }
+    }
+}
+
 pub struct ServerOpts {
     pub blocklog_loc: PathBuf,
     pub index_loc: PathBuf,
-    pub minter_key: KeyPair,
+    pub minter_signer: MinterSigner,
     pub bind_addr: String,
     pub reindex: Option<ReindexOpts>,
     pub enable_stale_production: bool,
+    pub max_connections: Option<usize>,
+    pub allowed_origins: Option<Vec<String>>,
+    pub max_get_blocks: usize,
+    /// Trusted pairs of block height and block header hash, see `Blockchain::set_checkpoints`.
+    pub checkpoints: Vec<(u64, Digest)>,
+    /// Seconds of clock skew tolerated when validating transaction expiry, see
+    /// `Blockchain::set_clock_skew_tolerance`.
+    pub clock_skew_tolerance: u64,
+    /// Reward minted to the owner wallet for every block, see `Blockchain::set_reward_schedule`.
+    pub reward_schedule: RewardSchedule,
+    /// Limit of the amount every account may transfer within a window of recent blocks, see
+    /// `Blockchain::set_spend_limit`.
+    pub spend_limit: Option<SpendLimit>,
+    /// Number of the most recent blocks to pre-load into the block cache on startup, see
+    /// `Blockchain::warmup`.
+    pub warmup_blocks: u64,
+    pub slow_request_threshold: Option<Duration>,
+    pub socket_opts: SocketOpts,
+    /// Path of a Unix domain socket to accept connections on in addition to the TCP bind address.
+    #[cfg(unix)]
+    pub unix_socket_path: Option<PathBuf>,
 }
 
 #[derive(Clone)]
@@ -30,14 +97,53 @@ pub struct ServerData {
     pub chain: Arc<Blockchain>,
     pub minter: Minter,
     pub sub_pool: SubscriptionPool,
+    /// Clients subscribed to receive transactions accepted into the memory pool.
+    pub tx_sub_pool: SubscriptionPool,
+    /// Maximum number of concurrent client connections, unlimited when `None`.
+    pub max_connections: Option<usize>,
+    /// Number of currently open client connections.
+    pub active_connections: Arc<AtomicUsize>,
+    /// Origins permitted to open a WebSocket connection, any origin is allowed when `None`.
+    pub allowed_origins: Option<Arc<Vec<String>>>,
+    /// Maximum number of heights a client may request in a single `GetBlocks` request.
+    pub max_get_blocks: usize,
+    /// Requests taking longer than the threshold are logged as a warning, disabled when `None`.
+    pub slow_request_threshold: Option<Duration>,
+    pub socket_opts: SocketOpts,
 }
 
 pub fn init() {
     metrics::register_metrics();
 }
 
-pub fn start(opts: ServerOpts) {
+pub fn start(opts: ServerOpts) -> Arc<ServerData> {
     let blockchain = Arc::new(Blockchain::new(&opts.blocklog_loc, &opts.index_loc));
+    if !opts.checkpoints.is_empty() {
+        info!("Using {} trusted checkpoints", opts.checkpoints.len());
+        for (height, hash) in &opts.checkpoints {
+            info!("=> Checkpoint at height {}: {}", height, hash);
+        }
+        blockchain.set_checkpoints(opts.checkpoints);
+    }
+    if opts.clock_skew_tolerance > 0 {
+        info!(
+            "Tolerating {}s of clock skew for transaction expiry",
+            opts.clock_skew_tolerance
+        );
+        blockchain.set_clock_skew_tolerance(opts.clock_skew_tolerance);
+    }
+    if opts.reward_schedule != RewardSchedule::default() {
+        info!("Using block reward schedule {:?}", opts.reward_schedule);
+    }
+    blockchain.set_reward_schedule(opts.reward_schedule);
+    if let Some(limit) = &opts.spend_limit {
+        info!(
+            "Limiting transfers to {} per account within {} blocks",
+            limit.max_amount.to_string(),
+            limit.window
+        );
+    }
+    blockchain.set_spend_limit(opts.spend_limit);
 
     let is_empty = blockchain.is_empty();
     if !is_empty && blockchain.index_status() != IndexStatus::Complete {
@@ -52,7 +158,13 @@ pub fn start(opts: ServerOpts) {
     }
 
     if is_empty {
-        let info = blockchain.create_genesis_block(opts.minter_key.clone());
+        let minter_key = match &opts.minter_signer {
+            MinterSigner::BuiltIn(key) => key.clone(),
+            MinterSigner::Remote(_) => {
+                panic!("a built-in minter key is required to create the genesis block")
+            }
+        };
+        let info = blockchain.create_genesis_block(minter_key);
         info!("=> Generated new block chain");
         info!(
             "=> Address: {} (raw id: {})",
@@ -70,11 +182,18 @@ pub fn start(opts: ServerOpts) {
         blockchain.get_chain_height()
     );
 
+    if opts.warmup_blocks > 0 {
+        let cached = blockchain.warmup(opts.warmup_blocks);
+        info!("Pre-loaded {} recent blocks into the block cache", cached);
+    }
+
     let sub_pool = SubscriptionPool::new();
+    let tx_sub_pool = SubscriptionPool::new();
     let minter = Minter::new(
         Arc::clone(&blockchain),
-        opts.minter_key,
+        opts.minter_signer,
         sub_pool.clone(),
+        tx_sub_pool.clone(),
         opts.enable_stale_production,
     );
     minter.clone().start_production_loop();
@@ -83,37 +202,156 @@ pub fn start(opts: ServerOpts) {
         chain: Arc::clone(&blockchain),
         minter,
         sub_pool,
+        tx_sub_pool,
+        max_connections: opts.max_connections,
+        active_connections: Arc::new(AtomicUsize::new(0)),
+        allowed_origins: opts.allowed_origins.map(Arc::new),
+        max_get_blocks: opts.max_get_blocks,
+        slow_request_threshold: opts.slow_request_threshold,
+        socket_opts: opts.socket_opts,
     });
 
     let addr = opts.bind_addr.parse::<SocketAddr>().unwrap();
-    start_server(addr, data);
+    start_server(addr, Arc::clone(&data));
+    #[cfg(unix)]
+    {
+        if let Some(path) = opts.unix_socket_path {
+            info!("Listening on Unix socket {:?}", path);
+            start_unix_server(path, Arc::clone(&data));
+        }
+    }
+    data
 }
 
-fn start_server(server_addr: SocketAddr, data: Arc<ServerData>) {
-    fn is_connection_error(e: &io::Error) -> bool {
-        match e.kind() {
-            io::ErrorKind::ConnectionRefused
-            | io::ErrorKind::ConnectionAborted
-            | io::ErrorKind::ConnectionReset => true,
-            _ => false,
+/// Reloads the minter key every time the process receives a SIGHUP signal. The `load_key` function
+/// is responsible for retrieving the new key. Keys that do not match the minter key of the current
+/// chain owner are logged and ignored.
+#[cfg(unix)]
+pub fn reload_minter_key_on_sighup<F>(minter: Minter, load_key: F)
+where
+    F: Fn() -> Result<KeyPair, String> + Send + 'static,
+{
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut stream = signal(SignalKind::hangup()).expect("failed to register SIGHUP handler");
+    tokio::spawn(async move {
+        while stream.recv().await.is_some() {
+            info!("Received SIGHUP, reloading the minter key");
+            match load_key() {
+                Ok(key) => {
+                    if minter.set_minter_key(key) {
+                        info!("Minter key reloaded");
+                    } else {
+                        warn!("Reloaded minter key does not match the chain owner, ignoring");
+                    }
+                }
+                Err(e) => error!("Failed to reload the minter key: {}", e),
+            }
         }
+    });
+}
+
+pub fn start_server(server_addr: SocketAddr, data: Arc<ServerData>) {
+    fn bind(addr: &SocketAddr, opts: &SocketOpts) -> io::Result<net::TcpListener> {
+        let builder = match addr {
+            SocketAddr::V4(_) => TcpBuilder::new_v4()?,
+            SocketAddr::V6(_) => TcpBuilder::new_v6()?,
+        };
+        builder.reuse_address(opts.reuse_address)?;
+        builder.bind(addr)?;
+        let listener = builder.listen(opts.backlog)?;
+        listener.set_nonblocking(true)?;
+        Ok(listener)
     }
 
     tokio::spawn(async move {
-        let mut server = TcpListener::bind(&server_addr).await.unwrap();
+        let listener = bind(&server_addr, &data.socket_opts).unwrap();
+        let mut server = TcpListener::from_std(listener).unwrap();
         loop {
             match server.accept().await {
                 Ok((stream, peer_addr)) => {
-                    client::handle_new_client(stream, peer_addr, Arc::clone(&data));
+                    if data.socket_opts.nodelay {
+                        if let Err(e) = stream.set_nodelay(true) {
+                            warn!("Failed to set TCP_NODELAY for {}: {:?}", peer_addr, e);
+                        }
+                    }
+                    if try_open_connection(&data, peer_addr) {
+                        client::handle_new_client(stream, peer_addr, Arc::clone(&data));
+                    }
                 }
-                Err(e) => {
-                    error!("Accept error: {:?}", e);
-                    match e {
-                        ref e if is_connection_error(e) => continue,
-                        _ => time::delay_for(Duration::from_millis(500)).await,
+                Err(e) => handle_accept_error(e).await,
+            }
+        }
+    });
+}
+
+/// Accepts WebSocket connections over a Unix domain socket at the provided path, access to the
+/// server is then controlled by the socket file's permissions. A stale socket file left behind by
+/// a previous run is replaced.
+///
+/// Unix socket clients have no network address, each connection is identified by a unique
+/// placeholder IPv6 address in the `2001:db8::/32` documentation range with port 0, which TCP
+/// clients never use. The first connection is logged as `[2001:db8::1]:0`.
+#[cfg(unix)]
+pub fn start_unix_server(path: PathBuf, data: Arc<ServerData>) {
+    use std::{
+        fs,
+        net::{Ipv6Addr, SocketAddrV6},
+        os::unix::fs::FileTypeExt,
+    };
+    use tokio::net::UnixListener;
+
+    // Documentation prefix (RFC 3849), reserved for examples and never routed
+    const UNIX_PEER_ADDR_PREFIX: u128 = 0x2001_0db8 << 96;
+
+    if let Ok(meta) = fs::symlink_metadata(&path) {
+        if meta.file_type().is_socket() {
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    tokio::spawn(async move {
+        let mut server = UnixListener::bind(&path).unwrap();
+        let mut next_id: u128 = 0;
+        loop {
+            match server.accept().await {
+                Ok((stream, _)) => {
+                    next_id += 1;
+                    let ip = Ipv6Addr::from(UNIX_PEER_ADDR_PREFIX | next_id);
+                    let peer_addr = SocketAddrV6::new(ip, 0, 0, 0).into();
+                    if try_open_connection(&data, peer_addr) {
+                        client::handle_new_client(stream, peer_addr, Arc::clone(&data));
                     }
                 }
+                Err(e) => handle_accept_error(e).await,
             }
         }
     });
 }
+
+/// Counts a newly accepted connection, returns false and drops the connection when the connection
+/// limit is reached.
+fn try_open_connection(data: &ServerData, peer_addr: SocketAddr) -> bool {
+    let conn_count = data.active_connections.fetch_add(1, Ordering::AcqRel) + 1;
+    if let Some(max_connections) = data.max_connections {
+        if conn_count > max_connections {
+            data.active_connections.fetch_sub(1, Ordering::AcqRel);
+            warn!(
+                "Connection limit ({}) reached, rejecting {}",
+                max_connections, peer_addr
+            );
+            return false;
+        }
+    }
+    true
+}
+
+async fn handle_accept_error(e: io::Error) {
+    error!("Accept error: {:?}", e);
+    match e.kind() {
+        io::ErrorKind::ConnectionRefused
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::ConnectionReset => {}
+        _ => time::delay_for(Duration::from_millis(500)).await,
+    }
+}
@@ -5,12 +5,19 @@ lazy_static::lazy_static! {
         IntCounter::new("net_sent_bytes", "Total network bytes sent").unwrap();
     pub static ref NET_BYTES_RECEIVED: IntCounter =
         IntCounter::new("net_received_bytes", "Total network bytes received").unwrap();
+    pub static ref NET_DESERIALIZE_ERRORS: IntCounter = IntCounter::new(
+        "net_deserialize_errors_total",
+        "Total client messages that failed to deserialize"
+    ).unwrap();
 
     pub static ref REQ_BROADCAST_FAIL: IntCounter =
         IntCounter::new("req_broadcast_failure", "Total transaction broadcasts rejected").unwrap();
     pub static ref REQ_BROADCAST_TOTAL: IntCounter =
         IntCounter::new("req_broadcast_total", "Total transactions broadcasted").unwrap();
 
+    pub static ref TXID_PURGED_TOTAL: IntCounter =
+        IntCounter::new("txid_purged_total", "Total expired txids removed from the index").unwrap();
+
     static ref REQ_DUR: HistogramVec = {
         let opts = Opts::new(
             "request_duration_seconds",
@@ -34,6 +41,12 @@ lazy_static::lazy_static! {
     );
     pub static ref REQ_SUBSCRIBE_DUR: Histogram = REQ_DUR.with_label_values(&["subscribe"]);
     pub static ref REQ_UNSUBSCRIBE_DUR: Histogram = REQ_DUR.with_label_values(&["unsubscribe"]);
+    pub static ref REQ_SUBSCRIBE_RECEIPTS_DUR: Histogram = REQ_DUR.with_label_values(
+        &["subscribe_receipts"]
+    );
+    pub static ref REQ_SUBSCRIBE_TXS_DUR: Histogram = REQ_DUR.with_label_values(
+        &["subscribe_txs"]
+    );
     pub static ref REQ_GET_PROPERTIES_DUR: Histogram = REQ_DUR.with_label_values(
         &["get_properties"]
     );
@@ -44,9 +57,18 @@ lazy_static::lazy_static! {
     pub static ref REQ_GET_BLOCK_RANGE_DUR: Histogram = REQ_DUR.with_label_values(
         &["get_block_range"]
     );
+    pub static ref REQ_GET_BLOCKS_DUR: Histogram = REQ_DUR.with_label_values(&["get_blocks"]);
     pub static ref REQ_GET_ACC_INFO_DUR: Histogram = REQ_DUR.with_label_values(
         &["get_account_info"]
     );
+    pub static ref REQ_LIST_ACCOUNTS_DUR: Histogram = REQ_DUR.with_label_values(
+        &["list_accounts"]
+    );
+    pub static ref REQ_GET_STATS_DUR: Histogram = REQ_DUR.with_label_values(&["get_stats"]);
+    pub static ref REQ_GET_PENDING_DUR: Histogram = REQ_DUR.with_label_values(&["get_pending"]);
+    pub static ref REQ_DROP_PENDING_DUR: Histogram = REQ_DUR.with_label_values(
+        &["drop_pending"]
+    );
 }
 
 pub fn register_metrics() {
@@ -60,19 +82,29 @@ pub fn register_metrics() {
 
     register!(NET_BYTES_SENT);
     register!(NET_BYTES_RECEIVED);
+    register!(NET_DESERIALIZE_ERRORS);
 
     register!(REQ_BROADCAST_FAIL);
     register!(REQ_BROADCAST_TOTAL);
 
+    register!(TXID_PURGED_TOTAL);
+
     register!(REQ_DUR);
     lazy_static::initialize(&REQ_BROADCAST_DUR);
     lazy_static::initialize(&REQ_SET_BLOCK_FILTER_DUR);
     lazy_static::initialize(&REQ_CLEAR_BLOCK_FILTER_DUR);
     lazy_static::initialize(&REQ_SUBSCRIBE_DUR);
     lazy_static::initialize(&REQ_UNSUBSCRIBE_DUR);
+    lazy_static::initialize(&REQ_SUBSCRIBE_RECEIPTS_DUR);
+    lazy_static::initialize(&REQ_SUBSCRIBE_TXS_DUR);
     lazy_static::initialize(&REQ_GET_PROPERTIES_DUR);
     lazy_static::initialize(&REQ_GET_BLOCK_DUR);
     lazy_static::initialize(&REQ_GET_FULL_BLOCK_DUR);
     lazy_static::initialize(&REQ_GET_BLOCK_RANGE_DUR);
+    lazy_static::initialize(&REQ_GET_BLOCKS_DUR);
     lazy_static::initialize(&REQ_GET_ACC_INFO_DUR);
+    lazy_static::initialize(&REQ_LIST_ACCOUNTS_DUR);
+    lazy_static::initialize(&REQ_GET_STATS_DUR);
+    lazy_static::initialize(&REQ_GET_PENDING_DUR);
+    lazy_static::initialize(&REQ_DROP_PENDING_DUR);
 }
@@ -1,37 +1,110 @@
-use crate::SubscriptionPool;
-use godcoin::{constants::BLOCK_PROD_TIME, prelude::*};
-use parking_lot::Mutex;
-use std::{sync::Arc, time::Duration};
-use tokio::time;
-use tracing::{info, warn};
+use crate::{metrics::TXID_PURGED_TOTAL, remote_signer::RemoteSigner, SubscriptionPool};
+use godcoin::{
+    constants::BLOCK_PROD_TIME,
+    prelude::{net::ErrorKind, *},
+};
+use parking_lot::{Mutex, RwLock};
+use std::{io, sync::Arc, time::Duration};
+use tokio::{task, time};
+use tracing::{error, info, warn};
+
+/// Decides whether a broadcasted transaction may enter the memory pool. The policy is checked
+/// before the transaction is executed, which must succeed as well for the transaction to be
+/// accepted.
+pub trait TxAdmissionPolicy: Send + Sync {
+    fn check(&self, chain: &Blockchain, tx: &TxVariant) -> Result<(), ErrorKind>;
+}
+
+/// Admits every transaction, leaving acceptance to the transaction execution.
+pub struct AcceptAll;
+
+impl TxAdmissionPolicy for AcceptAll {
+    fn check(&self, _: &Blockchain, _: &TxVariant) -> Result<(), ErrorKind> {
+        Ok(())
+    }
+}
+
+/// Selects how the minter signs produced blocks.
+#[derive(Clone)]
+pub enum MinterSigner {
+    /// Signs using a minter key held in process memory.
+    BuiltIn(KeyPair),
+    /// Signs using an external signer so the minter key never enters the process.
+    Remote(RemoteSigner),
+}
+
+impl MinterSigner {
+    pub fn public_key(&self) -> &PublicKey {
+        match self {
+            MinterSigner::BuiltIn(key) => &key.0,
+            MinterSigner::Remote(signer) => signer.public_key(),
+        }
+    }
+
+    /// Signs the message. Remote signers perform blocking network I/O and may fail, see
+    /// `RemoteSigner::try_sign`.
+    pub fn try_sign(&self, msg: &[u8]) -> io::Result<SigPair> {
+        match self {
+            MinterSigner::BuiltIn(key) => Ok(key.sign(msg)),
+            MinterSigner::Remote(signer) => signer.try_sign(msg),
+        }
+    }
+}
+
+impl From<KeyPair> for MinterSigner {
+    fn from(key: KeyPair) -> Self {
+        MinterSigner::BuiltIn(key)
+    }
+}
+
+impl From<RemoteSigner> for MinterSigner {
+    fn from(signer: RemoteSigner) -> Self {
+        MinterSigner::Remote(signer)
+    }
+}
+
+#[derive(Debug)]
+pub enum ProduceErr {
+    /// The signer failed to sign the block.
+    Sign(io::Error),
+    /// Pending transactions in the block were removed from the pool while the block was signed.
+    PoolChanged,
+    Block(blockchain::BlockErr),
+}
 
 #[derive(Clone)]
 pub struct Minter {
     chain: Arc<Blockchain>,
-    minter_key: KeyPair,
+    signer: Arc<RwLock<MinterSigner>>,
+    admission_policy: Arc<RwLock<Box<dyn TxAdmissionPolicy>>>,
     receipt_pool: Arc<Mutex<ReceiptPool>>,
     client_pool: SubscriptionPool,
+    tx_pool: SubscriptionPool,
     enable_stale_production: bool,
 }
 
 impl Minter {
-    pub fn new(
+    /// Creates a minter that signs produced blocks with the provided signer. The signer may be a
+    /// `KeyPair` held in memory or a `RemoteSigner`.
+    pub fn new<S: Into<MinterSigner>>(
         chain: Arc<Blockchain>,
-        minter_key: KeyPair,
+        signer: S,
         pool: SubscriptionPool,
+        tx_pool: SubscriptionPool,
         enable_stale_production: bool,
     ) -> Self {
-        match chain.get_owner() {
-            TxVariant::V0(tx) => match tx {
-                TxVariantV0::OwnerTx(tx) => assert_eq!(tx.minter, minter_key.0),
-                _ => unreachable!(),
-            },
-        }
+        let signer = signer.into();
+        assert!(
+            is_owner_minter_key(&chain, signer.public_key()),
+            "minter key must match the chain owner"
+        );
         Self {
             chain: Arc::clone(&chain),
-            minter_key,
+            signer: Arc::new(RwLock::new(signer)),
+            admission_policy: Arc::new(RwLock::new(Box::new(AcceptAll))),
             receipt_pool: Arc::new(Mutex::new(ReceiptPool::new(chain))),
             client_pool: pool,
+            tx_pool,
             enable_stale_production,
         }
     }
@@ -42,22 +115,46 @@ impl Minter {
             // We use a delay rather than an interval to prevent mass-producing blocks if the timer needs to "catch up"
             // on missed interval events.
             time::delay_for(dur).await;
-            self.produce(false).unwrap();
+            if let Err(e) = self.produce_async().await {
+                error!("Failed to produce block, skipping: {:?}", e);
+            }
             self.start_production_loop();
         });
     }
 
-    pub fn force_produce_block(
-        &self,
-        force_stale_production: bool,
-    ) -> Result<(), blockchain::BlockErr> {
+    /// Produces a block even when stale production is disabled and there are no new transactions
+    /// if `force_stale_production` is set. Blocks the current thread while the block is signed.
+    pub fn force_produce_block(&self, force_stale_production: bool) -> Result<(), ProduceErr> {
         warn!("Forcing produced block...");
-        self.produce(force_stale_production)
+        let block = match self.prepare_block(force_stale_production) {
+            Some(block) => block,
+            None => return Ok(()),
+        };
+        let block = sign_block(&self.signer.read(), block).map_err(ProduceErr::Sign)?;
+        self.commit_block(block)
     }
 
-    fn produce(&self, force_stale_production: bool) -> Result<(), blockchain::BlockErr> {
-        let mut receipt_pool_lock = self.receipt_pool.lock();
-        let receipts = receipt_pool_lock.flush();
+    async fn produce_async(&self) -> Result<(), ProduceErr> {
+        let block = match self.prepare_block(false) {
+            Some(block) => block,
+            None => return Ok(()),
+        };
+        // Remote signers block on network I/O, so the block is signed off the async workers and
+        // without holding the receipt pool lock
+        let signer = self.signer.read().clone();
+        let block = task::spawn_blocking(move || sign_block(&signer, block))
+            .await
+            .expect("block signing task panicked")
+            .map_err(ProduceErr::Sign)?;
+        self.commit_block(block)
+    }
+
+    /// Creates the next unsigned block from the pending receipts, or returns `None` if no block
+    /// should be produced. The receipts are left in the pool until the block is committed.
+    fn prepare_block(&self, force_stale_production: bool) -> Option<Block> {
+        let receipt_pool = self.receipt_pool.lock();
+        let receipts = receipt_pool.receipts();
+        self.purge_expired_txids();
         let should_produce =
             if force_stale_production || self.enable_stale_production || !receipts.is_empty() {
                 true
@@ -74,26 +171,37 @@ impl Minter {
                 "[current height: {}] No new transactions, refusing to produce block",
                 height
             );
-            return Ok(());
+            return None;
         }
 
         let head = self.chain.get_chain_head();
         let block = match head.as_ref() {
             Block::V0(block) => {
-                let mut b = block.new_child(receipts);
-                b.sign(&self.minter_key);
-                b
+                let reward = self.chain.reward_schedule().reward_at(block.height + 1);
+                block.new_child_with_reward(receipts.to_vec(), reward)
             }
         };
+        Some(block)
+    }
 
-        let height = block.height();
-        let receipt_len = block.receipts().len();
-
-        self.chain.insert_block(block.clone())?;
-
+    /// Inserts the signed block and removes its receipts from the pool. The pool is left unchanged
+    /// if the block fails to be inserted.
+    fn commit_block(&self, block: Block) -> Result<(), ProduceErr> {
         // Make sure the receipt pool is locked until the block is produced. This is necessary to
         // ensure that transactions that depends on a previous transaction in the memory pool can be
         // properly validated.
+        let mut receipt_pool_lock = self.receipt_pool.lock();
+        if !receipt_pool_lock.receipts().starts_with(block.receipts()) {
+            return Err(ProduceErr::PoolChanged);
+        }
+
+        let height = block.height();
+        let receipt_len = block.receipts().len();
+
+        self.chain
+            .insert_block(block.clone())
+            .map_err(ProduceErr::Block)?;
+        receipt_pool_lock.flush_front(receipt_len);
         std::mem::drop(receipt_pool_lock);
 
         let receipts = if receipt_len == 1 {
@@ -106,19 +214,76 @@ impl Minter {
             height, receipt_len, receipts
         );
 
-        self.client_pool
-            .broadcast(rpc::Response::GetBlock(FilteredBlock::Block(Arc::new(
-                block,
-            ))));
+        self.client_pool.broadcast_block(Arc::new(block));
         Ok(())
     }
 
-    pub fn push_tx(&self, tx: TxVariant) -> Result<(), blockchain::TxErr> {
+    /// Removes expired txids from the index to keep it bounded. Transactions that expired can no
+    /// longer be accepted so their txids aren't needed to detect duplicates.
+    fn purge_expired_txids(&self) {
+        let purged = self.chain.indexer().purge_expired_txids();
+        TXID_PURGED_TOTAL.inc_by(purged as i64);
+    }
+
+    /// Replaces the key used to sign produced blocks. The key is only replaced when it matches the
+    /// minter key of the current chain owner, otherwise the current key is kept and false is
+    /// returned.
+    pub fn set_minter_key(&self, minter_key: KeyPair) -> bool {
+        self.set_signer(minter_key)
+    }
+
+    /// Replaces the signer used to sign produced blocks, see `set_minter_key`.
+    pub fn set_signer<S: Into<MinterSigner>>(&self, signer: S) -> bool {
+        let signer = signer.into();
+        if !is_owner_minter_key(&self.chain, signer.public_key()) {
+            return false;
+        }
+        *self.signer.write() = signer;
+        true
+    }
+
+    /// Replaces the policy deciding which transactions may enter the memory pool.
+    pub fn set_admission_policy<P: TxAdmissionPolicy + 'static>(&self, policy: P) {
+        *self.admission_policy.write() = Box::new(policy);
+    }
+
+    pub fn push_tx(&self, tx: TxVariant) -> Result<(), ErrorKind> {
+        self.admission_policy.read().check(&self.chain, &tx)?;
+
+        // Avoid cloning the transaction when nobody is listening
+        let accepted_tx = if self.tx_pool.is_empty() {
+            None
+        } else {
+            Some(tx.clone())
+        };
+
         self.receipt_pool
             .lock()
             .push(tx.precompute(), blockchain::skip_flags::SKIP_NONE)
+            .map_err(ErrorKind::TxValidation)?;
+
+        if let Some(tx) = accepted_tx {
+            self.tx_pool.broadcast(rpc::Response::TxAccepted(tx));
+        }
+        Ok(())
+    }
+
+    pub fn get_pending(&self) -> Vec<TxVariant> {
+        self.receipt_pool.lock().txs()
     }
 
+    pub fn drop_pending(&self, txid: &TxId) -> bool {
+        let dropped = self.receipt_pool.lock().remove(txid);
+        if dropped {
+            info!("Dropped pending transaction: {:?}", txid);
+        }
+        dropped
+    }
+
+    /// Returns the account info with the effects of all transactions pending in the pool applied.
+    /// Clients observe their own accepted broadcasts immediately, before the transactions are
+    /// confirmed in a block. Pending state is shared by every connection and may still change if a
+    /// transaction is dropped from the pool.
     pub fn get_account_info(&self, id: AccountId) -> Result<AccountInfo, blockchain::TxErr> {
         self.receipt_pool
             .lock()
@@ -126,3 +291,20 @@ impl Minter {
             .ok_or(blockchain::TxErr::Arithmetic)
     }
 }
+
+fn sign_block(signer: &MinterSigner, mut block: Block) -> io::Result<Block> {
+    let pair = signer.try_sign(block.calc_header_hash().as_ref())?;
+    match &mut block {
+        Block::V0(block) => block.signer = Some(pair),
+    }
+    Ok(block)
+}
+
+fn is_owner_minter_key(chain: &Blockchain, minter_key: &PublicKey) -> bool {
+    match chain.get_owner() {
+        TxVariant::V0(tx) => match tx {
+            TxVariantV0::OwnerTx(tx) => &tx.minter == minter_key,
+            _ => unreachable!(),
+        },
+    }
+}
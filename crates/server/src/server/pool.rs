@@ -4,9 +4,15 @@ use parking_lot::RwLock;
 use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 use tokio_tungstenite::tungstenite::Message;
 
+struct Subscriber {
+    tx: Sender<Message>,
+    /// Only the receipts matching the filter are sent for new blocks when set.
+    receipt_filter: Option<BlockFilter>,
+}
+
 #[derive(Clone)]
 pub struct SubscriptionPool {
-    clients: Arc<RwLock<HashMap<SocketAddr, Sender<Message>>>>,
+    clients: Arc<RwLock<HashMap<SocketAddr, Subscriber>>>,
 }
 
 impl SubscriptionPool {
@@ -19,7 +25,27 @@ impl SubscriptionPool {
 
     #[inline]
     pub fn insert(&self, addr: SocketAddr, tx: Sender<Message>) {
-        self.clients.write().insert(addr, tx);
+        let sub = Subscriber {
+            tx,
+            receipt_filter: None,
+        };
+        self.clients.write().insert(addr, sub);
+    }
+
+    /// Inserts a client that receives only the receipts matching the filter when a block is
+    /// broadcasted with `broadcast_block`.
+    #[inline]
+    pub fn insert_with_receipt_filter(
+        &self,
+        addr: SocketAddr,
+        tx: Sender<Message>,
+        filter: BlockFilter,
+    ) {
+        let sub = Subscriber {
+            tx,
+            receipt_filter: Some(filter),
+        };
+        self.clients.write().insert(addr, sub);
     }
 
     #[inline]
@@ -27,22 +53,51 @@ impl SubscriptionPool {
         self.clients.write().remove(&addr);
     }
 
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.clients.read().is_empty()
+    }
+
     pub fn broadcast(&self, msg: rpc::Response) {
-        let msg = {
-            let mut buf = Vec::with_capacity(65536);
-            let res = Msg {
-                id: u32::max_value(),
-                body: Body::Response(msg),
-            };
-            res.serialize(&mut buf);
-            Message::Binary(buf)
-        };
+        let msg = serialize_response(msg);
+        let clients = self.clients.read();
+        for client in clients.values() {
+            // Errors only occur when the other end is dropped, it is the pool managers responsibility to remove any
+            // disconnected clients
+            let _ = client.tx.clone().try_send(msg.clone());
+        }
+    }
 
+    /// Broadcasts a new block to all clients. Clients with a receipt filter receive the matching
+    /// receipts instead of the full block.
+    pub fn broadcast_block(&self, block: Arc<Block>) {
         let clients = self.clients.read();
+        let mut block_msg = None;
         for client in clients.values() {
+            let msg = match &client.receipt_filter {
+                Some(filter) => {
+                    let receipts = block
+                        .receipts()
+                        .iter()
+                        .filter(|receipt| receipt.matches_filter(filter))
+                        .cloned()
+                        .collect();
+                    serialize_response(rpc::Response::FilteredReceipts {
+                        height: block.height(),
+                        receipts,
+                    })
+                }
+                None => block_msg
+                    .get_or_insert_with(|| {
+                        serialize_response(rpc::Response::GetBlock(FilteredBlock::Block(
+                            Arc::clone(&block),
+                        )))
+                    })
+                    .clone(),
+            };
             // Errors only occur when the other end is dropped, it is the pool managers responsibility to remove any
             // disconnected clients
-            let _ = client.clone().try_send(msg.clone());
+            let _ = client.tx.clone().try_send(msg);
         }
     }
 }
@@ -53,3 +108,13 @@ impl Default for SubscriptionPool {
         SubscriptionPool::new()
     }
 }
+
+fn serialize_response(res: rpc::Response) -> Message {
+    let mut buf = Vec::with_capacity(65536);
+    let res = Msg {
+        id: u32::max_value(),
+        body: Body::Response(res),
+    };
+    res.serialize(&mut buf);
+    Message::Binary(buf)
+}
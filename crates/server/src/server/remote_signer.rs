@@ -0,0 +1,80 @@
+use godcoin::prelude::*;
+use std::{
+    io::{self, Cursor, Read, Write},
+    net::{Shutdown, SocketAddr, TcpStream},
+    time::Duration,
+};
+
+/// Default amount of time to wait when connecting to, writing to, or reading from the remote
+/// signer.
+pub const DEFAULT_REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Delegates signing to an external process so the private key never enters this process.
+///
+/// Every signature opens a new TCP connection to the remote signer. The request is the length of
+/// the message as a big-endian u32 followed by the message. The remote signer must respond with
+/// a serialized signature pair and close the connection. Signature pairs that are not from the
+/// expected public key or do not verify are rejected.
+#[derive(Clone, Debug)]
+pub struct RemoteSigner {
+    addr: SocketAddr,
+    public_key: PublicKey,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    pub fn new(addr: SocketAddr, public_key: PublicKey) -> Self {
+        Self {
+            addr,
+            public_key,
+            timeout: DEFAULT_REMOTE_SIGNER_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    #[inline]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The public key that signatures produced by the remote signer must be from.
+    #[inline]
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Signs the message using the remote signer. Fails when the remote signer is unreachable,
+    /// times out, or responds with an invalid signature. The network I/O is blocking so this must
+    /// not be called from an async task.
+    pub fn try_sign(&self, msg: &[u8]) -> io::Result<SigPair> {
+        let mut stream = TcpStream::connect_timeout(&self.addr, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let mut req = Vec::with_capacity(4 + msg.len());
+        req.push_u32(msg.len() as u32);
+        req.extend_from_slice(msg);
+        stream.write_all(&req)?;
+        stream.shutdown(Shutdown::Write)?;
+
+        let mut res = Vec::with_capacity(96);
+        stream.read_to_end(&mut res)?;
+        let pair = Cursor::<&[u8]>::new(&res).take_sig_pair()?;
+        if pair.pub_key != self.public_key {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "signature is from an unexpected key",
+            ));
+        } else if !pair.verify(msg) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid signature",
+            ));
+        }
+        Ok(pair)
+    }
+}
@@ -1,8 +1,12 @@
 use godcoin::{
-    blockchain::error::TxErr,
+    blockchain::{
+        error::TxErr, index::RawIndexEntry, GenesisBlockInfo, ReindexOpts, RewardSchedule,
+    },
     constants,
+    crypto::Digest,
     prelude::{net::ErrorKind, script::EvalErrKind, *},
 };
+use std::convert::TryInto;
 
 mod common;
 pub use common::*;
@@ -29,6 +33,185 @@ fn fresh_blockchain() {
     assert_eq!(chain.index_status(), IndexStatus::Complete);
 }
 
+#[test]
+fn fees_near_genesis() {
+    let minter = TestMinter::new();
+    let dir = minter.tmp_dir().join("fees_near_genesis");
+    std::fs::create_dir(&dir).unwrap();
+    let chain = Blockchain::new(&dir.join("blklog"), &dir.join("index"));
+    let minter_key = KeyPair::gen();
+    let info = chain.create_genesis_block(minter_key.clone());
+    let owner_id = info.owner_id;
+
+    let produce_block = |mint_count: usize| {
+        let receipts = (0..mint_count)
+            .map(|_| {
+                let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+                    base: create_tx_header("0.00000 TEST"),
+                    to: owner_id,
+                    amount: get_asset("1.00000 TEST"),
+                    attachment: vec![],
+                    attachment_name: "".to_string(),
+                }));
+                tx.append_sign(&info.wallet_keys[1]);
+                tx.append_sign(&info.wallet_keys[0]);
+                Receipt { tx, log: vec![] }
+            })
+            .collect();
+
+        let head = chain.get_chain_head();
+        let block = match head.as_ref() {
+            Block::V0(block) => {
+                let mut b = block.new_child(receipts);
+                b.sign(&minter_key);
+                b
+            }
+        };
+        chain.insert_block(block).unwrap();
+    };
+    let net_fee = |count| {
+        constants::GRAEL_FEE_MIN
+            .checked_mul(constants::GRAEL_FEE_NET_MULT.checked_pow(count).unwrap())
+    };
+    let account_fee = |count| {
+        constants::GRAEL_FEE_MIN.checked_mul(constants::GRAEL_FEE_MULT.checked_pow(count).unwrap())
+    };
+
+    // The genesis block contains the owner account creation and owner transactions
+    assert_eq!(chain.get_chain_height(), 0);
+    assert_eq!(chain.get_network_fee(), net_fee((1 + 2) / 10));
+    assert_eq!(chain.get_account_fee(owner_id, &[]), account_fee(2));
+
+    // Heights below 5 only consider the genesis block for the network fee
+    produce_block(10);
+    assert_eq!(chain.get_chain_height(), 1);
+    assert_eq!(chain.get_network_fee(), net_fee((1 + 2) / 10));
+    assert_eq!(chain.get_account_fee(owner_id, &[]), account_fee(2));
+
+    for _ in 0..3 {
+        produce_block(10);
+    }
+    assert_eq!(chain.get_chain_height(), 4);
+    assert_eq!(chain.get_network_fee(), net_fee((1 + 2) / 10));
+    // The genesis block falls outside of the account fee reset window
+    assert_eq!(chain.get_account_fee(owner_id, &[]), account_fee(1));
+
+    produce_block(10);
+    assert_eq!(chain.get_chain_height(), 5);
+    assert_eq!(chain.get_network_fee(), net_fee((1 + 2 + 50) / 10));
+    assert_eq!(chain.get_account_fee(owner_id, &[]), account_fee(1));
+}
+
+#[test]
+fn properties_fee_adjust_heights() {
+    let minter = TestMinter::new();
+    let chain = minter.chain();
+
+    // The test minter starts with a block after the genesis block
+    for height in 1..=11 {
+        assert_eq!(chain.get_chain_height(), height);
+        let props = chain.get_properties();
+        assert_eq!(props.network_fee_height, height / 5 * 5);
+        assert_eq!(props.next_fee_adjust_height, (height / 5 + 1) * 5);
+        assert!(props.next_fee_adjust_height > height);
+        assert_eq!(props.next_fee_adjust_height % 5, 0);
+        minter.produce_block().unwrap();
+    }
+}
+
+#[test]
+fn genesis_block_at_timestamp_is_reproducible() {
+    let minter = TestMinter::new();
+    let info = GenesisBlockInfo::new(KeyPair::gen(), 0);
+    let timestamp = 1_577_836_800;
+
+    let create_chain = |name: &str| {
+        let dir = minter.tmp_dir().join(name);
+        std::fs::create_dir(&dir).unwrap();
+        let chain = Blockchain::new(&dir.join("blklog"), &dir.join("index"));
+        chain.create_genesis_block_at(info.clone(), timestamp);
+        chain
+    };
+
+    let chain_a = create_chain("genesis_a");
+    let chain_b = create_chain("genesis_b");
+    let genesis_a = chain_a.get_block(0).unwrap();
+    let genesis_b = chain_b.get_block(0).unwrap();
+    assert_eq!(genesis_a.timestamp(), timestamp);
+    assert_eq!(genesis_a.calc_header_hash(), genesis_b.calc_header_hash());
+    assert_eq!(genesis_a, genesis_b);
+}
+
+#[test]
+fn genesis_filtered_for_owner() {
+    let minter = TestMinter::new();
+    let chain = minter.chain();
+    let genesis = chain.get_block(0).unwrap();
+
+    // The genesis block creates the owner account and sets the owner wallet
+    let mut filter = BlockFilter::new();
+    filter.insert(minter.genesis_info().owner_id);
+    assert_eq!(
+        chain.get_filtered_block(0, &filter),
+        Some(FilteredBlock::Block(genesis.clone()))
+    );
+
+    let mut filter = BlockFilter::new();
+    filter.insert(minter.genesis_info().owner_id + 1);
+    assert_eq!(
+        chain.get_filtered_block(0, &filter),
+        Some(FilteredBlock::Header((
+            genesis.header(),
+            genesis.signer().unwrap().clone()
+        )))
+    );
+}
+
+#[test]
+fn genesis_details_match_owner() {
+    let minter = TestMinter::new();
+    let chain = minter.chain();
+    let owner = match chain.get_owner() {
+        TxVariant::V0(tx) => match tx {
+            TxVariantV0::OwnerTx(tx) => tx,
+            _ => unreachable!(),
+        },
+    };
+    let owner_account = chain.get_account(owner.wallet, &[]).unwrap();
+
+    let details = chain.get_genesis_details().unwrap();
+    assert_eq!(details.owner_id, owner.wallet);
+    assert_eq!(details.owner_id, minter.genesis_info().owner_id);
+    assert_eq!(details.minter_key, owner.minter);
+    assert_eq!(details.minter_key, minter.genesis_info().minter_key.0);
+    assert_eq!(details.owner_permissions, owner_account.permissions);
+}
+
+#[test]
+fn warmup_caches_recent_blocks() {
+    let minter = TestMinter::new();
+    for _ in 0..4 {
+        minter.produce_block().unwrap();
+    }
+    let chain = minter.chain();
+    assert_eq!(chain.get_chain_height(), 5);
+
+    assert_eq!(chain.warmup(3), 3);
+    assert_eq!(chain.cached_block_heights(), vec![3, 4, 5]);
+
+    // Newly inserted blocks are cached alongside the warmed up blocks
+    minter.produce_block().unwrap();
+    assert_eq!(chain.cached_block_heights(), vec![3, 4, 5, 6]);
+
+    // The warmup is capped to the available blocks
+    assert_eq!(chain.warmup(1000), 7);
+    assert_eq!(chain.cached_block_heights(), (0..=6).collect::<Vec<_>>());
+
+    assert_eq!(chain.warmup(0), 0);
+    assert!(chain.cached_block_heights().is_empty());
+    assert!(chain.get_block(6).is_some());
+}
+
 #[test]
 fn reindexed_blockchain() {
     let mut minter = TestMinter::new();
@@ -133,6 +316,77 @@ fn reindexed_blockchain() {
     assert_eq!(res, Some(Err(ErrorKind::TxValidation(TxErr::TxDupe))));
 }
 
+#[test]
+fn reindex_matches_incremental_index() {
+    let mut minter = TestMinter::new();
+
+    let from_acc = minter.genesis_info().owner_id;
+    let to_acc = minter.create_default_account(1, true);
+
+    let amount = get_asset("1.00000 TEST");
+    let mut tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+        base: create_tx_header("1.00000 TEST"),
+        from: from_acc,
+        call_fn: 1,
+        args: {
+            let mut args = vec![];
+            args.push_u64(to_acc.id);
+            args.push_asset(amount);
+            args
+        },
+        amount,
+        memo: vec![],
+    }));
+    tx.append_sign(&minter.genesis_info().wallet_keys[3]);
+    tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+    let res = minter.send_req(rpc::Request::Broadcast(tx));
+    assert_eq!(res, Some(Ok(rpc::Response::Broadcast)));
+    minter.produce_block().unwrap();
+
+    let incremental = minter.chain().indexer().raw_entries();
+    minter.unindexed();
+    minter.reindex();
+    let reindexed = minter.chain().indexer().raw_entries();
+
+    // Txids that expired before reindexing remain in the incremental index until they're purged
+    // but are never reindexed. The genesis transactions expire after a second, so only txids
+    // still unexpired after reindexing are compared.
+    let current_time = godcoin::get_epoch_time();
+    let unexpired = |entries: Vec<RawIndexEntry>| -> Vec<RawIndexEntry> {
+        entries
+            .into_iter()
+            .filter(|(cf, _, value)| {
+                *cf != "tx_expiry"
+                    || u64::from_be_bytes(value.as_ref().try_into().unwrap()) > current_time
+            })
+            .collect()
+    };
+
+    // Covers the owner, balances, token supply, block positions, and txid expirations
+    assert_eq!(unexpired(incremental), unexpired(reindexed));
+}
+
+#[test]
+fn reindex_genesis_with_nonzero_owner() {
+    let minter = TestMinter::new();
+    let info = GenesisBlockInfo::new(KeyPair::gen(), 5);
+
+    let dir = minter.tmp_dir().join("nonzero_owner");
+    std::fs::create_dir(&dir).unwrap();
+    let chain = Blockchain::new(&dir.join("blklog"), &dir.join("index"));
+    chain.create_genesis_block_at(info, godcoin::get_epoch_time());
+    let incremental = chain.indexer().raw_entries();
+
+    let reindex_dir = minter.tmp_dir().join("nonzero_owner_reindex");
+    std::fs::create_dir(&reindex_dir).unwrap();
+    std::fs::copy(dir.join("blklog"), reindex_dir.join("blklog")).unwrap();
+    let chain = Blockchain::new(&reindex_dir.join("blklog"), &reindex_dir.join("index"));
+    chain.reindex(ReindexOpts { auto_trim: false });
+
+    assert_eq!(chain.get_genesis_details().unwrap().owner_id, 5);
+    assert_eq!(incremental, chain.indexer().raw_entries());
+}
+
 #[test]
 fn tx_dupe() {
     let minter = TestMinter::new();
@@ -268,6 +522,60 @@ fn tx_expiry_far_in_the_future() {
     assert_eq!(res, Err(ErrorKind::TxValidation(TxErr::TxExpired)));
 }
 
+#[test]
+fn tx_expiry_within_clock_skew_tolerance() {
+    let minter = TestMinter::new();
+    minter.chain().set_clock_skew_tolerance(10);
+    let current_time = godcoin::get_epoch_time();
+
+    let broadcast = |expiry| {
+        let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+            base: create_tx_header_with_expiry("0.00000 TEST", expiry),
+            to: minter.genesis_info().owner_id,
+            amount: get_asset("10.00000 TEST"),
+            attachment: vec![],
+            attachment_name: "".to_string(),
+        }));
+        tx.append_sign(&minter.genesis_info().wallet_keys[1]);
+        tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+        let txid = tx.calc_txid();
+        (txid, minter.send_req(rpc::Request::Broadcast(tx)).unwrap())
+    };
+
+    // Margins leave room for the clock to advance during the test
+    let (_, res) = broadcast(current_time - 10);
+    assert_eq!(res, Err(ErrorKind::TxValidation(TxErr::TxExpired)));
+
+    let (txid, res) = broadcast(current_time - 8);
+    assert_eq!(res, Ok(rpc::Response::Broadcast));
+    // The txid must stay indexed until the tolerance elapses to prevent replays
+    assert!(minter.chain().indexer().has_txid(&txid));
+    minter.chain().indexer().purge_expired_txids();
+    assert!(minter.chain().indexer().has_txid(&txid));
+
+    let (_, res) = broadcast(current_time + constants::TX_MAX_EXPIRY_TIME + 10);
+    assert_eq!(res, Ok(rpc::Response::Broadcast));
+
+    let (_, res) = broadcast(current_time + constants::TX_MAX_EXPIRY_TIME + 12);
+    assert_eq!(res, Err(ErrorKind::TxValidation(TxErr::TxExpired)));
+}
+
+#[test]
+fn expired_txids_purged_on_block_production() {
+    let minter = TestMinter::new();
+    let indexer = minter.chain().indexer();
+    let current_time = godcoin::get_epoch_time();
+
+    let expired_id = TxId::from_digest(Digest::from_slice(&[0; 32]).unwrap());
+    let active_id = TxId::from_digest(Digest::from_slice(&[1; 32]).unwrap());
+    indexer.insert_txid(&expired_id, current_time - 3600);
+    indexer.insert_txid(&active_id, current_time + 3600);
+
+    minter.produce_block().unwrap();
+    assert!(!indexer.has_txid(&expired_id));
+    assert!(indexer.has_txid(&active_id));
+}
+
 #[test]
 fn tx_too_many_signatures_err() {
     let minter = TestMinter::new();
@@ -285,6 +593,32 @@ fn tx_too_many_signatures_err() {
     assert_eq!(res, Err(ErrorKind::TxValidation(TxErr::TooManySignatures)));
 }
 
+#[test]
+fn tx_duplicate_signature_err() {
+    let minter = TestMinter::new();
+
+    // The owner wallet requires signatures from two of its keys
+    let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+        base: create_tx_header("0.00000 TEST"),
+        to: minter.genesis_info().owner_id,
+        amount: get_asset("10.00000 TEST"),
+        attachment: vec![],
+        attachment_name: "".to_string(),
+    }));
+    tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+    tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+
+    let res = minter
+        .send_req(rpc::Request::Broadcast(tx.clone()))
+        .unwrap();
+    assert_eq!(res, Err(ErrorKind::TxValidation(TxErr::DuplicateSignature)));
+
+    // A duplicate is rejected even when the threshold is otherwise met
+    tx.append_sign(&minter.genesis_info().wallet_keys[1]);
+    let res = minter.send_req(rpc::Request::Broadcast(tx)).unwrap();
+    assert_eq!(res, Err(ErrorKind::TxValidation(TxErr::DuplicateSignature)));
+}
+
 #[test]
 fn tx_with_bad_chain_id() {
     fn manual_sign(key_pair: &KeyPair, tx: &mut TxVariant, chain_id: [u8; 2]) {
@@ -358,3 +692,360 @@ fn tx_with_bad_chain_id() {
         }
     }
 }
+
+#[test]
+fn checkpoint_skips_block_signature_verification() {
+    let minter = TestMinter::new();
+    let chain = minter.chain();
+    let minter_key = &minter.genesis_info().minter_key;
+
+    // Signs the block with a signature over a different header, the signing key is still the
+    // expected minter key.
+    let create_child = |parent: &Block, valid_sig: bool| {
+        let mut child = match parent {
+            Block::V0(block) => block.new_child(vec![]),
+        };
+        if valid_sig {
+            child.sign(minter_key);
+        } else {
+            let mut other = child.clone();
+            match &mut other {
+                Block::V0(block) => block.header.timestamp += 1,
+            }
+            other.sign(minter_key);
+            match &mut child {
+                Block::V0(block) => block.signer = other.signer().cloned(),
+            }
+        }
+        child
+    };
+
+    let block = create_child(&chain.get_chain_head(), false);
+    assert_eq!(
+        chain.insert_block(block.clone()),
+        Err(blockchain::BlockErr::InvalidSignature)
+    );
+
+    // Hash linkage is still verified for checkpointed blocks
+    chain.set_checkpoints(vec![(3, Digest::from_slice(&[0; 32]).unwrap())]);
+    let mut bad_prev_hash = block.clone();
+    match &mut bad_prev_hash {
+        Block::V0(block) => block.header.previous_hash = Digest::from_slice(&[0; 32]).unwrap(),
+    }
+    assert_eq!(
+        chain.insert_block(bad_prev_hash),
+        Err(blockchain::BlockErr::InvalidPrevHash)
+    );
+
+    // Blocks at a checkpoint height must match the checkpoint hash
+    chain.set_checkpoints(vec![(2, Digest::from_slice(&[0; 32]).unwrap())]);
+    assert_eq!(
+        chain.insert_block(create_child(&chain.get_chain_head(), true)),
+        Err(blockchain::BlockErr::CheckpointMismatch)
+    );
+
+    chain.set_checkpoints(vec![(2, block.calc_header_hash())]);
+    chain.insert_block(block.clone()).unwrap();
+    assert_eq!(chain.get_chain_height(), 2);
+
+    // Blocks below a checkpoint are only trusted once they are proven to be its ancestors
+    let block_3 = create_child(&block, false);
+    let block_4 = create_child(&block_3, false);
+    chain.set_checkpoints(vec![
+        (2, block.calc_header_hash()),
+        (4, block_4.calc_header_hash()),
+    ]);
+    assert_eq!(
+        chain.insert_block(block_3.clone()),
+        Err(blockchain::BlockErr::InvalidSignature)
+    );
+    chain.insert_block_batch(vec![block_3, block_4]).unwrap();
+    assert_eq!(chain.get_chain_height(), 4);
+
+    // Blocks above the highest checkpoint are fully verified
+    assert_eq!(
+        chain.insert_block(create_child(&chain.get_chain_head(), false)),
+        Err(blockchain::BlockErr::InvalidSignature)
+    );
+    chain
+        .insert_block(create_child(&chain.get_chain_head(), true))
+        .unwrap();
+    assert_eq!(chain.get_chain_height(), 5);
+}
+
+#[test]
+fn fixed_block_reward_schedule() {
+    let minter = TestMinter::new();
+    let chain = minter.chain();
+    let owner_id = minter.genesis_info().owner_id;
+    chain.set_reward_schedule(RewardSchedule::Fixed(get_asset("2.00000 TEST")));
+
+    let supply = chain.get_properties().token_supply;
+    let owner_bal = chain.get_account(owner_id, &[]).unwrap().balance;
+    for i in 1..=3 {
+        minter.produce_block().unwrap();
+        let head = chain.get_chain_head();
+        assert_eq!(head.rewards(), get_asset("2.00000 TEST"));
+
+        let minted = get_asset("2.00000 TEST")
+            .checked_mul(Asset::new(i * 100_000))
+            .unwrap();
+        assert_eq!(
+            chain.get_properties().token_supply,
+            supply.checked_add(minted).unwrap()
+        );
+        assert_eq!(
+            chain.get_account(owner_id, &[]).unwrap().balance,
+            owner_bal.checked_add(minted).unwrap()
+        );
+    }
+}
+
+#[test]
+fn halving_block_reward_schedule() {
+    let mut minter = TestMinter::new();
+    let owner_id = minter.genesis_info().owner_id;
+    minter.chain().set_reward_schedule(RewardSchedule::Halving {
+        initial: get_asset("10.00000 TEST"),
+        interval: 3,
+    });
+
+    let supply = minter.chain().get_properties().token_supply;
+    let expected = [
+        (2, "10.00000 TEST"),
+        (3, "5.00000 TEST"),
+        (4, "5.00000 TEST"),
+        (5, "5.00000 TEST"),
+        (6, "2.50000 TEST"),
+    ];
+    let mut minted = Asset::default();
+    for &(height, reward) in &expected {
+        minter.produce_block().unwrap();
+        let block = minter.chain().get_block(height).unwrap();
+        assert_eq!(block.rewards(), get_asset(reward));
+        minted = minted.checked_add(get_asset(reward)).unwrap();
+    }
+    assert_eq!(
+        minter.chain().get_properties().token_supply,
+        supply.checked_add(minted).unwrap()
+    );
+
+    // Transaction fees are paid to the owner on top of the block reward
+    let acc = minter.create_default_account(1, true);
+    let block = minter.chain().get_block(7).unwrap();
+    assert_eq!(block.rewards(), get_asset("4.50000 TEST"));
+    minted = minted.checked_add(get_asset("2.50000 TEST")).unwrap();
+    assert_eq!(
+        minter.chain().get_properties().token_supply,
+        supply.checked_add(minted).unwrap()
+    );
+    assert!(minter.chain().get_account(acc.id, &[]).is_some());
+
+    // The minted rewards are restored when reindexing
+    let owner_bal = minter.chain().get_account(owner_id, &[]).unwrap().balance;
+    minter.unindexed();
+    minter.reindex();
+    assert_eq!(
+        minter.chain().get_properties().token_supply,
+        supply.checked_add(minted).unwrap()
+    );
+    assert_eq!(
+        minter.chain().get_account(owner_id, &[]).unwrap().balance,
+        owner_bal
+    );
+}
+
+#[test]
+fn block_reward_must_match_schedule() {
+    let minter = TestMinter::new();
+    let chain = minter.chain();
+    let minter_key = &minter.genesis_info().minter_key;
+
+    let create_child = |reward: &str| {
+        let head = chain.get_chain_head();
+        let mut child = match head.as_ref() {
+            Block::V0(block) => block.new_child_with_reward(vec![], get_asset(reward)),
+        };
+        child.sign(minter_key);
+        child
+    };
+
+    // No reward is minted without a configured schedule
+    assert_eq!(
+        chain.insert_block(create_child("1.00000 TEST")),
+        Err(blockchain::BlockErr::InvalidReward)
+    );
+    chain.insert_block(create_child("0.00000 TEST")).unwrap();
+
+    chain.set_reward_schedule(RewardSchedule::Fixed(get_asset("1.00000 TEST")));
+    let supply = chain.get_properties().token_supply;
+    for reward in &[
+        "1.00001 TEST",
+        "100.00000 TEST",
+        "0.99999 TEST",
+        "0.00000 TEST",
+    ] {
+        assert_eq!(
+            chain.insert_block(create_child(reward)),
+            Err(blockchain::BlockErr::InvalidReward)
+        );
+    }
+    assert_eq!(chain.get_chain_height(), 2);
+    assert_eq!(chain.get_properties().token_supply, supply);
+
+    chain.insert_block(create_child("1.00000 TEST")).unwrap();
+    assert_eq!(chain.get_chain_height(), 3);
+    assert_eq!(
+        chain.get_properties().token_supply,
+        supply.checked_add(get_asset("1.00000 TEST")).unwrap()
+    );
+}
+
+#[test]
+fn prune_destroyed_accounts() {
+    let minter = TestMinter::new();
+    let chain = minter.chain();
+
+    let key = KeyPair::gen();
+    let acc = {
+        let mut acc = Account::create_default(
+            1,
+            Permissions {
+                threshold: 1,
+                keys: vec![key.0.clone()],
+            },
+        );
+        acc.script = script::Builder::new()
+            .push(
+                script::FnBuilder::new(0, OpFrame::OpDefine(vec![script::Arg::AccountId]))
+                    .push(OpFrame::OpDestroy),
+            )
+            .build()
+            .unwrap();
+        acc.balance = get_asset("4.00000 TEST");
+        minter.create_account(acc, "2.00000 TEST", true)
+    };
+    let owner_id = minter.genesis_info().owner_id;
+
+    // Nothing is pruned while the account is active
+    assert_eq!(chain.prune_destroyed_accounts(), 0);
+    assert!(chain.get_account(acc.id, &[]).is_some());
+
+    let tx = {
+        let mut tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+            base: create_tx_header("1.00000 TEST"),
+            from: acc.id,
+            call_fn: 0,
+            args: {
+                let mut args = vec![];
+                args.push_u64(owner_id);
+                args
+            },
+            amount: get_asset("0.00000 TEST"),
+            memo: vec![],
+        }));
+        tx.append_sign(&key);
+        tx
+    };
+    let res = minter.send_req(rpc::Request::Broadcast(tx));
+    assert_eq!(res, Some(Ok(rpc::Response::Broadcast)));
+    minter.produce_block().unwrap();
+
+    let destroyed_acc = chain.get_account(acc.id, &[]).unwrap();
+    assert!(destroyed_acc.destroyed);
+    assert_eq!(destroyed_acc.balance, Asset::new(0));
+
+    assert_eq!(chain.prune_destroyed_accounts(), 1);
+    assert!(chain.get_account(acc.id, &[]).is_none());
+    assert!(chain.get_account(owner_id, &[]).is_some());
+    assert_eq!(chain.prune_destroyed_accounts(), 0);
+
+    // The pruned ID can't be reused for a new account
+    let mut new_acc = Account::create_default(
+        acc.id,
+        Permissions {
+            threshold: 1,
+            keys: vec![KeyPair::gen().0],
+        },
+    );
+    new_acc.balance = get_asset("4.00000 TEST");
+    let res = minter.try_create_account(new_acc, "2.00000 TEST", false);
+    assert_eq!(
+        res,
+        Err(ErrorKind::TxValidation(TxErr::AccountAlreadyExists))
+    );
+}
+
+#[test]
+fn get_block_header_matches_block() {
+    let minter = TestMinter::new();
+    for _ in 0..3 {
+        minter.produce_block().unwrap();
+    }
+
+    let chain = minter.chain();
+    let height = chain.get_chain_height();
+    for h in 0..=height {
+        let header = chain.get_block_header(h).unwrap();
+        assert_eq!(header, chain.get_block(h).unwrap().header());
+    }
+    assert!(chain.get_block_header(height + 1).is_none());
+}
+
+#[test]
+fn get_accounts_returns_existing_accounts() {
+    let minter = TestMinter::new();
+    let accounts: Vec<Account> = (1..=3)
+        .map(|id| minter.create_default_account(id, true))
+        .collect();
+
+    let chain = minter.chain();
+    let found = chain.get_accounts(&[1, 2, 3, 100, 101]);
+    assert_eq!(found.len(), 3);
+    for acc in &accounts {
+        assert_eq!(found.get(&acc.id), Some(acc));
+    }
+    assert!(!found.contains_key(&100));
+    assert!(!found.contains_key(&101));
+
+    assert!(chain.get_accounts(&[]).is_empty());
+}
+
+#[test]
+fn height_at_or_before_timestamp() {
+    let minter = TestMinter::new();
+    let dir = minter.tmp_dir().join("height_at_or_before");
+    std::fs::create_dir(&dir).unwrap();
+    let chain = Blockchain::new(&dir.join("blklog"), &dir.join("index"));
+    assert_eq!(chain.height_at_or_before(u64::MAX), None);
+
+    let minter_key = KeyPair::gen();
+    chain.create_genesis_block_at(GenesisBlockInfo::new(minter_key.clone(), 0), 1000);
+    for &timestamp in &[1003, 1003, 1006, 1009] {
+        let head = chain.get_chain_head();
+        let block = match head.as_ref() {
+            Block::V0(block) => {
+                let mut b = block.new_child(vec![]);
+                match &mut b {
+                    Block::V0(child) => child.header.timestamp = timestamp,
+                }
+                b.sign(&minter_key);
+                b
+            }
+        };
+        chain.insert_block(block).unwrap();
+    }
+    assert_eq!(chain.get_chain_height(), 4);
+
+    assert_eq!(chain.height_at_or_before(0), None);
+    assert_eq!(chain.height_at_or_before(999), None);
+    assert_eq!(chain.height_at_or_before(1000), Some(0));
+    assert_eq!(chain.height_at_or_before(1002), Some(0));
+    // Blocks with the same timestamp resolve to the highest of them
+    assert_eq!(chain.height_at_or_before(1003), Some(2));
+    assert_eq!(chain.height_at_or_before(1005), Some(2));
+    assert_eq!(chain.height_at_or_before(1006), Some(3));
+    assert_eq!(chain.height_at_or_before(1008), Some(3));
+    assert_eq!(chain.height_at_or_before(1009), Some(4));
+    assert_eq!(chain.height_at_or_before(u64::MAX), Some(4));
+}
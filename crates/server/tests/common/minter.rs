@@ -1,4 +1,4 @@
-use super::create_tx_header;
+use super::{create_tx_header, get_asset};
 use godcoin::{
     blockchain::{GenesisBlockInfo, ReindexOpts},
     prelude::*,
@@ -11,7 +11,7 @@ use std::{
     io::Cursor,
     net::SocketAddr,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{atomic::AtomicUsize, Arc},
 };
 use tokio_tungstenite::tungstenite::Message;
 
@@ -67,11 +67,25 @@ impl TestMinter {
         }
 
         let sub_pool = SubscriptionPool::default();
-        let minter = Minter::new(Arc::clone(&chain), minter_key, sub_pool.clone(), false);
+        let tx_sub_pool = SubscriptionPool::default();
+        let minter = Minter::new(
+            Arc::clone(&chain),
+            minter_key,
+            sub_pool.clone(),
+            tx_sub_pool.clone(),
+            false,
+        );
         let data = ServerData {
             chain,
             minter,
             sub_pool,
+            tx_sub_pool,
+            max_connections: None,
+            active_connections: Arc::new(AtomicUsize::new(0)),
+            allowed_origins: None,
+            max_get_blocks: godcoin_server::DEFAULT_MAX_GET_BLOCKS,
+            slow_request_threshold: None,
+            socket_opts: Default::default(),
         };
         Self(data, info, tmp_dir, true)
     }
@@ -101,10 +115,19 @@ impl TestMinter {
         chain.reindex(ReindexOpts { auto_trim: true });
         let key = self.1.minter_key.clone();
         let pool = self.0.sub_pool.clone();
-        self.0.minter = Minter::new(chain, key, pool, false);
+        let tx_pool = self.0.tx_sub_pool.clone();
+        self.0.minter = Minter::new(chain, key, pool, tx_pool, false);
         self.3 = true;
     }
 
+    pub fn data(&self) -> &ServerData {
+        &self.0
+    }
+
+    pub fn data_mut(&mut self) -> &mut ServerData {
+        &mut self.0
+    }
+
     pub fn chain(&self) -> &Blockchain {
         &self.0.chain
     }
@@ -117,8 +140,18 @@ impl TestMinter {
         &self.1
     }
 
+    pub fn tmp_dir(&self) -> &Path {
+        &self.2
+    }
+
     pub fn produce_block(&self) -> Result<(), blockchain::BlockErr> {
-        self.0.minter.force_produce_block(true)
+        self.0
+            .minter
+            .force_produce_block(true)
+            .map_err(|e| match e {
+                ProduceErr::Block(e) => e,
+                _ => panic!("failed to produce block: {:?}", e),
+            })
     }
 
     pub fn create_account(&self, new_acc: Account, fee: &str, produce_block: bool) -> Account {
@@ -126,6 +159,20 @@ impl TestMinter {
             .unwrap()
     }
 
+    /// Creates an account with the default script and a single generated key, funded with 4 TEST
+    /// by the owner wallet for a fee of 2 TEST.
+    pub fn create_default_account(&self, id: AccountId, produce_block: bool) -> Account {
+        let mut acc = Account::create_default(
+            id,
+            Permissions {
+                threshold: 1,
+                keys: vec![KeyPair::gen().0],
+            },
+        );
+        acc.balance = get_asset("4.00000 TEST");
+        self.create_account(acc, "2.00000 TEST", produce_block)
+    }
+
     pub fn try_create_account(
         &self,
         new_acc: Account,
@@ -200,7 +200,38 @@ fn create_acc_tx_fail_script_too_large() {
     assert_eq!(
         res,
         Some(Err(net::ErrorKind::TxValidation(
-            blockchain::TxErr::TxTooLarge,
+            blockchain::TxErr::ScriptTooLarge,
+        )))
+    );
+}
+
+#[test]
+fn create_acc_tx_fail_account_too_large() {
+    let minter = TestMinter::new();
+
+    // The script and permissions are each within their limits
+    let mut account = Account::create_default(
+        100,
+        Permissions {
+            threshold: 1,
+            keys: (0..MAX_PERM_KEYS).map(|_| KeyPair::gen().0).collect(),
+        },
+    );
+    account.balance = get_asset("2.00000 TEST");
+    account.script = Script::new(vec![0; MAX_SCRIPT_BYTE_SIZE]);
+    assert!(account.serialized_size() > MAX_ACCOUNT_BYTE_SIZE);
+
+    let tx = TxVariant::V0(TxVariantV0::CreateAccountTx(CreateAccountTx {
+        base: create_tx_header("1.00000 TEST"),
+        creator: minter.genesis_info().owner_id,
+        account,
+    }));
+
+    let res = minter.send_req(rpc::Request::Broadcast(tx));
+    assert_eq!(
+        res,
+        Some(Err(net::ErrorKind::TxValidation(
+            blockchain::TxErr::AccountTooLarge,
         )))
     );
 }
@@ -99,3 +99,47 @@ fn mint_tx_updates_balances() {
         .balance;
     assert_eq!(bal, expected_bal);
 }
+
+#[test]
+fn mint_tx_to_other_account() {
+    let minter = TestMinter::new();
+    let owner_id = minter.genesis_info().owner_id;
+    let acc = minter.create_default_account(1, true);
+    let owner_bal = minter.chain().get_account(owner_id, &[]).unwrap().balance;
+
+    let create_tx = |to: AccountId| {
+        let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+            base: create_tx_header("0.00000 TEST"),
+            to,
+            amount: get_asset("10.00000 TEST"),
+            attachment: vec![],
+            attachment_name: "".to_string(),
+        }));
+        tx.append_sign(&minter.genesis_info().wallet_keys[1]);
+        tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+        tx
+    };
+
+    let res = minter.send_req(rpc::Request::Broadcast(create_tx(0xFFFF)));
+    assert_eq!(
+        res,
+        Some(Err(net::ErrorKind::TxValidation(
+            blockchain::TxErr::AccountNotFound
+        )))
+    );
+
+    let res = minter.send_req(rpc::Request::Broadcast(create_tx(acc.id)));
+    assert_eq!(res, Some(Ok(rpc::Response::Broadcast)));
+    minter.produce_block().unwrap();
+
+    let chain = minter.chain();
+    assert_eq!(
+        chain.get_properties().token_supply,
+        get_asset("1010.00000 TEST")
+    );
+    assert_eq!(
+        chain.get_account(acc.id, &[]).unwrap().balance,
+        get_asset("14.00000 TEST")
+    );
+    assert_eq!(chain.get_account(owner_id, &[]).unwrap().balance, owner_bal);
+}
@@ -183,3 +183,58 @@ fn owner_tx_accept_mint_tokens() {
         assert_eq!(bal, get_asset("1004.00000 TEST"));
     }
 }
+
+#[cfg(unix)]
+#[test]
+fn owner_tx_minter_key_reload_on_sighup() {
+    use std::{fs, process::Command, time::Duration};
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let minter = TestMinter::new();
+        let key_file = minter.tmp_dir().join("minter_key");
+        fs::write(&key_file, &*minter.genesis_info().minter_key.1.to_wif()).unwrap();
+
+        {
+            let key_file = key_file.clone();
+            godcoin_server::reload_minter_key_on_sighup(minter.minter().clone(), move || {
+                let wif = fs::read_to_string(&key_file).map_err(|e| e.to_string())?;
+                PrivateKey::from_wif(&wif).map_err(|e| e.to_string())
+            });
+        }
+
+        let minter_key = KeyPair::gen();
+        let tx = {
+            let mut tx = TxVariant::V0(TxVariantV0::OwnerTx(OwnerTx {
+                base: create_tx_header("0.00000 TEST"),
+                minter: minter_key.0.clone(),
+                wallet: minter.genesis_info().owner_id,
+            }));
+            tx.append_sign(&minter.genesis_info().wallet_keys[3]);
+            tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+            tx
+        };
+        let res = minter.send_req(rpc::Request::Broadcast(tx)).unwrap();
+        assert_eq!(res, Ok(rpc::Response::Broadcast));
+        minter.produce_block().unwrap();
+
+        // The running minter still holds the old key
+        let res = minter.produce_block();
+        assert_eq!(res.unwrap_err(), blockchain::BlockErr::InvalidSignature);
+
+        fs::write(&key_file, &*minter_key.1.to_wif()).unwrap();
+        let status = Command::new("kill")
+            .arg("-HUP")
+            .arg(std::process::id().to_string())
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let mut attempts = 0;
+        while minter.produce_block().is_err() {
+            attempts += 1;
+            assert!(attempts < 100, "minter key was not reloaded");
+            tokio::time::delay_for(Duration::from_millis(50)).await;
+        }
+    });
+}
@@ -0,0 +1,110 @@
+use godcoin::prelude::*;
+use godcoin_server::prelude::*;
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener},
+    thread,
+};
+
+mod common;
+pub use common::*;
+
+#[test]
+fn produce_block_with_remote_signer() {
+    let minter = TestMinter::new();
+    let minter_key = minter.genesis_info().minter_key.clone();
+    let addr = start_stub_signer(minter_key.clone());
+
+    let signer = RemoteSigner::new(addr, minter_key.0.clone());
+    assert!(minter.minter().set_signer(signer));
+    minter.produce_block().unwrap();
+
+    let chain = minter.chain();
+    let block = chain.get_block(chain.get_chain_height()).unwrap();
+    let signer = block.signer().unwrap();
+    assert_eq!(signer.pub_key, minter_key.0);
+    assert!(signer.verify(block.calc_header_hash().as_ref()));
+}
+
+#[test]
+fn reject_remote_signer_with_invalid_signature() {
+    let minter = TestMinter::new();
+    let minter_key = minter.genesis_info().minter_key.clone();
+
+    // The remote signer must respond with a signature from the configured key
+    let addr = start_stub_signer(KeyPair::gen());
+    let signer = RemoteSigner::new(addr, minter_key.0.clone());
+    assert!(signer.try_sign(b"hello world").is_err());
+
+    // Signers that don't match the chain owner's minter key are rejected
+    let other_key = KeyPair::gen();
+    let addr = start_stub_signer(other_key.clone());
+    let signer = RemoteSigner::new(addr, other_key.0);
+    assert!(signer.try_sign(b"hello world").is_ok());
+    assert!(!minter.minter().set_signer(signer));
+}
+
+#[test]
+fn keep_pending_txs_when_remote_signer_fails() {
+    let minter = TestMinter::new();
+    let info = minter.genesis_info();
+    let height = minter.chain().get_chain_height();
+
+    // Nothing listens on the address once the listener is dropped
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let signer = RemoteSigner::new(addr, info.minter_key.0.clone());
+    assert!(minter.minter().set_signer(signer));
+
+    let tx = {
+        let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+            base: create_tx_header("0.00000 TEST"),
+            to: info.owner_id,
+            amount: get_asset("10.00000 TEST"),
+            attachment: vec![],
+            attachment_name: "".to_string(),
+        }));
+        tx.append_sign(&info.wallet_keys[1]);
+        tx.append_sign(&info.wallet_keys[0]);
+        tx
+    };
+    let res = minter
+        .send_req(rpc::Request::Broadcast(tx.clone()))
+        .unwrap();
+    assert_eq!(res, Ok(rpc::Response::Broadcast));
+
+    match minter.minter().force_produce_block(true) {
+        Err(ProduceErr::Sign(_)) => {}
+        res => panic!("Assertion failed, got {:?}", res),
+    }
+    assert_eq!(minter.chain().get_chain_height(), height);
+    assert_eq!(minter.minter().get_pending(), vec![tx.clone()]);
+
+    assert!(minter.minter().set_minter_key(info.minter_key.clone()));
+    minter.produce_block().unwrap();
+    assert!(minter.minter().get_pending().is_empty());
+    let block = minter.chain().get_block(height + 1).unwrap();
+    assert_eq!(block.receipts()[0].tx, tx);
+}
+
+/// Starts a remote signer that signs every request with the provided key.
+fn start_stub_signer(key: KeyPair) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut len = [0; 4];
+            stream.read_exact(&mut len).unwrap();
+            let mut msg = vec![0; u32::from_be_bytes(len) as usize];
+            stream.read_exact(&mut msg).unwrap();
+
+            let mut buf = vec![];
+            buf.push_sig_pair(&key.sign(&msg));
+            stream.write_all(&buf).unwrap();
+        }
+    });
+    addr
+}
@@ -1,15 +1,26 @@
 use futures::{channel::*, prelude::*};
 use godcoin::{
     constants,
+    crypto::Digest,
     prelude::{net::ErrorKind, *},
 };
-use godcoin_server::client::WsClient;
+use godcoin_server::{
+    client::{process_ws_msg, WsClient},
+    metrics::NET_DESERIALIZE_ERRORS,
+    prelude::*,
+    ServerData,
+};
 use std::{
-    io::Cursor,
-    net::SocketAddr,
-    sync::atomic::{AtomicU64, Ordering},
+    io::{Cursor, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
 };
-use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::{self, http, HandshakeError, Message};
 
 mod common;
 pub use common::*;
@@ -33,6 +44,241 @@ fn successful_broadcast() {
     assert_eq!(res, Ok(rpc::Response::Broadcast));
 }
 
+#[test]
+fn admission_policy_rejects_broadcast() {
+    struct MinFee(Asset);
+
+    impl TxAdmissionPolicy for MinFee {
+        fn check(&self, _: &Blockchain, tx: &TxVariant) -> Result<(), ErrorKind> {
+            let fee = match tx {
+                TxVariant::V0(tx) => tx.fee,
+            };
+            if fee < self.0 {
+                return Err(ErrorKind::InvalidRequest);
+            }
+            Ok(())
+        }
+    }
+
+    let minter = TestMinter::new();
+    minter
+        .minter()
+        .set_admission_policy(MinFee(get_asset("1.00000 TEST")));
+
+    let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+        base: create_tx_header("0.00000 TEST"),
+        to: minter.genesis_info().owner_id,
+        amount: get_asset("10.00000 TEST"),
+        attachment: vec![],
+        attachment_name: "".to_string(),
+    }));
+    tx.append_sign(&minter.genesis_info().wallet_keys[1]);
+    tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+
+    let res = minter
+        .send_req(rpc::Request::Broadcast(tx.clone()))
+        .unwrap();
+    assert_eq!(res, Err(ErrorKind::InvalidRequest));
+
+    minter.minter().set_admission_policy(AcceptAll);
+    let res = minter.send_req(rpc::Request::Broadcast(tx)).unwrap();
+    assert_eq!(res, Ok(rpc::Response::Broadcast));
+}
+
+#[test]
+fn slow_requests_are_logged() {
+    struct SlowPolicy;
+
+    impl TxAdmissionPolicy for SlowPolicy {
+        fn check(&self, _: &Blockchain, _: &TxVariant) -> Result<(), ErrorKind> {
+            thread::sleep(Duration::from_millis(50));
+            Ok(())
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct CaptureWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for CaptureWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut minter = TestMinter::new();
+    minter.data_mut().slow_request_threshold = Some(Duration::from_millis(25));
+    minter.minter().set_admission_policy(SlowPolicy);
+
+    let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+        base: create_tx_header("0.00000 TEST"),
+        to: minter.genesis_info().owner_id,
+        amount: get_asset("10.00000 TEST"),
+        attachment: vec![],
+        attachment_name: "".to_string(),
+    }));
+    tx.append_sign(&minter.genesis_info().wallet_keys[1]);
+    tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+
+    let writer = CaptureWriter::default();
+    let subscriber = {
+        let writer = writer.clone();
+        tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish()
+    };
+    tracing::subscriber::with_default(subscriber, || {
+        let res = minter.send_req(rpc::Request::GetProperties).unwrap();
+        assert!(res.is_ok());
+        assert!(writer.0.lock().unwrap().is_empty());
+
+        let res = minter.send_req(rpc::Request::Broadcast(tx)).unwrap();
+        assert_eq!(res, Ok(rpc::Response::Broadcast));
+    });
+
+    let logs = String::from_utf8(writer.0.lock().unwrap().clone()).unwrap();
+    assert!(logs.contains("WARN"));
+    assert!(logs.contains("Slow request"));
+    assert!(logs.contains("request=\"broadcast\""));
+}
+
+#[test]
+fn broadcast_pushes_tx_to_subscribers() {
+    let minter = TestMinter::new();
+    let (mut state, mut rx) = create_uninit_state();
+
+    let res = minter
+        .send_msg(
+            &mut state,
+            Msg {
+                id: 0,
+                body: Body::Request(rpc::Request::SubscribeTxs),
+            },
+        )
+        .unwrap()
+        .body;
+    assert_eq!(res, Body::Response(rpc::Response::SubscribeTxs));
+
+    let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+        base: create_tx_header("0.00000 TEST"),
+        to: minter.genesis_info().owner_id,
+        amount: get_asset("10.00000 TEST"),
+        attachment: vec![],
+        attachment_name: "".to_string(),
+    }));
+    tx.append_sign(&minter.genesis_info().wallet_keys[1]);
+    tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+
+    let res = minter
+        .send_req(rpc::Request::Broadcast(tx.clone()))
+        .unwrap();
+    assert_eq!(res, Ok(rpc::Response::Broadcast));
+
+    let msg = match rx.try_next().unwrap().unwrap() {
+        Message::Binary(msg) => {
+            let mut cur = Cursor::<&[u8]>::new(&msg);
+            Msg::deserialize(&mut cur).unwrap()
+        }
+        _ => panic!("Expected binary response"),
+    };
+    assert_eq!(msg.id, u32::max_value());
+    assert_eq!(
+        msg.body,
+        Body::Response(rpc::Response::TxAccepted(tx.clone()))
+    );
+
+    // Rejected transactions are not pushed
+    let res = minter.send_req(rpc::Request::Broadcast(tx)).unwrap();
+    assert!(res.is_err());
+    assert!(rx.try_next().is_err());
+}
+
+#[test]
+fn subscribe_receipts_pushes_matching_receipts() {
+    let minter = TestMinter::new();
+    let (mut state, mut rx) = create_uninit_state();
+
+    let mut send = |req| {
+        minter
+            .send_msg(
+                &mut state,
+                Msg {
+                    id: 0,
+                    body: Body::Request(req),
+                },
+            )
+            .unwrap()
+            .body
+    };
+
+    // A block filter must be set before subscribing to receipts
+    let res = send(rpc::Request::SubscribeReceipts);
+    assert_eq!(res, Body::Error(ErrorKind::InvalidRequest));
+
+    let mut filter = BlockFilter::new();
+    filter.insert(100);
+    let res = send(rpc::Request::SetBlockFilter(filter));
+    assert_eq!(res, Body::Response(rpc::Response::SetBlockFilter));
+    let res = send(rpc::Request::SubscribeReceipts);
+    assert_eq!(res, Body::Response(rpc::Response::SubscribeReceipts));
+
+    let account = {
+        let mut account = Account::create_default(
+            100,
+            Permissions {
+                threshold: 0,
+                keys: vec![],
+            },
+        );
+        account.balance = get_asset("4.00000 TEST");
+        account
+    };
+    minter.create_account(account, "2.00000 TEST", false);
+
+    let mut mint_tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+        base: create_tx_header("0.00000 TEST"),
+        to: minter.genesis_info().owner_id,
+        amount: get_asset("10.00000 TEST"),
+        attachment: vec![],
+        attachment_name: "".to_string(),
+    }));
+    mint_tx.append_sign(&minter.genesis_info().wallet_keys[1]);
+    mint_tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+    let res = minter.send_req(rpc::Request::Broadcast(mint_tx)).unwrap();
+    assert_eq!(res, Ok(rpc::Response::Broadcast));
+
+    minter.produce_block().unwrap();
+
+    let msg = match rx.try_next().unwrap().unwrap() {
+        Message::Binary(msg) => {
+            let mut cur = Cursor::<&[u8]>::new(&msg);
+            Msg::deserialize(&mut cur).unwrap()
+        }
+        _ => panic!("Expected binary response"),
+    };
+    assert_eq!(msg.id, u32::max_value());
+
+    let head = minter.chain().get_chain_head();
+    assert_eq!(head.receipts().len(), 2);
+    match msg.body {
+        Body::Response(rpc::Response::FilteredReceipts { height, receipts }) => {
+            assert_eq!(height, head.height());
+            assert_eq!(receipts.len(), 1);
+            assert!(receipts[0].touches_account(100));
+            match &receipts[0].tx {
+                TxVariant::V0(TxVariantV0::CreateAccountTx(_)) => {}
+                unexp => panic!("Expected a create account tx: {:?}", unexp),
+            }
+        }
+        unexp => panic!("Expected FilteredReceipts response: {:?}", unexp),
+    }
+}
+
 #[test]
 fn get_properties() {
     let minter = TestMinter::new();
@@ -234,6 +480,71 @@ fn get_block_filtered_all() {
     }
 }
 
+#[test]
+fn get_block_filtered_with_bloom() {
+    let send = |minter: &TestMinter, state: &mut WsClient, req: rpc::Request| {
+        minter
+            .send_msg(
+                state,
+                Msg {
+                    id: 0,
+                    body: Body::Request(req),
+                },
+            )
+            .unwrap()
+            .body
+    };
+
+    let mut state = create_uninit_state().0;
+    let minter = TestMinter::new();
+    let owner_id = minter.genesis_info().owner_id;
+
+    // Exact filters are limited to a small number of accounts
+    let mut filter = BlockFilter::new();
+    for id in 0..17 {
+        filter.insert(id + 1000);
+    }
+    let res = send(&minter, &mut state, rpc::Request::SetBlockFilter(filter));
+    assert_eq!(res, Body::Error(ErrorKind::InvalidRequest));
+
+    // Bloom filters allow watching many accounts
+    let mut bloom = BloomFilter::new(1000, 0.0001);
+    for id in 0..1000 {
+        bloom.insert(id + 1000);
+    }
+    assert!(!bloom.contains(owner_id));
+    let filter = BlockFilter::Bloom(bloom.clone());
+    let res = send(
+        &minter,
+        &mut state,
+        rpc::Request::SetBlockFilter(filter.clone()),
+    );
+    assert_eq!(res, Body::Response(rpc::Response::SetBlockFilter));
+    assert_eq!(state.filter(), Some(&filter));
+
+    // Block 1 mints tokens to the owner, which is not in the filter
+    let block = minter.chain().get_block(1).unwrap();
+    let signer = block.signer().unwrap().clone();
+    let res = send(&minter, &mut state, rpc::Request::GetBlock(1));
+    assert_eq!(
+        res,
+        Body::Response(rpc::Response::GetBlock(FilteredBlock::Header((
+            block.header(),
+            signer
+        ))))
+    );
+
+    bloom.insert(owner_id);
+    let filter = BlockFilter::Bloom(bloom);
+    let res = send(&minter, &mut state, rpc::Request::SetBlockFilter(filter));
+    assert_eq!(res, Body::Response(rpc::Response::SetBlockFilter));
+    let res = send(&minter, &mut state, rpc::Request::GetBlock(1));
+    assert_eq!(
+        res,
+        Body::Response(rpc::Response::GetBlock(FilteredBlock::Block(block)))
+    );
+}
+
 #[test]
 fn clear_block_filter() {
     let mut state = create_uninit_state().0;
@@ -376,6 +687,84 @@ fn get_full_block() {
     assert_eq!(res, Err(ErrorKind::InvalidHeight));
 }
 
+#[test]
+fn get_blocks() {
+    let mut state = create_uninit_state().0;
+    let minter = TestMinter::new();
+    minter.produce_block().unwrap();
+    minter.produce_block().unwrap();
+
+    {
+        // Unfiltered, results are in request order with missing heights left empty
+        let res = minter
+            .send_msg(
+                &mut state,
+                Msg {
+                    id: 0,
+                    body: Body::Request(rpc::Request::GetBlocks(vec![3, 0, 10, 2])),
+                },
+            )
+            .unwrap()
+            .body;
+        let get_block = |height| {
+            Some(FilteredBlock::Block(
+                minter.chain().get_block(height).unwrap(),
+            ))
+        };
+        assert_eq!(
+            res,
+            Body::Response(rpc::Response::GetBlocks(vec![
+                get_block(3),
+                get_block(0),
+                None,
+                get_block(2),
+            ]))
+        );
+    }
+
+    // Empty filter means filter everything
+    let filter = BlockFilter::new();
+    let res = minter
+        .send_msg(
+            &mut state,
+            Msg {
+                id: 0,
+                body: Body::Request(rpc::Request::SetBlockFilter(filter)),
+            },
+        )
+        .unwrap()
+        .body;
+    assert_eq!(res, Body::Response(rpc::Response::SetBlockFilter));
+
+    {
+        // Filtered
+        let res = minter
+            .send_msg(
+                &mut state,
+                Msg {
+                    id: 0,
+                    body: Body::Request(rpc::Request::GetBlocks(vec![1, 5])),
+                },
+            )
+            .unwrap()
+            .body;
+        let block = minter.chain().get_block(1).unwrap();
+        let signer = block.signer().unwrap().clone();
+        assert_eq!(
+            res,
+            Body::Response(rpc::Response::GetBlocks(vec![
+                Some(FilteredBlock::Header((block.header(), signer))),
+                None,
+            ]))
+        );
+    }
+
+    // Too many heights requested
+    let heights = vec![0; godcoin_server::DEFAULT_MAX_GET_BLOCKS + 1];
+    let res = minter.send_req(rpc::Request::GetBlocks(heights)).unwrap();
+    assert_eq!(res, Err(ErrorKind::InvalidRequest));
+}
+
 #[test]
 fn get_block_range_unfiltered() {
     let mut runtime = tokio::runtime::Runtime::new().unwrap();
@@ -425,6 +814,10 @@ fn get_block_range_unfiltered() {
                         _ => panic!("Expected a full block"),
                     }
                 }
+                Body::Response(rpc::Response::GetBlockRangeStart { count }) => {
+                    assert_eq!(height.load(Ordering::Acquire), 0);
+                    assert_eq!(count, 101);
+                }
                 Body::Response(rpc::Response::GetBlockRange) => {
                     assert_eq!(height.load(Ordering::Acquire), 101);
                 }
@@ -502,6 +895,10 @@ fn get_block_range_filter_all() {
                         _ => panic!("Expected a partial block"),
                     }
                 }
+                Body::Response(rpc::Response::GetBlockRangeStart { count }) => {
+                    assert_eq!(height.load(Ordering::Acquire), 0);
+                    assert_eq!(count, 101);
+                }
                 Body::Response(rpc::Response::GetBlockRange) => {
                     assert_eq!(height.load(Ordering::Acquire), 101);
                 }
@@ -516,6 +913,115 @@ fn get_block_range_filter_all() {
     runtime.block_on(rx).unwrap();
 }
 
+#[test]
+fn reject_block_ranges_over_limit() {
+    let mut runtime = tokio::runtime::Runtime::new().unwrap();
+    let (tx, rx) = oneshot::channel();
+
+    runtime.spawn(async {
+        let minter = TestMinter::new();
+        let (mut state, mut rx) = create_uninit_state();
+        for _ in 0..100 {
+            minter.produce_block().unwrap();
+        }
+
+        let range_req = Msg {
+            id: 123,
+            body: Body::Request(rpc::Request::GetBlockRange(0, 100)),
+        };
+
+        // The ranges can't complete while the response channel isn't being read
+        for _ in 0..godcoin_server::MAX_CONCURRENT_BLOCK_RANGES {
+            assert_eq!(minter.send_msg(&mut state, range_req.clone()), None);
+        }
+        let res = minter.send_msg(&mut state, range_req.clone()).unwrap();
+        assert_eq!(res.body, Body::Error(ErrorKind::Overloaded));
+
+        // Drain the responses to let the in progress ranges complete
+        let mut completed = 0;
+        while completed < godcoin_server::MAX_CONCURRENT_BLOCK_RANGES {
+            let msg = match rx.next().await.unwrap() {
+                Message::Binary(msg) => Msg::deserialize(&mut Cursor::<&[u8]>::new(&msg)).unwrap(),
+                _ => panic!("Expected binary response"),
+            };
+            if msg.body == Body::Response(rpc::Response::GetBlockRange) {
+                completed += 1;
+            }
+        }
+
+        // Slots are released once the ranges finish streaming
+        let mut accepted = false;
+        for _ in 0..100 {
+            if minter.send_msg(&mut state, range_req.clone()).is_none() {
+                accepted = true;
+                break;
+            }
+            tokio::time::delay_for(Duration::from_millis(10)).await;
+        }
+        assert!(accepted);
+
+        tx.send(()).unwrap();
+    });
+
+    runtime.block_on(rx).unwrap();
+}
+
+#[test]
+fn get_block_range_start_precedes_blocks() {
+    let mut runtime = tokio::runtime::Runtime::new().unwrap();
+    let (tx, rx) = oneshot::channel();
+
+    runtime.spawn(async {
+        let minter = TestMinter::new();
+        let (mut state, rx) = create_uninit_state();
+        for _ in 0..20 {
+            minter.produce_block().unwrap();
+        }
+
+        let res = minter.send_msg(
+            &mut state,
+            Msg {
+                id: 123,
+                body: Body::Request(rpc::Request::GetBlockRange(5, 15)),
+            },
+        );
+        assert_eq!(res, None);
+        std::mem::drop(state);
+
+        let msgs = rx
+            .map(|msg| {
+                let msg = match msg {
+                    Message::Binary(msg) => msg,
+                    _ => panic!("Expected binary response"),
+                };
+                let mut cur = Cursor::<&[u8]>::new(&msg);
+                Msg::deserialize(&mut cur).unwrap().body
+            })
+            .collect::<Vec<_>>()
+            .await;
+
+        // Start message, 11 blocks and the finalizer
+        assert_eq!(msgs.len(), 13);
+        assert_eq!(
+            msgs[0],
+            Body::Response(rpc::Response::GetBlockRangeStart { count: 11 })
+        );
+        for (msg, height) in msgs[1..12].iter().zip(5..=15) {
+            match msg {
+                Body::Response(rpc::Response::GetBlock(FilteredBlock::Block(block))) => {
+                    assert_eq!(block.height(), height);
+                }
+                unexp => panic!("Expected GetBlock response: {:?}", unexp),
+            }
+        }
+        assert_eq!(msgs[12], Body::Response(rpc::Response::GetBlockRange));
+
+        tx.send(()).unwrap();
+    });
+
+    runtime.block_on(rx).unwrap();
+}
+
 #[test]
 fn get_account_info() {
     let minter = TestMinter::new();
@@ -536,6 +1042,173 @@ fn get_account_info() {
     assert_eq!(res, expected);
 }
 
+#[test]
+fn list_accounts() {
+    let minter = TestMinter::new();
+    let owner_id = minter.genesis_info().owner_id;
+    for &id in &[100, 101, 102] {
+        minter.create_default_account(id, false);
+    }
+    minter.produce_block().unwrap();
+
+    let list = |start, limit| match minter
+        .send_req(rpc::Request::ListAccounts { start, limit })
+        .unwrap()
+        .unwrap()
+    {
+        rpc::Response::ListAccounts { accounts, next } => (
+            accounts.into_iter().map(|acc| acc.id).collect::<Vec<_>>(),
+            next,
+        ),
+        res => panic!("Unexpected response: {:?}", res),
+    };
+
+    let mut ids = vec![];
+    let mut start = None;
+    loop {
+        let (page, next) = list(start, 2);
+        assert!(page.len() <= 2);
+        ids.extend(page);
+        match next {
+            Some(next) => start = Some(next),
+            None => break,
+        }
+    }
+    let mut expected = vec![owner_id, 100, 101, 102];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    assert_eq!(list(Some(101), 10), (vec![101, 102], None));
+    assert_eq!(list(Some(103), 10), (vec![], None));
+
+    // The limit is capped by the server
+    let (page, _) = list(None, u16::max_value());
+    assert_eq!(page.len(), 4);
+}
+
+#[test]
+fn get_stats() {
+    let minter = TestMinter::new();
+    for &id in &[100, 101, 102] {
+        minter.create_default_account(id, false);
+    }
+    minter.produce_block().unwrap();
+    minter.produce_block().unwrap();
+    minter.produce_block().unwrap();
+
+    let get_stats = || match minter.send_req(rpc::Request::GetStats).unwrap().unwrap() {
+        rpc::Response::GetStats(stats) => stats,
+        res => panic!("Unexpected response: {:?}", res),
+    };
+
+    // The genesis block has two transactions, followed by the test minter's mint transaction and the
+    // block creating three accounts
+    let stats = get_stats();
+    assert_eq!(
+        stats,
+        ChainStats {
+            height: 4,
+            token_supply: minter.chain().get_properties().token_supply,
+            owner_id: minter.genesis_info().owner_id,
+            account_count: 4,
+            window_blocks: 5,
+            window_txs: 6,
+        }
+    );
+    assert_eq!(stats.avg_txs_per_block(), 1.2);
+    assert_eq!(minter.chain().stats(), stats);
+
+    // Cached stats are refreshed once a new block is produced
+    minter.produce_block().unwrap();
+    let stats = get_stats();
+    assert_eq!(stats.height, 5);
+    assert_eq!(stats.window_blocks, 6);
+    assert_eq!(stats.window_txs, 6);
+}
+
+#[test]
+fn get_and_drop_pending() {
+    let minter = TestMinter::new();
+    let minter_key = &minter.genesis_info().minter_key;
+    let expiry = godcoin::get_epoch_time() + 30;
+
+    let txs = (0..2)
+        .map(|_| {
+            let mut tx = TxVariant::V0(TxVariantV0::MintTx(MintTx {
+                base: create_tx_header("0.00000 TEST"),
+                to: minter.genesis_info().owner_id,
+                amount: get_asset("10.00000 TEST"),
+                attachment: vec![],
+                attachment_name: "".to_string(),
+            }));
+            tx.append_sign(&minter.genesis_info().wallet_keys[1]);
+            tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+
+            let res = minter.send_req(rpc::Request::Broadcast(tx.clone()));
+            assert_eq!(res, Some(Ok(rpc::Response::Broadcast)));
+            tx
+        })
+        .collect::<Vec<_>>();
+
+    let res = minter.send_req(rpc::Request::new_get_pending(minter_key, expiry));
+    assert_eq!(res, Some(Ok(rpc::Response::GetPending(txs.clone()))));
+
+    let txid = txs[0].calc_txid();
+    let res = minter.send_req(rpc::Request::new_drop_pending(
+        txid.clone(),
+        minter_key,
+        expiry,
+    ));
+    assert_eq!(res, Some(Ok(rpc::Response::DropPending)));
+
+    let res = minter.send_req(rpc::Request::new_get_pending(minter_key, expiry));
+    assert_eq!(
+        res,
+        Some(Ok(rpc::Response::GetPending(vec![txs[1].clone()])))
+    );
+
+    // The transaction is no longer pending
+    let res = minter.send_req(rpc::Request::new_drop_pending(txid, minter_key, expiry));
+    assert_eq!(res, Some(Err(ErrorKind::InvalidRequest)));
+
+    minter.produce_block().unwrap();
+    let receipts = minter.chain().get_chain_head().receipts().to_vec();
+    assert_eq!(receipts.len(), 1);
+    assert_eq!(receipts[0].tx, txs[1]);
+}
+
+#[test]
+fn pending_requests_require_minter_auth() {
+    let minter = TestMinter::new();
+    let minter_key = &minter.genesis_info().minter_key;
+    let current_time = godcoin::get_epoch_time();
+
+    let res = minter.send_req(rpc::Request::new_get_pending(
+        &KeyPair::gen(),
+        current_time + 30,
+    ));
+    assert_eq!(res, Some(Err(ErrorKind::Unauthorized)));
+
+    let res = minter.send_req(rpc::Request::new_get_pending(minter_key, current_time));
+    assert_eq!(res, Some(Err(ErrorKind::Unauthorized)));
+
+    let res = minter.send_req(rpc::Request::new_get_pending(
+        minter_key,
+        current_time + constants::MINTER_AUTH_MAX_EXPIRY_TIME + 30,
+    ));
+    assert_eq!(res, Some(Err(ErrorKind::Unauthorized)));
+
+    // The signature must cover the txid being dropped
+    let txid = TxId::from_digest(Digest::from_slice(&[0; 32]).unwrap());
+    let auth = match rpc::Request::new_drop_pending(txid, minter_key, current_time + 30) {
+        rpc::Request::DropPending(_, auth) => auth,
+        _ => unreachable!(),
+    };
+    let other_txid = TxId::from_digest(Digest::from_slice(&[1; 32]).unwrap());
+    let res = minter.send_req(rpc::Request::DropPending(other_txid, auth));
+    assert_eq!(res, Some(Err(ErrorKind::Unauthorized)));
+}
+
 #[test]
 fn receives_pong_after_ping() {
     let minter = TestMinter::new();
@@ -557,6 +1230,24 @@ fn receives_pong_after_ping() {
     );
 }
 
+#[test]
+fn ws_ping_counts_towards_heartbeat() {
+    let minter = TestMinter::new();
+    let (mut state, _) = create_uninit_state();
+    state.set_needs_pong(true);
+
+    // The websocket library replies to protocol level pings itself
+    let res = process_ws_msg(minter.data(), &mut state, Message::Ping(vec![1, 2, 3]));
+    assert_eq!(res, None);
+    // Protocol level pings also count towards the heartbeat
+    assert!(!state.needs_pong().load(Ordering::Acquire));
+
+    state.set_needs_pong(true);
+    let res = process_ws_msg(minter.data(), &mut state, Message::Pong(vec![1, 2, 3]));
+    assert_eq!(res, None);
+    assert!(!state.needs_pong().load(Ordering::Acquire));
+}
+
 #[test]
 fn error_with_bytes_remaining() {
     let minter = TestMinter::new();
@@ -607,6 +1298,35 @@ fn eof_returns_max_u32_id() {
     assert_eq!(res.body, Body::Error(ErrorKind::Io));
 }
 
+#[test]
+fn deserialize_errors_are_counted() {
+    let minter = TestMinter::new();
+    let start = NET_DESERIALIZE_ERRORS.get();
+
+    let res = minter
+        .send_bin_msg(&mut create_uninit_state().0, vec![0xFF; 3])
+        .unwrap();
+    assert_eq!(res.body, Body::Error(ErrorKind::Io));
+
+    let buf = {
+        let req = Msg {
+            id: 123456789,
+            body: Body::Request(rpc::Request::GetProperties),
+        };
+        let mut buf = Vec::with_capacity(4096);
+        req.serialize(&mut buf);
+        buf.push(0);
+        buf
+    };
+    let res = minter
+        .send_bin_msg(&mut create_uninit_state().0, buf)
+        .unwrap();
+    assert_eq!(res.body, Body::Error(ErrorKind::BytesRemaining));
+
+    // Other tests running concurrently may also increment the counter
+    assert!(NET_DESERIALIZE_ERRORS.get() - start >= 2);
+}
+
 #[test]
 fn response_id_matches_request() {
     let minter = TestMinter::new();
@@ -640,6 +1360,168 @@ fn response_id_matches_request() {
     assert_eq!(res, expected);
 }
 
+#[test]
+fn reject_connections_over_limit() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let minter = TestMinter::new();
+    let addr = get_unused_addr();
+    let data = Arc::new(ServerData {
+        max_connections: Some(1),
+        ..minter.data().clone()
+    });
+    runtime.spawn(async move { godcoin_server::start_server(addr, data) });
+
+    let mut buf = [0; 1];
+    let mut first = connect_with_retry(addr);
+    let mut second = TcpStream::connect(addr).unwrap();
+
+    // The connection over the limit is closed without any data being sent
+    second
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(second.read(&mut buf).unwrap(), 0);
+
+    // The first connection remains open waiting for a handshake
+    first
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+    assert!(first.read(&mut buf).is_err());
+}
+
+#[test]
+fn reject_disallowed_origins() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let minter = TestMinter::new();
+    let addr = get_unused_addr();
+    let data = Arc::new(ServerData {
+        allowed_origins: Some(Arc::new(vec!["https://wallet.godcoin.gold".to_owned()])),
+        ..minter.data().clone()
+    });
+    runtime.spawn(async move { godcoin_server::start_server(addr, data) });
+
+    let connect = |origin: &str| {
+        let req = http::Request::builder()
+            .uri(format!("ws://{}", addr))
+            .header(http::header::ORIGIN, origin)
+            .body(())
+            .unwrap();
+        tungstenite::client(req, connect_with_retry(addr))
+    };
+
+    let (mut ws, _) = connect("https://wallet.godcoin.gold").unwrap();
+    ws.close(None).unwrap();
+
+    match connect("https://evil.example.com") {
+        Err(HandshakeError::Failure(tungstenite::Error::Http(status))) => {
+            assert_eq!(status, http::StatusCode::FORBIDDEN);
+        }
+        _ => panic!("Expected the handshake to be rejected"),
+    }
+}
+
+#[test]
+fn rebind_after_restart() {
+    let minter = TestMinter::new();
+    let addr = get_unused_addr();
+    let data = Arc::new(ServerData {
+        max_connections: Some(0),
+        ..minter.data().clone()
+    });
+    let mut buf = [0; 1];
+
+    // Connections over the limit are closed by the server, leaving the server's side of the
+    // connection in TIME_WAIT after the server is stopped
+    {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let data = Arc::clone(&data);
+        runtime.spawn(async move { godcoin_server::start_server(addr, data) });
+
+        let mut stream = connect_with_retry(addr);
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        assert_eq!(stream.read(&mut buf).unwrap(), 0);
+    }
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.spawn(async move { godcoin_server::start_server(addr, data) });
+
+    let mut stream = connect_with_retry(addr);
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(stream.read(&mut buf).unwrap(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn get_properties_over_unix_socket() {
+    use std::os::unix::net::UnixStream;
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let minter = TestMinter::new();
+    let path = minter.tmp_dir().join("godcoin.sock");
+    let data = Arc::new(minter.data().clone());
+    {
+        let path = path.clone();
+        runtime.spawn(async move { godcoin_server::start_unix_server(path, data) });
+    }
+
+    let stream = {
+        let mut stream = None;
+        for _ in 0..100 {
+            match UnixStream::connect(&path) {
+                Ok(s) => {
+                    stream = Some(s);
+                    break;
+                }
+                Err(_) => thread::sleep(Duration::from_millis(20)),
+            }
+        }
+        stream.expect("Failed to connect to the Unix socket")
+    };
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let (mut ws, _) = tungstenite::client("ws://localhost", stream).unwrap();
+
+    let msg = Msg {
+        id: 123,
+        body: Body::Request(rpc::Request::GetProperties),
+    };
+    let mut buf = Vec::with_capacity(16);
+    msg.serialize(&mut buf);
+    ws.write_message(Message::Binary(buf)).unwrap();
+
+    let res = match ws.read_message().unwrap() {
+        Message::Binary(buf) => Msg::deserialize(&mut Cursor::<&[u8]>::new(&buf)).unwrap(),
+        msg => panic!("Unexpected message: {:?}", msg),
+    };
+    assert_eq!(res.id, 123);
+    assert_eq!(
+        res.body,
+        Body::Response(rpc::Response::GetProperties(
+            minter.chain().get_properties()
+        ))
+    );
+    ws.close(None).unwrap();
+}
+
+fn get_unused_addr() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap()
+}
+
+fn connect_with_retry(addr: SocketAddr) -> TcpStream {
+    for _ in 0..100 {
+        match TcpStream::connect(addr) {
+            Ok(stream) => return stream,
+            Err(_) => thread::sleep(Duration::from_millis(20)),
+        }
+    }
+    panic!("Failed to connect to {}", addr);
+}
+
 fn create_uninit_state() -> (WsClient, mpsc::Receiver<Message>) {
     let (tx, rx) = mpsc::channel(8);
     (
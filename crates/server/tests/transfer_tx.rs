@@ -1,5 +1,6 @@
 use godcoin::{constants::*, prelude::*};
-use std::sync::Arc;
+use godcoin_server::client::WsClient;
+use std::{net::SocketAddr, sync::Arc};
 
 mod common;
 pub use common::*;
@@ -64,6 +65,146 @@ fn transfer_from_minter() {
     );
 }
 
+#[test]
+fn transfer_from_owner_uses_owner_script() {
+    let minter = TestMinter::new();
+    let info = minter.genesis_info();
+
+    let owner_id = info.owner_id;
+    let to_acc = minter.create_default_account(1, true);
+    let owner_bal = minter.chain().get_account(owner_id, &[]).unwrap().balance;
+    let supply = minter.chain().indexer().get_token_supply();
+    let amount = get_asset("10.00000 TEST");
+
+    let create_tx = |call_fn: u8, keys: &[&KeyPair]| {
+        let mut tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+            base: create_tx_header("1.00000 TEST"),
+            from: owner_id,
+            call_fn,
+            args: {
+                let mut args = vec![];
+                args.push_u64(to_acc.id);
+                args.push_asset(amount);
+                args
+            },
+            amount,
+            memo: vec![],
+        }));
+        for &key in keys {
+            tx.append_sign(key);
+        }
+        tx
+    };
+
+    // The owner wallet's permissions require 2 signatures
+    let res = minter.send_req(rpc::Request::Broadcast(create_tx(
+        1,
+        &[&info.wallet_keys[0]],
+    )));
+    match res {
+        Some(Err(net::ErrorKind::TxValidation(blockchain::TxErr::ScriptEval(eval_err)))) => {
+            assert_eq!(eval_err.err, script::EvalErrKind::ScriptRetFalse);
+        }
+        _ => panic!("Unexpected response {:?}", res),
+    }
+
+    // Calling the minting function of the owner script does not transfer or mint any funds, the
+    // amount is returned to the owner wallet
+    let keys = [&info.wallet_keys[3], &info.wallet_keys[0]];
+    let res = minter.send_req(rpc::Request::Broadcast(create_tx(0, &keys)));
+    assert_eq!(res, Some(Ok(rpc::Response::Broadcast)));
+    minter.produce_block().unwrap();
+
+    let chain = minter.chain();
+    // The fee is rewarded back to the owner wallet
+    assert_eq!(chain.get_account(owner_id, &[]).unwrap().balance, owner_bal);
+    assert_eq!(
+        chain.get_account(to_acc.id, &[]).unwrap().balance,
+        to_acc.balance
+    );
+    assert_eq!(chain.indexer().get_token_supply(), supply);
+
+    let res = minter.send_req(rpc::Request::Broadcast(create_tx(1, &keys)));
+    assert_eq!(res, Some(Ok(rpc::Response::Broadcast)));
+    minter.produce_block().unwrap();
+
+    let chain = minter.chain();
+    assert_eq!(
+        chain.get_account(owner_id, &[]).unwrap().balance,
+        owner_bal.checked_sub(amount).unwrap()
+    );
+    assert_eq!(
+        chain.get_account(to_acc.id, &[]).unwrap().balance,
+        to_acc.balance.checked_add(amount).unwrap()
+    );
+    assert_eq!(chain.indexer().get_token_supply(), supply);
+}
+
+#[test]
+fn get_account_info_includes_pending_transfer() {
+    let minter = TestMinter::new();
+    let (tx, _rx) = futures::channel::mpsc::channel(8);
+    let mut state = WsClient::new(SocketAddr::from(([127, 0, 0, 1], 7777)), tx);
+    let mut send_req = |req: rpc::Request| {
+        let msg = minter.send_msg(
+            &mut state,
+            Msg {
+                id: 0,
+                body: Body::Request(req),
+            },
+        );
+        msg.unwrap().body
+    };
+
+    let from_acc = minter.genesis_info().owner_id;
+    let from_bal = minter.chain().get_account(from_acc, &[]).unwrap().balance;
+    let to_acc = minter.create_default_account(1, true);
+    let from_bal = from_bal.checked_sub(to_acc.balance).unwrap();
+    let amount = get_asset("1.00000 TEST");
+    let fee = get_asset("1.00000 TEST");
+
+    let tx = {
+        let mut tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+            base: create_tx_header(&fee.to_string()),
+            from: from_acc,
+            call_fn: 1,
+            args: {
+                let mut args = vec![];
+                args.push_u64(to_acc.id);
+                args.push_asset(amount);
+                args
+            },
+            amount,
+            memo: vec![],
+        }));
+        tx.append_sign(&minter.genesis_info().wallet_keys[3]);
+        tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+        tx
+    };
+    let res = send_req(rpc::Request::Broadcast(tx));
+    assert_eq!(res, Body::Response(rpc::Response::Broadcast));
+
+    // The transfer is reflected before a block is produced
+    let balance = match send_req(rpc::Request::GetAccountInfo(from_acc)) {
+        Body::Response(rpc::Response::GetAccountInfo(info)) => info.account.balance,
+        unexp @ _ => panic!("Expected GetAccountInfo response: {:?}", unexp),
+    };
+    assert_eq!(
+        balance,
+        from_bal
+            .checked_sub(fee)
+            .unwrap()
+            .checked_sub(amount)
+            .unwrap()
+    );
+
+    let balance = match send_req(rpc::Request::GetAccountInfo(to_acc.id)) {
+        Body::Response(rpc::Response::GetAccountInfo(info)) => info.account.balance,
+        unexp @ _ => panic!("Expected GetAccountInfo response: {:?}", unexp),
+    };
+    assert_eq!(balance, to_acc.balance.checked_add(amount).unwrap());
+}
+
 #[test]
 fn fail_transfer_to_nonexistent_account() {
     let minter = TestMinter::new();
@@ -449,7 +590,7 @@ fn memo_too_large() {
     assert_eq!(
         res,
         Some(Err(net::ErrorKind::TxValidation(
-            blockchain::TxErr::TxTooLarge
+            blockchain::TxErr::MemoTooLarge
         )))
     );
     minter.produce_block().unwrap();
@@ -683,7 +824,7 @@ fn net_fee_dynamic_increase() {
                 amount: Asset::default(),
                 memo: vec![],
             }));
-            tx.append_sign(&key);
+            tx.append_sign(key);
             tx
         };
 
@@ -1181,6 +1322,7 @@ fn destroyed_acc_unused_funds_goes_to_correct_acc() {
 
         let log = &receipts[0].log;
         let expected_log = vec![
+            LogEntry::Fee(get_asset("1.00000 TEST")),
             LogEntry::Transfer(acc_3.id, get_asset("1.50000 TEST")),
             LogEntry::Destroy(acc_2.id),
             LogEntry::Transfer(acc_2.id, get_asset("0.50000 TEST")),
@@ -1188,3 +1330,268 @@ fn destroyed_acc_unused_funds_goes_to_correct_acc() {
         assert_eq!(log, &expected_log);
     }
 }
+
+#[test]
+fn log_entries_sum_to_balance_changes() {
+    let minter = TestMinter::new();
+
+    let create_account = |id: AccountId| -> (Account, KeyPair) {
+        let key = KeyPair::gen();
+        let mut acc = Account::create_default(
+            id,
+            Permissions {
+                threshold: 1,
+                keys: vec![key.0.clone()],
+            },
+        );
+        acc.balance = get_asset("4.00000 TEST");
+        (
+            minter.create_account(acc.clone(), "2.00000 TEST", true),
+            key,
+        )
+    };
+
+    let (acc_1, acc_1_key) = create_account(1);
+    let (acc_2, _) = create_account(2);
+
+    let tx = {
+        let mut tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+            base: create_tx_header("1.00000 TEST"),
+            from: acc_1.id,
+            call_fn: 0,
+            args: {
+                let mut args = vec![];
+                args.push_u64(acc_2.id);
+                args.push_asset(get_asset("1.50000 TEST"));
+                args
+            },
+            amount: get_asset("2.00000 TEST"),
+            memo: vec![],
+        }));
+        tx.append_sign(&acc_1_key);
+        tx
+    };
+    let res = minter.send_req(rpc::Request::Broadcast(tx));
+    assert_eq!(res, Some(Ok(rpc::Response::Broadcast)));
+    minter.produce_block().unwrap();
+
+    let chain = minter.chain();
+    let block = chain.get_chain_head();
+    let receipts = block.receipts();
+    assert_eq!(receipts.len(), 1);
+
+    let log = &receipts[0].log;
+    assert_eq!(
+        log,
+        &vec![
+            LogEntry::Fee(get_asset("1.00000 TEST")),
+            LogEntry::Transfer(acc_2.id, get_asset("1.50000 TEST")),
+            LogEntry::Transfer(acc_1.id, get_asset("0.50000 TEST")),
+        ]
+    );
+
+    // The sender is debited the fee and the transaction amount, any funds the script does not
+    // transfer elsewhere are returned to the sender through a transfer entry
+    let log_delta = |id: AccountId| -> i64 {
+        let mut delta = 0;
+        if id == acc_1.id {
+            delta -= get_asset("2.00000 TEST").amount;
+        }
+        for entry in log {
+            match entry {
+                LogEntry::Fee(fee) if id == acc_1.id => delta -= fee.amount,
+                LogEntry::Transfer(to_acc, amount) if *to_acc == id => delta += amount.amount,
+                _ => {}
+            }
+        }
+        delta
+    };
+
+    for acc in &[&acc_1, &acc_2] {
+        let cur_bal = chain.get_account(acc.id, &[]).unwrap().balance;
+        assert_eq!(cur_bal.amount - acc.balance.amount, log_delta(acc.id));
+    }
+}
+
+#[test]
+fn many_transfers_from_one_account_in_block() {
+    let minter = TestMinter::new();
+    let from_acc = minter.genesis_info().owner_id;
+    let to_acc = minter.create_default_account(1, true);
+    let amount = get_asset("1.00000 TEST");
+
+    const TX_COUNT: usize = 10;
+    for _ in 0..TX_COUNT {
+        let res = minter
+            .send_req(rpc::Request::GetAccountInfo(from_acc))
+            .unwrap();
+        let fee = match res {
+            Ok(rpc::Response::GetAccountInfo(info)) => info.total_fee().unwrap(),
+            unexp @ _ => panic!("Expected GetAccountInfo response: {:?}", unexp),
+        };
+
+        let mut tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+            base: create_tx_header(&fee.to_string()),
+            from: from_acc,
+            call_fn: 1,
+            args: {
+                let mut args = vec![];
+                args.push_u64(to_acc.id);
+                args.push_asset(amount);
+                args
+            },
+            amount,
+            memo: vec![],
+        }));
+        tx.append_sign(&minter.genesis_info().wallet_keys[3]);
+        tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+        let res = minter.send_req(rpc::Request::Broadcast(tx));
+        assert_eq!(res, Some(Ok(rpc::Response::Broadcast)));
+    }
+
+    // All transactions are verified in the same block sharing the decoded owner script
+    minter.produce_block().unwrap();
+
+    let chain = minter.chain();
+    let head = chain.get_chain_head();
+    assert_eq!(head.receipts().len(), TX_COUNT);
+
+    let expected_bal =
+        (0..TX_COUNT).fold(to_acc.balance, |bal, _| bal.checked_add(amount).unwrap());
+    let cur_bal = chain.get_account(to_acc.id, &[]).unwrap().balance;
+    assert_eq!(cur_bal, expected_bal);
+}
+
+#[test]
+fn spend_limit_within_window() {
+    let minter = TestMinter::new();
+    minter.chain().set_spend_limit(Some(blockchain::SpendLimit {
+        max_amount: get_asset("5.00000 TEST"),
+        window: 2,
+    }));
+
+    let from_acc = minter.genesis_info().owner_id;
+    let to_acc = minter.create_default_account(1, true);
+
+    let transfer = |amount: &str| {
+        let amount = get_asset(amount);
+        let mut tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+            base: create_tx_header("1.00000 TEST"),
+            from: from_acc,
+            call_fn: 1,
+            args: {
+                let mut args = vec![];
+                args.push_u64(to_acc.id);
+                args.push_asset(amount);
+                args
+            },
+            amount,
+            memo: vec![],
+        }));
+        tx.append_sign(&minter.genesis_info().wallet_keys[3]);
+        tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+        minter.send_req(rpc::Request::Broadcast(tx)).unwrap()
+    };
+    let rate_limited = Err(net::ErrorKind::TxValidation(blockchain::TxErr::RateLimited));
+
+    // Pending transfers count toward the limit
+    assert_eq!(transfer("3.00000 TEST"), Ok(rpc::Response::Broadcast));
+    assert_eq!(transfer("2.50000 TEST"), rate_limited);
+    assert_eq!(transfer("2.00000 TEST"), Ok(rpc::Response::Broadcast));
+    minter.produce_block().unwrap();
+
+    // Transfers in the most recent blocks count toward the limit
+    assert_eq!(transfer("0.00001 TEST"), rate_limited);
+    minter.produce_block().unwrap();
+    assert_eq!(transfer("0.00001 TEST"), rate_limited);
+    minter.produce_block().unwrap();
+
+    // The block with the transfers has left the window
+    assert_eq!(transfer("5.00000 TEST"), Ok(rpc::Response::Broadcast));
+    assert_eq!(transfer("0.00001 TEST"), rate_limited);
+
+    minter.chain().set_spend_limit(None);
+    assert_eq!(transfer("10.00000 TEST"), Ok(rpc::Response::Broadcast));
+    minter.produce_block().unwrap();
+}
+
+#[test]
+fn replace_pending_tx_with_higher_fee() {
+    let minter = TestMinter::new();
+
+    let from_acc = minter.genesis_info().owner_id;
+    let to_acc = minter.create_default_account(1, true);
+
+    let nonce = create_tx_header("0.00000 TEST").nonce;
+    let transfer = |fee: &str, amount: &str| {
+        let amount = get_asset(amount);
+        let mut tx = TxVariant::V0(TxVariantV0::TransferTx(TransferTx {
+            base: {
+                let mut base = create_tx_header(fee);
+                base.nonce = nonce;
+                base
+            },
+            from: from_acc,
+            call_fn: 1,
+            args: {
+                let mut args = vec![];
+                args.push_u64(to_acc.id);
+                args.push_asset(amount);
+                args
+            },
+            amount,
+            memo: vec![],
+        }));
+        tx.append_sign(&minter.genesis_info().wallet_keys[3]);
+        tx.append_sign(&minter.genesis_info().wallet_keys[0]);
+        tx
+    };
+
+    let original = transfer("1.00000 TEST", "1.00000 TEST");
+    let res = minter.send_req(rpc::Request::Broadcast(original.clone()));
+    assert_eq!(res, Some(Ok(rpc::Response::Broadcast)));
+
+    // A replacement must raise the fee by at least 10%
+    for fee in &["1.00000 TEST", "1.09999 TEST"] {
+        let underpriced = transfer(fee, "2.00000 TEST");
+        let res = minter.send_req(rpc::Request::Broadcast(underpriced));
+        assert_eq!(
+            res,
+            Some(Err(net::ErrorKind::TxValidation(
+                blockchain::TxErr::ReplacementUnderpriced
+            )))
+        );
+    }
+
+    // A replacement must be signed by the account
+    let mut unsigned = transfer("1.50000 TEST", "2.00000 TEST");
+    unsigned.sigs_mut().clear();
+    let res = minter.send_req(rpc::Request::Broadcast(unsigned));
+    assert_eq!(
+        res,
+        Some(Err(net::ErrorKind::TxValidation(
+            blockchain::TxErr::ReplacementUnauthorized
+        )))
+    );
+    assert_eq!(minter.minter().get_pending(), vec![original.clone()]);
+
+    let replacement = transfer("1.50000 TEST", "3.00000 TEST");
+    let res = minter.send_req(rpc::Request::Broadcast(replacement.clone()));
+    assert_eq!(res, Some(Ok(rpc::Response::Broadcast)));
+
+    // The replaced transaction cannot be broadcasted again
+    let res = minter.send_req(rpc::Request::Broadcast(original));
+    assert_eq!(
+        res,
+        Some(Err(net::ErrorKind::TxValidation(blockchain::TxErr::TxDupe)))
+    );
+
+    minter.produce_block().unwrap();
+    let chain = minter.chain();
+    let block = chain.get_block(chain.get_chain_height()).unwrap();
+    let txs: Vec<_> = block.receipts().iter().map(|r| r.tx.clone()).collect();
+    assert_eq!(txs, vec![replacement]);
+
+    let to_bal = chain.get_account(to_acc.id, &[]).unwrap().balance;
+    assert_eq!(to_bal, get_asset("7.00000 TEST"));
+}
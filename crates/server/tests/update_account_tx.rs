@@ -255,7 +255,30 @@ fn update_acc_tx_fail_script_too_large() {
     assert_eq!(
         res,
         Some(Err(net::ErrorKind::TxValidation(
-            blockchain::TxErr::TxTooLarge,
+            blockchain::TxErr::ScriptTooLarge,
+        )))
+    );
+}
+
+#[test]
+fn update_acc_tx_fail_account_too_large() {
+    let minter = TestMinter::new();
+
+    let tx = TxVariant::V0(TxVariantV0::UpdateAccountTx(UpdateAccountTx {
+        base: create_tx_header("1.00000 TEST"),
+        account_id: minter.genesis_info().owner_id,
+        new_script: Some(Script::new(vec![0; MAX_SCRIPT_BYTE_SIZE])),
+        new_permissions: Some(Permissions {
+            threshold: 1,
+            keys: (0..MAX_PERM_KEYS).map(|_| KeyPair::gen().0).collect(),
+        }),
+    }));
+
+    let res = minter.send_req(rpc::Request::Broadcast(tx));
+    assert_eq!(
+        res,
+        Some(Err(net::ErrorKind::TxValidation(
+            blockchain::TxErr::AccountTooLarge,
         )))
     );
 }